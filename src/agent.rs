@@ -1,5 +1,6 @@
-use crate::model::{Action, AgentConfig, AgentProvider};
+use crate::model::{Action, AgentConfig, ConversationTurn, PollResponse, Viewport};
 use dioxus::prelude::*;
+use uuid::Uuid;
 
 // ============================================================================
 // SERVER-ONLY MODULE - Agent Loop
@@ -7,10 +8,20 @@ use dioxus::prelude::*;
 #[cfg(feature = "server")]
 mod server_agent {
     use super::*;
-    use crate::server_state::{AGENT_CONFIG, AGENT_HISTORY};
+    use crate::notes::{append_run, ResearchRun};
+    use crate::model::{AgentProvider, Board, Bounds, RunLimits};
+    use crate::server_state::{record_action, AGENT_BOARD, AGENT_CONFIG, CONVERSATION, RESEARCH_RUN};
+    use crate::transcript::{store as transcript, RunStatus};
     use crate::tools::{
-        board::{make_draw_tool, make_wipe_tool},
-        ddg::make_ddg_tool,
+        board::{
+            describe_board, make_chart_tool, make_diagram_tool, make_draw_shapes_tool,
+            make_draw_tool, make_edit_shapes_tool, make_frame_tool, make_layer_tool, make_layout_tool, make_mind_map_tool,
+            make_table_tool, make_timeline_tool, make_wipe_tool, make_z_order_tool,
+        },
+        documents::make_documents_tool,
+        fetch::make_fetch_tool,
+        notes::make_note_tool,
+        search::make_search_tool,
     };
     use radkit::agent::LlmWorker;
     use radkit::models::providers::{
@@ -19,6 +30,13 @@ mod server_agent {
     use radkit::models::Thread;
     use std::time::Duration;
 
+    /// Earlier exchanges repeated in a follow-up prompt
+    const MAX_PROMPT_TURNS: usize = 8;
+    /// Longest earlier answer repeated in a follow-up prompt, in characters
+    const MAX_PROMPT_ANSWER_CHARS: usize = 1500;
+    /// Most shapes listed in a follow-up prompt's board summary
+    const MAX_DESCRIBED_SHAPES: usize = 200;
+
     pub fn start_agent_loop() {
         std::thread::spawn(|| {
             let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
@@ -43,29 +61,93 @@ mod server_agent {
                         config.provider, config.model, config.research_topic
                     );
 
-                    // Build prompt with system instructions
-                    let prompt = format!(
-                        "{}\n\nResearch topic: '{}'\n\nUse web_search to find information, then use draw_shape to create a visual presentation with multiple shapes representing key concepts. Use wipe_board first if the canvas has old content.",
-                        config.system_prompt,
-                        config.research_topic
-                    );
+                    if let Ok(mut run) = RESEARCH_RUN.lock() {
+                        *run = ResearchRun::new(&config.research_topic);
+                    }
+
+                    // Follow-ups continue the board's conversation; anything else starts a new one
+                    let conversation = match CONVERSATION.lock() {
+                        Ok(mut turns) if !config.follow_up => {
+                            turns.clear();
+                            Vec::new()
+                        }
+                        Ok(turns) => turns.clone(),
+                        Err(_) => Vec::new(),
+                    };
+                    let board = AGENT_BOARD.lock()
+                        .map(|b| b.clone())
+                        .unwrap_or_default();
+                    let prompt = if config.follow_up {
+                        follow_up_prompt(&config, &board, &conversation)
+                    } else {
+                        research_prompt(&config, &board.viewport.bounds())
+                    };
 
                     // Create tools from the tools/ module
-                    let search_tool = make_ddg_tool();
-                    let draw_tool = make_draw_tool();
-                    let wipe_tool = make_wipe_tool();
+                    let mut tools = vec![
+                        make_search_tool(),
+                        make_fetch_tool(),
+                        make_note_tool(),
+                        make_draw_tool(),
+                        make_draw_shapes_tool(),
+                        make_edit_shapes_tool(),
+                        make_wipe_tool(),
+                        make_frame_tool(),
+                        make_diagram_tool(),
+                        make_layout_tool(),
+                        make_mind_map_tool(),
+                        make_chart_tool(),
+                        make_timeline_tool(),
+                        make_table_tool(),
+                        make_z_order_tool(),
+                        make_layer_tool(),
+                    ];
+                    if !config.documents_dir.trim().is_empty() {
+                        tools.push(make_documents_tool());
+                    }
 
-                    // Run with appropriate provider
-                    let result = run_with_provider(&config, &prompt, search_tool, draw_tool, wipe_tool).await;
+                    transcript::begin(
+                        &config.research_topic,
+                        config.provider.clone(),
+                        &config.model,
+                        config.limits.clone(),
+                        &prompt,
+                    );
 
-                    match result {
-                        Ok(response) => println!("Agent: Cycle finished. Response: {}", response),
-                        Err(e) => println!("Agent: Error in cycle: {:?}", e),
+                    // Run with appropriate provider; a run at its limits still ends with an answer
+                    let (answer, run) = match run_with_provider(&config, &prompt, tools).await {
+                        Ok(response) => {
+                            println!("Agent: Cycle finished. Response: {}", response);
+                            let run = transcript::finish(Ok(&response)).await;
+                            let answer = match run.as_ref().map(|r| &r.status) {
+                                Some(RunStatus::LimitReached(reason)) => {
+                                    format!("{}\n\n*The run stopped at {}.*", response, reason)
+                                }
+                                _ => response,
+                            };
+                            (answer, run)
+                        }
+                        Err(e) => {
+                            let error = format!("{:?}", e);
+                            println!("Agent: Cycle stopped: {}", error);
+                            let answer = format!("*The run stopped early: {}*", error);
+                            let run = transcript::finish(Err(RunStatus::Failed(error))).await;
+                            (answer, run)
+                        }
+                    };
+                    save_notes(answer.clone());
+                    if let Ok(mut turns) = CONVERSATION.lock() {
+                        turns.push(ConversationTurn {
+                            instruction: config.research_topic.clone(),
+                            answer,
+                            run,
+                        });
                     }
 
                     // Clear topic after processing so we don't repeat
                     if let Ok(mut c) = AGENT_CONFIG.lock() {
                         c.research_topic.clear();
+                        c.follow_up = false;
                     }
 
                     println!("Agent: Waiting for next research topic...");
@@ -75,12 +157,123 @@ mod server_agent {
         });
     }
 
+    fn documents_hint(config: &AgentConfig) -> &'static str {
+        if config.documents_dir.trim().is_empty() {
+            ""
+        } else {
+            "\n\nThe team's own notes and papers are searchable with search_documents; check them as well as the web and cite file paths for what you use."
+        }
+    }
+
+    /// Tell the agent its budget so it can plan its tool use
+    fn limits_hint(config: &AgentConfig) -> String {
+        let limits = &config.limits;
+        let mut parts = Vec::new();
+        if limits.max_tool_calls > 0 {
+            parts.push(format!("{} tool calls", limits.max_tool_calls));
+        }
+        if limits.max_iterations > 0 {
+            parts.push(format!("{} model turns", limits.max_iterations));
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n\nThis run is limited to {}; once it reaches a limit your tool calls are refused and you get one last turn to answer, so batch your drawing and leave room to finish.",
+                parts.join(" and ")
+            )
+        }
+    }
+
+    /// Prompt for a fresh research run on `config.research_topic`
+    fn research_prompt(config: &AgentConfig, visible: &Bounds) -> String {
+        format!(
+            "{}\n\nResearch topic: '{}'\n\nThe canvas is unbounded. The user is currently viewing the region x={:.0}..{:.0}, y={:.0}..{:.0} (world coordinates); place new shapes inside it.\n\nUse web_search to find sources and fetch_page to read the most relevant ones, then create a visual presentation with multiple shapes representing key concepts, drawing them together with draw_shapes rather than one draw_shape call each. Cite the pages behind each fact by passing their url, title and a short supporting quote as the shape's sources. Group related shapes into titled sections with create_frame. For flowcharts and other graphs, describe them in Mermaid or DOT and use draw_diagram. Summarize hierarchical findings (topic, subtopics, facts) with draw_mind_map and show numbers with draw_chart. Put dated events on a draw_timeline and comparisons in a draw_table. Record what you learn with add_note as you go, and finish with a written summary of your findings. Use auto_layout to tidy the board. Use wipe_board first if the canvas has old content.{}{}",
+            config.system_prompt,
+            config.research_topic,
+            visible.min_x, visible.max_x, visible.min_y, visible.max_y,
+            documents_hint(config),
+            limits_hint(config)
+        )
+    }
+
+    /// Prompt for a follow-up instruction: the earlier exchanges, what is on
+    /// the board now and the new instruction
+    fn follow_up_prompt(config: &AgentConfig, board: &Board, conversation: &[ConversationTurn]) -> String {
+        let mut history = String::new();
+        let skipped = conversation.len().saturating_sub(MAX_PROMPT_TURNS);
+        if skipped > 0 {
+            history.push_str(&format!("({} earlier exchanges omitted)\n", skipped));
+        }
+        for turn in &conversation[skipped..] {
+            let mut answer: String = turn.answer.chars().take(MAX_PROMPT_ANSWER_CHARS).collect();
+            if answer.len() < turn.answer.len() {
+                answer.push_str("...");
+            }
+            history.push_str(&format!("User: {}\nYou: {}\n\n", turn.instruction, answer.trim()));
+        }
+        if history.is_empty() {
+            history.push_str("(no earlier exchanges in this session)\n");
+        }
+        let visible = board.viewport.bounds();
+        format!(
+            "{}\n\nYou are working on an existing board together with the user.\n\nConversation so far:\n{}\nCurrent board:\n{}\nThe canvas is unbounded. The user is currently viewing the region x={:.0}..{:.0}, y={:.0}..{:.0} (world coordinates).\n\nNew instruction: '{}'\n\nCarry out the instruction by building on what is already drawn. Change existing shapes with edit_shapes using the ids above instead of redrawing them, and place new shapes next to the ones they relate to. Do not use wipe_board unless the user asks to start over. Research with web_search and fetch_page when the instruction needs new facts, cite sources on new shapes, record what you learn with add_note, and finish with a short summary of what you changed.{}{}",
+            config.system_prompt,
+            history,
+            describe_board(board, MAX_DESCRIBED_SHAPES),
+            visible.min_x, visible.max_x, visible.min_y, visible.max_y,
+            config.research_topic,
+            documents_hint(config),
+            limits_hint(config)
+        )
+    }
+
+    /// Append the finished run (answer, findings and sources, including the
+    /// ones cited on the board) to the board's notes
+    fn save_notes(answer: String) {
+        let Ok(mut run) = RESEARCH_RUN.lock().map(|mut r| std::mem::take(&mut *r)) else {
+            return;
+        };
+        let Ok(board) = AGENT_BOARD.lock().map(|b| b.clone()) else {
+            return;
+        };
+        for source in board.bibliography() {
+            run.add_source(source.clone());
+        }
+        run.answer = answer;
+        if run.is_empty() {
+            return;
+        }
+        record_action(Action::SetNotes(append_run(&board.notes, &run)));
+    }
+
+    /// Model requests the worker may make: the turn limit, then the last turn
+    /// to answer and the reply that ends a run still calling tools after it
+    fn worker_turns(limits: &RunLimits) -> usize {
+        match limits.max_iterations {
+            0 => usize::MAX,
+            turns => turns.saturating_add(2),
+        }
+    }
+
+    /// Build an LlmWorker for `$llm` with every tool attached and run it on
+    /// `$thread`, recording each model reply in the transcript and holding
+    /// the run to `$limits`
+    macro_rules! run_worker {
+        ($llm:expr, $tools:expr, $thread:expr, $limits:expr) => {{
+            let mut builder = LlmWorker::<String>::builder(transcript::RecordedLlm::new($llm))
+                .with_max_iterations(worker_turns($limits));
+            for tool in $tools {
+                builder = builder.with_tool(tool);
+            }
+            builder.build().run($thread).await
+        }};
+    }
+
     async fn run_with_provider(
         config: &AgentConfig,
         prompt: &str,
-        search_tool: radkit::tools::FunctionTool,
-        draw_tool: radkit::tools::FunctionTool,
-        wipe_tool: radkit::tools::FunctionTool,
+        tools: Vec<radkit::tools::FunctionTool>,
     ) -> Result<String, radkit::errors::AgentError> {
         let thread = Thread::from_user(prompt);
 
@@ -88,68 +281,32 @@ mod server_agent {
             AgentProvider::OpenAI => {
                 std::env::set_var("OPENAI_API_KEY", &config.api_key);
                 let llm = OpenAILlm::from_env(&config.model)?;
-                LlmWorker::<String>::builder(llm)
-                    .with_tool(search_tool)
-                    .with_tool(draw_tool)
-                    .with_tool(wipe_tool)
-                    .build()
-                    .run(thread)
-                    .await
+                run_worker!(llm, tools, thread, &config.limits)
             }
             AgentProvider::Anthropic => {
                 std::env::set_var("ANTHROPIC_API_KEY", &config.api_key);
                 let llm = AnthropicLlm::from_env(&config.model)?;
-                LlmWorker::<String>::builder(llm)
-                    .with_tool(search_tool)
-                    .with_tool(draw_tool)
-                    .with_tool(wipe_tool)
-                    .build()
-                    .run(thread)
-                    .await
+                run_worker!(llm, tools, thread, &config.limits)
             }
             AgentProvider::OpenRouter => {
                 std::env::set_var("OPENROUTER_API_KEY", &config.api_key);
                 let llm = OpenRouterLlm::from_env(&config.model)?;
-                LlmWorker::<String>::builder(llm)
-                    .with_tool(search_tool)
-                    .with_tool(draw_tool)
-                    .with_tool(wipe_tool)
-                    .build()
-                    .run(thread)
-                    .await
+                run_worker!(llm, tools, thread, &config.limits)
             }
             AgentProvider::Gemini => {
                 std::env::set_var("GEMINI_API_KEY", &config.api_key);
                 let llm = GeminiLlm::from_env(&config.model)?;
-                LlmWorker::<String>::builder(llm)
-                    .with_tool(search_tool)
-                    .with_tool(draw_tool)
-                    .with_tool(wipe_tool)
-                    .build()
-                    .run(thread)
-                    .await
+                run_worker!(llm, tools, thread, &config.limits)
             }
             AgentProvider::Grok => {
                 std::env::set_var("Grok_API_KEY", &config.api_key);
                 let llm = GrokLlm::from_env(&config.model)?;
-                LlmWorker::<String>::builder(llm)
-                    .with_tool(search_tool)
-                    .with_tool(draw_tool)
-                    .with_tool(wipe_tool)
-                    .build()
-                    .run(thread)
-                    .await
+                run_worker!(llm, tools, thread, &config.limits)
             }
             AgentProvider::DeepSeek => {
                 std::env::set_var("DEEPSEEK_API_KEY", &config.api_key);
                 let llm = DeepSeekLlm::from_env(&config.model)?;
-                LlmWorker::<String>::builder(llm)
-                    .with_tool(search_tool)
-                    .with_tool(draw_tool)
-                    .with_tool(wipe_tool)
-                    .build()
-                    .run(thread)
-                    .await
+                run_worker!(llm, tools, thread, &config.limits)
             }
         }
     }
@@ -175,8 +332,8 @@ pub async fn update_agent_config(config: AgentConfig) -> Result<(), ServerFnErro
     {
         use crate::server_state::AGENT_CONFIG;
         println!(
-            "Server: Updating config - provider:{:?} model:{} topic:{}",
-            config.provider, config.model, config.research_topic
+            "Server: Updating config - provider:{:?} model:{} topic:{} search:{:?}",
+            config.provider, config.model, config.research_topic, config.search.backend
         );
         if let Ok(mut c) = AGENT_CONFIG.lock() {
             *c = config;
@@ -201,17 +358,60 @@ pub async fn get_agent_config() -> Result<AgentConfig, ServerFnError> {
     Err(ServerFnError::new("Not on server"))
 }
 
+/// The exchanges with the agent about the current board, oldest first
+#[server]
+pub async fn get_conversation() -> Result<Vec<ConversationTurn>, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        use crate::server_state::CONVERSATION;
+        Ok(CONVERSATION.lock().map(|c| c.clone()).unwrap_or_default())
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+#[server]
+pub async fn update_viewport(viewport: Viewport) -> Result<(), ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        use crate::server_state::AGENT_BOARD;
+        if let Ok(mut board) = AGENT_BOARD.lock() {
+            board.viewport = viewport;
+        }
+        Ok(())
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+#[server]
+pub async fn submit_action(client_id: Uuid, action: Action) -> Result<(), ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        use crate::server_state::record_client_action;
+        record_client_action(client_id, action);
+        Ok(())
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
 #[server]
-pub async fn poll_agent(last_index: usize) -> Result<Vec<Action>, ServerFnError> {
+pub async fn poll_agent(last_index: usize, client_id: Uuid) -> Result<PollResponse, ServerFnError> {
     #[cfg(feature = "server")]
     {
         use crate::server_state::AGENT_HISTORY;
         if let Ok(history) = AGENT_HISTORY.lock() {
             if last_index < history.len() {
-                return Ok(history[last_index..].to_vec());
+                let actions = history[last_index..]
+                    .iter()
+                    .filter(|entry| entry.origin != Some(client_id))
+                    .map(|entry| entry.action.clone())
+                    .collect();
+                return Ok(PollResponse { next_index: history.len(), actions });
             }
         }
-        Ok(Vec::new())
+        Ok(PollResponse { next_index: last_index, actions: Vec::new() })
     }
     #[cfg(not(feature = "server"))]
     Err(ServerFnError::new("Not on server"))
@@ -0,0 +1,254 @@
+use crate::formats::excalidraw;
+use crate::model::{Board, Bounds, Shape, ShapeType};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Marker identifying our payload among whatever else is on the system clipboard
+pub const CLIPBOARD_TYPE: &str = "agent-draw/clipboard";
+
+/// Offset applied to pasted and duplicated shapes so they don't hide the originals
+pub const PASTE_OFFSET: f64 = 20.0;
+
+/// Clipboard payload: a tagged list of shapes, so other tools can tell it apart from plain JSON
+#[derive(Serialize, Deserialize)]
+struct ClipboardPayload {
+    #[serde(rename = "type")]
+    kind: String,
+    shapes: Vec<Shape>,
+}
+
+/// Serialize shapes into the clipboard format
+pub fn to_clipboard_text(shapes: &[Shape]) -> String {
+    let payload = ClipboardPayload {
+        kind: CLIPBOARD_TYPE.to_string(),
+        shapes: shapes.to_vec(),
+    };
+    serde_json::to_string_pretty(&payload).unwrap_or_default()
+}
+
+/// Parse clipboard text into shapes.
+///
+/// Accepts our tagged payload, a bare JSON array of shapes, a single shape or
+/// Excalidraw's clipboard/scene JSON.
+/// Anything else is treated as plain text and becomes a text shape at (`x`, `y`).
+pub fn parse_clipboard_text(text: &str, x: f64, y: f64) -> Vec<Shape> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Vec::new();
+    }
+    if let Ok(value) = serde_json::from_str::<Value>(trimmed) {
+        let kind = value.get("type").and_then(Value::as_str).unwrap_or_default();
+        let shapes = match value {
+            Value::Object(_) if kind == excalidraw::CLIPBOARD_TYPE || kind == excalidraw::FILE_TYPE => {
+                excalidraw::import_json(trimmed).ok().map(|board| board.shapes)
+            }
+            Value::Object(ref map) if map.contains_key("shapes") => {
+                serde_json::from_value::<ClipboardPayload>(value).ok().map(|p| p.shapes)
+            }
+            Value::Array(_) => serde_json::from_value::<Vec<Shape>>(value).ok(),
+            Value::Object(_) => serde_json::from_value::<Shape>(value).ok().map(|s| vec![s]),
+            _ => None,
+        };
+        if let Some(shapes) = shapes.filter(|s| !s.is_empty()) {
+            return shapes;
+        }
+    }
+    vec![Shape::text(x, y, trimmed.to_string())]
+}
+
+/// Prepare shapes for insertion into `board`: fresh ids, shifted by `offset`,
+/// and pulled into view when they would otherwise land outside the visible area.
+/// Layers that don't exist on this board fall back to the base layer.
+pub fn prepare_paste(board: &Board, shapes: &[Shape], offset: f64) -> Vec<Shape> {
+    let Some(bounds) = shapes
+        .iter()
+        .map(Shape::bounds)
+        .reduce(|a, b| a.union(&b))
+    else {
+        return Vec::new();
+    };
+    let view = board.viewport.bounds();
+    let (dx, dy) = if overlaps(&bounds, &view) {
+        (offset, offset)
+    } else {
+        // Center the pasted content in the current view
+        (
+            view.min_x + view.width() / 2.0 - (bounds.min_x + bounds.width() / 2.0),
+            view.min_y + view.height() / 2.0 - (bounds.min_y + bounds.height() / 2.0),
+        )
+    };
+    let mut ids = HashMap::new();
+    let mut copies: Vec<Shape> = shapes
+        .iter()
+        .map(|shape| {
+            let mut copy = shape.duplicated(dx, dy);
+            if copy.layer.is_some_and(|id| board.layer(id).is_none()) {
+                copy.layer = None;
+            }
+            ids.insert(shape.id, copy.id);
+            copy
+        })
+        .collect();
+    // Arrows stay bound to the copies of their shapes; ends bound to
+    // shapes that weren't copied are left free
+    for copy in copies.iter_mut() {
+        if let ShapeType::Arrow { start, end, .. } = &mut copy.shape_type {
+            *start = start.and_then(|id| ids.get(&id).copied());
+            *end = end.and_then(|id| ids.get(&id).copied());
+        }
+    }
+    copies
+}
+
+fn overlaps(a: &Bounds, b: &Bounds) -> bool {
+    a.min_x < b.max_x && a.max_x > b.min_x && a.min_y < b.max_y && a.max_y > b.min_y
+}
+
+/// Write text to the system clipboard. Returns false when the browser refuses
+/// (no permission, insecure context), so callers can keep an in-app copy.
+pub async fn write_system_clipboard(text: String) -> bool {
+    let eval = document::eval(
+        r#"
+        const text = await dioxus.recv();
+        try {
+            await navigator.clipboard.writeText(text);
+            return true;
+        } catch (e) {
+            return false;
+        }
+        "#,
+    );
+    if eval.send(text).is_err() {
+        return false;
+    }
+    eval.join::<bool>().await.unwrap_or(false)
+}
+
+/// Read text from the system clipboard, if the browser allows it
+pub async fn read_system_clipboard() -> Option<String> {
+    document::eval(
+        r#"
+        try {
+            return await navigator.clipboard.readText();
+        } catch (e) {
+            return null;
+        }
+        "#,
+    )
+    .join::<Option<String>>()
+    .await
+    .ok()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Layer;
+
+    const FLOW: &str = include_str!("../tests/fixtures/excalidraw/flow.excalidraw");
+
+    fn rect(x: f64, y: f64) -> Shape {
+        Shape::new(ShapeType::Rectangle, x, y, 100.0, 60.0, "#fff".to_string())
+    }
+
+    fn labels(shapes: &[Shape]) -> Vec<Option<&str>> {
+        shapes.iter().map(|s| s.label.as_deref()).collect()
+    }
+
+    #[test]
+    fn our_payload_round_trips() {
+        let mut a = rect(10.0, 20.0);
+        a.label = Some("Start".to_string());
+        let shapes = vec![a, rect(200.0, 20.0)];
+        let text = to_clipboard_text(&shapes);
+        assert!(text.contains(CLIPBOARD_TYPE));
+        assert_eq!(parse_clipboard_text(&text, 0.0, 0.0), shapes);
+    }
+
+    #[test]
+    fn bare_arrays_and_single_shapes_are_accepted() {
+        let shapes = vec![rect(0.0, 0.0), rect(150.0, 0.0)];
+        let array = serde_json::to_string(&shapes).unwrap();
+        assert_eq!(parse_clipboard_text(&array, 0.0, 0.0), shapes);
+        let single = serde_json::to_string(&shapes[0]).unwrap();
+        assert_eq!(parse_clipboard_text(&single, 0.0, 0.0), vec![shapes[0].clone()]);
+    }
+
+    #[test]
+    fn excalidraw_clipboard_json_is_imported() {
+        let clipboard = FLOW.replacen(r#""type": "excalidraw""#, &format!(r#""type": "{}""#, excalidraw::CLIPBOARD_TYPE), 1);
+        assert_ne!(clipboard, FLOW);
+        assert_eq!(parse_clipboard_text(&clipboard, 0.0, 0.0).len(), 6);
+        assert_eq!(parse_clipboard_text(FLOW, 0.0, 0.0).len(), 6);
+    }
+
+    #[test]
+    fn other_text_becomes_a_text_shape() {
+        for text in ["Solar panels\nare cheap", r#"{"name": "not a shape"}"#, "[1, 2, 3]", "42", "[]"] {
+            let shapes = parse_clipboard_text(&format!("  {}  ", text), 30.0, 40.0);
+            assert_eq!(shapes.len(), 1, "{}", text);
+            assert!(matches!(shapes[0].shape_type, ShapeType::Text));
+            assert_eq!((shapes[0].x, shapes[0].y), (30.0, 40.0));
+            assert_eq!(labels(&shapes), vec![Some(text)]);
+        }
+        assert!(parse_clipboard_text(" \n ", 0.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn pasted_copies_get_fresh_ids_and_an_offset() {
+        let board = Board::default();
+        let shapes = vec![rect(10.0, 20.0), rect(200.0, 20.0)];
+        let copies = prepare_paste(&board, &shapes, PASTE_OFFSET);
+        assert_eq!(copies.len(), 2);
+        for (copy, original) in copies.iter().zip(&shapes) {
+            assert_ne!(copy.id, original.id);
+            assert_eq!((copy.x, copy.y), (original.x + PASTE_OFFSET, original.y + PASTE_OFFSET));
+        }
+        assert!(prepare_paste(&board, &[], PASTE_OFFSET).is_empty());
+    }
+
+    #[test]
+    fn arrows_stay_bound_to_the_copies() {
+        let (a, b, outside) = (rect(0.0, 0.0), rect(300.0, 0.0), rect(600.0, 0.0));
+        let bound = Shape::connector(&a, &b, "#000".to_string());
+        let half = Shape::connector(&a, &outside, "#000".to_string());
+        let copies = prepare_paste(&Board::default(), &[a, b, bound, half], PASTE_OFFSET);
+        let ends = |shape: &Shape| match shape.shape_type {
+            ShapeType::Arrow { start, end, .. } => (start, end),
+            _ => panic!("not an arrow"),
+        };
+        assert_eq!(ends(&copies[2]), (Some(copies[0].id), Some(copies[1].id)));
+        assert_eq!(ends(&copies[3]), (Some(copies[0].id), None));
+    }
+
+    #[test]
+    fn unknown_layers_fall_back_to_the_base_layer() {
+        let layer = Layer::new("Notes".to_string());
+        let board = Board {
+            layers: vec![layer.clone()],
+            ..Board::default()
+        };
+        let mut known = rect(0.0, 0.0);
+        known.layer = Some(layer.id);
+        let mut unknown = rect(0.0, 0.0);
+        unknown.layer = Some(uuid::Uuid::new_v4());
+        let copies = prepare_paste(&board, &[known, unknown], PASTE_OFFSET);
+        assert_eq!(copies[0].layer, Some(layer.id));
+        assert_eq!(copies[1].layer, None);
+    }
+
+    #[test]
+    fn off_screen_content_is_centred_in_view() {
+        let board = Board::default();
+        let view = board.viewport.bounds();
+        let copies = prepare_paste(&board, &[rect(5000.0, 5000.0), rect(5200.0, 5100.0)], PASTE_OFFSET);
+        let bounds = copies[0].bounds().union(&copies[1].bounds());
+        assert_eq!(bounds.min_x + bounds.width() / 2.0, view.min_x + view.width() / 2.0);
+        assert_eq!(bounds.min_y + bounds.height() / 2.0, view.min_y + view.height() / 2.0);
+        // The pasted shapes keep their arrangement
+        assert_eq!((copies[1].x - copies[0].x, copies[1].y - copies[0].y), (200.0, 100.0));
+    }
+}
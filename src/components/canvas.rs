@@ -0,0 +1,575 @@
+use crate::agent::update_viewport;
+use crate::components::shape::ShapeView;
+use crate::export::svg::{
+    citation_tooltip, FONT_FAMILY, FRAME_FILL, FRAME_STROKE, FRAME_TITLE_COLOR, FRAME_TITLE_HEIGHT,
+};
+use crate::model::{Action, Bounds, GroupKind, Viewport, CANVAS_HEIGHT, CANVAS_WIDTH};
+use crate::state::BoardState;
+use dioxus::html::input_data::MouseButton;
+use dioxus::prelude::*;
+use std::rc::Rc;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[cfg(target_arch = "wasm32")]
+use gloo_timers::future::sleep;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::sleep;
+
+/// Zoom multiplier for one wheel notch / zoom button press
+const ZOOM_STEP: f64 = 1.1;
+/// Padding (world units) kept around shapes when zooming to fit
+const FIT_PADDING: f64 = 40.0;
+
+const MINIMAP_WIDTH: f64 = 160.0;
+const MINIMAP_HEIGHT: f64 = 100.0;
+
+/// Smallest size a group can be resized to, in world units
+const MIN_GROUP_SIZE: f64 = 40.0;
+
+/// Screen-space rectangle of the canvas element: (left, top, width) in CSS pixels
+type CanvasRect = (f64, f64, f64);
+
+#[derive(Clone, Copy, PartialEq)]
+enum DragMode {
+    Move,
+    Resize,
+}
+
+/// An in-progress move/resize of a group or frame
+#[derive(Clone, Copy)]
+struct GroupDrag {
+    id: Uuid,
+    mode: DragMode,
+    /// World point where the drag started
+    start: (f64, f64),
+    /// Group bounds when the drag started
+    origin: Bounds,
+    /// Latest bounds, committed as a single action on release
+    current: Bounds,
+}
+
+impl GroupDrag {
+    fn bounds_at(&self, x: f64, y: f64) -> Bounds {
+        let (dx, dy) = (x - self.start.0, y - self.start.1);
+        match self.mode {
+            DragMode::Move => Bounds {
+                min_x: self.origin.min_x + dx,
+                min_y: self.origin.min_y + dy,
+                max_x: self.origin.max_x + dx,
+                max_y: self.origin.max_y + dy,
+            },
+            DragMode::Resize => Bounds {
+                max_x: (self.origin.max_x + dx).max(self.origin.min_x + MIN_GROUP_SIZE),
+                max_y: (self.origin.max_y + dy).max(self.origin.min_y + MIN_GROUP_SIZE),
+                ..self.origin
+            },
+        }
+    }
+}
+
+/// Convert a client (CSS pixel) point into world coordinates
+fn to_world(viewport: &Viewport, rect: CanvasRect, client_x: f64, client_y: f64) -> (f64, f64) {
+    let (left, top, width) = rect;
+    let scale = viewport.width() / width.max(1.0);
+    (
+        viewport.x + (client_x - left) * scale,
+        viewport.y + (client_y - top) * scale,
+    )
+}
+
+/// The infinite board canvas: renders shapes in world coordinates and handles
+/// wheel zoom, space/middle-button drag panning, zoom-to-fit and the minimap,
+/// plus selection and moving/resizing of groups and frames.
+#[component]
+pub fn Canvas() -> Element {
+    let mut state = use_context::<BoardState>();
+    let mut mounted = use_signal(|| None::<Rc<MountedData>>);
+    let mut canvas_rect = use_signal(|| None::<CanvasRect>);
+    let mut space_held = use_signal(|| false);
+    let mut pan_origin = use_signal(|| None::<(f64, f64)>);
+    let mut sync_generation = use_signal(|| 0u64);
+    let mut group_drag = use_signal(|| None::<GroupDrag>);
+
+    // Measure the canvas element so pointer positions can be mapped to world coordinates
+    let refresh_rect = move || {
+        if let Some(element) = mounted() {
+            spawn(async move {
+                if let Ok(rect) = element.get_client_rect().await {
+                    canvas_rect.set(Some((rect.origin.x, rect.origin.y, rect.size.width)));
+                }
+            });
+        }
+    };
+
+    // Push the viewport to the server once the user stops moving it
+    let mut sync_viewport = move || {
+        let generation = sync_generation() + 1;
+        sync_generation.set(generation);
+        spawn(async move {
+            sleep(Duration::from_millis(300)).await;
+            if sync_generation() == generation {
+                let viewport = state.board.read().viewport;
+                let _ = update_viewport(viewport).await;
+            }
+        });
+    };
+
+    let mut zoom_centered = move |factor: f64| {
+        let mut board = state.board.write();
+        let center = board.viewport.bounds();
+        board.viewport.zoom_at(
+            factor,
+            center.min_x + center.width() / 2.0,
+            center.min_y + center.height() / 2.0,
+        );
+        drop(board);
+        sync_viewport();
+    };
+
+    let mut zoom_to_fit = move || {
+        let bounds = state.board.read().bounds();
+        state.board.write().viewport = match bounds {
+            Some(bounds) => Viewport::fit(&bounds, FIT_PADDING),
+            None => Viewport::default(),
+        };
+        sync_viewport();
+    };
+
+    // Clicking a grouped shape selects its whole group; shift toggles shapes in/out
+    let mut select_shape = move |id: Uuid, additive: bool| {
+        let group = state
+            .board
+            .read()
+            .group_of(id)
+            .map(|g| (g.id, g.members.clone()));
+        let ids = group.as_ref().map(|(_, m)| m.clone()).unwrap_or_else(|| vec![id]);
+        if additive {
+            let mut selection = state.selection.write();
+            for id in ids {
+                match selection.iter().position(|s| *s == id) {
+                    Some(pos) => {
+                        selection.remove(pos);
+                    }
+                    None => selection.push(id),
+                }
+            }
+            state.selected_group.set(None);
+        } else {
+            state.selection.set(ids);
+            state.selected_group.set(group.map(|(gid, _)| gid));
+        }
+    };
+
+    let mut select_group = move |id: Uuid| {
+        let members = state
+            .board
+            .read()
+            .group(id)
+            .map(|g| g.members.clone())
+            .unwrap_or_default();
+        state.selection.set(members);
+        state.selected_group.set(Some(id));
+    };
+
+    let mut start_group_drag = move |id: Uuid, mode: DragMode, e: MouseEvent| {
+        if space_held() {
+            return;
+        }
+        e.stop_propagation();
+        let Some(rect) = canvas_rect() else { return };
+        let Some(origin) = state.board.read().group_bounds(id) else { return };
+        let point = e.client_coordinates();
+        let start = to_world(&state.board.read().viewport, rect, point.x, point.y);
+        select_group(id);
+        group_drag.set(Some(GroupDrag {
+            id,
+            mode,
+            start,
+            origin,
+            current: origin,
+        }));
+    };
+
+    let mut end_drag = move || {
+        if pan_origin().is_some() {
+            pan_origin.set(None);
+            sync_viewport();
+        }
+        if let Some(drag) = group_drag() {
+            group_drag.set(None);
+            if drag.current != drag.origin {
+                // Put the preview back first so undo returns to where the drag started
+                state.board.write().apply(&Action::TransformGroup {
+                    id: drag.id,
+                    bounds: drag.origin,
+                });
+                state.dispatch(Action::TransformGroup {
+                    id: drag.id,
+                    bounds: drag.current,
+                });
+            }
+        }
+    };
+
+    let board = state.board.read();
+    let bibliography = board.bibliography();
+    let viewport = board.viewport;
+    let visible = viewport.bounds();
+    let zoom_percent = (viewport.zoom * 100.0).round();
+    let cursor = if pan_origin().is_some() {
+        "grabbing"
+    } else if space_held() {
+        "grab"
+    } else {
+        "default"
+    };
+    // Overlays keep a constant on-screen size regardless of zoom
+    let handle_size = 10.0 / viewport.zoom;
+    let selection = state.selection.read();
+    let selected_group = state.selected_group.read().and_then(|id| {
+        let group = board.group(id)?;
+        let bounds = board.group_bounds(id)?;
+        Some((id, group.kind.clone(), bounds))
+    });
+
+    rsx! {
+        div {
+            class: "relative outline-none",
+            tabindex: "0",
+            onkeydown: move |e| {
+                let modifiers = e.modifiers();
+                let shortcut = modifiers.ctrl() || modifiers.meta();
+                match e.code() {
+                    Code::Space => {
+                        e.prevent_default();
+                        space_held.set(true);
+                    }
+                    Code::KeyC if shortcut => state.copy_selection(),
+                    Code::KeyX if shortcut => state.cut_selection(),
+                    Code::KeyV if shortcut => state.paste(),
+                    Code::KeyZ if shortcut && modifiers.shift() => state.redo(),
+                    Code::KeyZ if shortcut => {
+                        e.prevent_default();
+                        state.undo();
+                    }
+                    Code::KeyY if shortcut => state.redo(),
+                    Code::KeyD if shortcut => {
+                        // Browsers bookmark the page on Ctrl+D
+                        e.prevent_default();
+                        state.duplicate_selection();
+                    }
+                    Code::Delete | Code::Backspace => state.delete_selection(),
+                    _ => {}
+                }
+            },
+            onkeyup: move |e| {
+                if e.code() == Code::Space {
+                    space_held.set(false);
+                }
+            },
+            svg {
+                id: "board-svg",
+                class: "w-full block",
+                view_box: "{viewport.view_box()}",
+                style: "background: linear-gradient(135deg, #fafafa 0%, #f0f0f0 100%); cursor: {cursor};",
+                onmounted: move |e| {
+                    mounted.set(Some(e.data()));
+                    refresh_rect();
+                },
+                onmouseenter: move |_| refresh_rect(),
+                onwheel: move |e| {
+                    e.prevent_default();
+                    let Some(rect) = canvas_rect() else { return };
+                    let dy = e.delta().strip_units().y;
+                    let factor = if dy < 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+                    let point = e.client_coordinates();
+                    let mut board = state.board.write();
+                    let (wx, wy) = to_world(&board.viewport, rect, point.x, point.y);
+                    board.viewport.zoom_at(factor, wx, wy);
+                    drop(board);
+                    sync_viewport();
+                },
+                onmousedown: move |e| {
+                    let middle = e.trigger_button() == Some(MouseButton::Auxiliary);
+                    if space_held() || middle {
+                        e.prevent_default();
+                        let point = e.client_coordinates();
+                        pan_origin.set(Some((point.x, point.y)));
+                    }
+                },
+                onmousemove: move |e| {
+                    let Some(rect) = canvas_rect() else { return };
+                    let point = e.client_coordinates();
+                    if let Some((last_x, last_y)) = pan_origin() {
+                        let mut board = state.board.write();
+                        let scale = board.viewport.width() / rect.2.max(1.0);
+                        board.viewport.pan_by(-(point.x - last_x) * scale, -(point.y - last_y) * scale);
+                        pan_origin.set(Some((point.x, point.y)));
+                    } else if let Some(mut drag) = group_drag() {
+                        // Preview locally; the final bounds are recorded once on release
+                        let (wx, wy) = to_world(&state.board.read().viewport, rect, point.x, point.y);
+                        drag.current = drag.bounds_at(wx, wy);
+                        state.board.write().apply(&Action::TransformGroup {
+                            id: drag.id,
+                            bounds: drag.current,
+                        });
+                        group_drag.set(Some(drag));
+                    }
+                },
+                onmouseup: move |_| end_drag(),
+                onmouseleave: move |_| end_drag(),
+
+                // Dot grid covering the visible region so panning is visible on an empty board
+                defs {
+                    pattern {
+                        id: "board-grid",
+                        width: "20",
+                        height: "20",
+                        pattern_units: "userSpaceOnUse",
+                        circle { cx: "1", cy: "1", r: "1", fill: "#d4d4d4" }
+                    }
+                }
+                rect {
+                    x: "{visible.min_x}",
+                    y: "{visible.min_y}",
+                    width: "{visible.width()}",
+                    height: "{visible.height()}",
+                    fill: "url(#board-grid)",
+                    onclick: move |_| {
+                        if !space_held() {
+                            state.clear_selection();
+                        }
+                    },
+                }
+
+                // Frames sit behind shapes; their empty area and title strip are draggable
+                for frame in board.groups.iter().filter(|g| g.kind == GroupKind::Frame) {
+                    {
+                        let id = frame.id;
+                        rsx! {
+                            g { key: "{id}",
+                                rect {
+                                    x: "{frame.x}",
+                                    y: "{frame.y - FRAME_TITLE_HEIGHT}",
+                                    width: "{frame.width}",
+                                    height: "{FRAME_TITLE_HEIGHT}",
+                                    fill: "transparent",
+                                    cursor: "move",
+                                    onmousedown: move |e| start_group_drag(id, DragMode::Move, e),
+                                }
+                                text {
+                                    x: "{frame.x + 4.0}",
+                                    y: "{frame.y - 7.0}",
+                                    font_family: FONT_FAMILY,
+                                    font_size: "13",
+                                    font_weight: "600",
+                                    fill: FRAME_TITLE_COLOR,
+                                    pointer_events: "none",
+                                    "{frame.title}"
+                                }
+                                rect {
+                                    x: "{frame.x}",
+                                    y: "{frame.y}",
+                                    width: "{frame.width}",
+                                    height: "{frame.height}",
+                                    rx: "8",
+                                    fill: FRAME_FILL,
+                                    stroke: FRAME_STROKE,
+                                    stroke_width: "1.5",
+                                    onmousedown: move |e| start_group_drag(id, DragMode::Move, e),
+                                    onclick: move |e| e.stop_propagation(),
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for shape in board.render_order() {
+                    {
+                        let id = shape.id;
+                        let locked = board.is_locked(shape);
+                        rsx! {
+                            ShapeView {
+                                key: "{id}",
+                                shape: shape.clone(),
+                                citations: shape.citation_numbers(&bibliography),
+                                citation_note: citation_tooltip(shape, &bibliography),
+                                onselect: move |e: MouseEvent| {
+                                    if !space_held() && !locked {
+                                        select_shape(id, e.modifiers().shift());
+                                    }
+                                },
+                            }
+                        }
+                    }
+                }
+
+                // Selection outlines
+                for bounds in board.render_order().into_iter().filter(|s| selection.contains(&s.id)).map(|s| s.bounds()) {
+                    rect {
+                        x: "{bounds.min_x - 4.0}",
+                        y: "{bounds.min_y - 4.0}",
+                        width: "{bounds.width() + 8.0}",
+                        height: "{bounds.height() + 8.0}",
+                        fill: "none",
+                        stroke: "#3b82f6",
+                        stroke_width: "1.5",
+                        stroke_dasharray: "4 3",
+                        vector_effect: "non-scaling-stroke",
+                        pointer_events: "none",
+                    }
+                }
+
+                // Selected group: draggable outline (plain groups) and a resize handle
+                if let Some((id, kind, bounds)) = selected_group {
+                    if kind == GroupKind::Group {
+                        rect {
+                            x: "{bounds.min_x - 8.0}",
+                            y: "{bounds.min_y - 8.0}",
+                            width: "{bounds.width() + 16.0}",
+                            height: "{bounds.height() + 16.0}",
+                            fill: "none",
+                            stroke: "#3b82f6",
+                            stroke_width: "8",
+                            stroke_opacity: "0",
+                            cursor: "move",
+                            pointer_events: "stroke",
+                            onmousedown: move |e| start_group_drag(id, DragMode::Move, e),
+                        }
+                    }
+                    rect {
+                        x: "{bounds.min_x}",
+                        y: "{bounds.min_y}",
+                        width: "{bounds.width()}",
+                        height: "{bounds.height()}",
+                        fill: "none",
+                        stroke: "#2563eb",
+                        stroke_width: "1",
+                        vector_effect: "non-scaling-stroke",
+                        pointer_events: "none",
+                    }
+                    rect {
+                        x: "{bounds.max_x - handle_size / 2.0}",
+                        y: "{bounds.max_y - handle_size / 2.0}",
+                        width: "{handle_size}",
+                        height: "{handle_size}",
+                        fill: "white",
+                        stroke: "#2563eb",
+                        stroke_width: "1.5",
+                        vector_effect: "non-scaling-stroke",
+                        cursor: "nwse-resize",
+                        onmousedown: move |e| start_group_drag(id, DragMode::Resize, e),
+                    }
+                }
+            }
+
+            // Minimap
+            div { class: "absolute bottom-3 left-3",
+                Minimap {}
+            }
+
+            // Zoom controls
+            div { class: "absolute bottom-3 right-3 flex items-center gap-1 bg-gray-800/80 text-white rounded-lg px-2 py-1 text-sm",
+                button {
+                    class: "px-2 hover:text-blue-300",
+                    title: "Zoom out",
+                    onclick: move |_| zoom_centered(1.0 / ZOOM_STEP),
+                    "−"
+                }
+                span { class: "w-12 text-center tabular-nums", "{zoom_percent}%" }
+                button {
+                    class: "px-2 hover:text-blue-300",
+                    title: "Zoom in",
+                    onclick: move |_| zoom_centered(ZOOM_STEP),
+                    "+"
+                }
+                button {
+                    class: "px-2 hover:text-blue-300",
+                    title: "Zoom to fit all shapes",
+                    onclick: move |_| zoom_to_fit(),
+                    "⤢ Fit"
+                }
+            }
+        }
+    }
+}
+
+/// Overview of the whole board with the current viewport outlined.
+/// Clicking re-centers the canvas on that point.
+#[component]
+fn Minimap() -> Element {
+    let mut state = use_context::<BoardState>();
+
+    let board = state.board.read();
+    let visible = board.viewport.bounds();
+    let content = board
+        .bounds()
+        .map(|b| b.union(&visible))
+        .unwrap_or(visible)
+        .padded(20.0);
+
+    // Grow the region to the minimap's aspect ratio so the mapping is uniform
+    let scale = (content.width() / MINIMAP_WIDTH).max(content.height() / MINIMAP_HEIGHT);
+    let cx = content.min_x + content.width() / 2.0;
+    let cy = content.min_y + content.height() / 2.0;
+    let region = Bounds {
+        min_x: cx - MINIMAP_WIDTH * scale / 2.0,
+        min_y: cy - MINIMAP_HEIGHT * scale / 2.0,
+        max_x: cx + MINIMAP_WIDTH * scale / 2.0,
+        max_y: cy + MINIMAP_HEIGHT * scale / 2.0,
+    };
+    let view_box = format!(
+        "{} {} {} {}",
+        region.min_x,
+        region.min_y,
+        region.width(),
+        region.height()
+    );
+    let outline = CANVAS_WIDTH.max(CANVAS_HEIGHT) * scale / 100.0;
+
+    rsx! {
+        svg {
+            class: "rounded-md border border-gray-300 bg-white/90 shadow cursor-pointer",
+            width: "{MINIMAP_WIDTH}",
+            height: "{MINIMAP_HEIGHT}",
+            view_box: "{view_box}",
+            onclick: move |e| {
+                let point = e.element_coordinates();
+                state.board.write().viewport.center_on(
+                    region.min_x + point.x * scale,
+                    region.min_y + point.y * scale,
+                );
+                let viewport = state.board.read().viewport;
+                spawn(async move {
+                    let _ = update_viewport(viewport).await;
+                });
+            },
+            for shape in board.shapes.iter() {
+                {
+                    let b = shape.bounds();
+                    rsx! {
+                        rect {
+                            x: "{b.min_x}",
+                            y: "{b.min_y}",
+                            width: "{b.width().max(outline)}",
+                            height: "{b.height().max(outline)}",
+                            fill: "{shape.color}",
+                            pointer_events: "none",
+                        }
+                    }
+                }
+            }
+            rect {
+                x: "{visible.min_x}",
+                y: "{visible.min_y}",
+                width: "{visible.width()}",
+                height: "{visible.height()}",
+                fill: "rgba(59, 130, 246, 0.1)",
+                stroke: "#3b82f6",
+                stroke_width: "{outline}",
+                pointer_events: "none",
+            }
+        }
+    }
+}
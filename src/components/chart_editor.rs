@@ -0,0 +1,67 @@
+use crate::model::{Action, ChartData, ChartKind, Shape, ShapeType};
+use crate::state::BoardState;
+use dioxus::prelude::*;
+
+/// Edit the selected chart as data: its type and a CSV table of its series
+#[component]
+pub fn ChartEditor(shape: Shape) -> Element {
+    let mut state = use_context::<BoardState>();
+    let ShapeType::Chart { data } = &shape.shape_type else {
+        return rsx! {};
+    };
+    let mut kind = use_signal(|| data.kind);
+    let mut table = use_signal(|| data.to_csv());
+    let mut error = use_signal(|| None::<String>);
+
+    // Show the chart's current data when another chart is selected or the agent updates it
+    let current = data.clone();
+    use_effect(use_reactive!(|current| {
+        kind.set(current.kind);
+        table.set(current.to_csv());
+        error.set(None);
+    }));
+
+    let previous = data.clone();
+    let apply = move |_| match ChartData::from_csv(kind(), &table.read(), Some(&previous)) {
+        Ok(data) => {
+            error.set(None);
+            let mut updated = shape.clone();
+            updated.shape_type = ShapeType::Chart { data };
+            state.dispatch(Action::UpdateShape(updated));
+        }
+        Err(message) => error.set(Some(message)),
+    };
+
+    rsx! {
+        div { class: "flex flex-col w-full max-w-xl gap-2 mt-1",
+            div { class: "flex items-center gap-2 text-sm",
+                span { class: "text-gray-400", "Chart" }
+                select {
+                    class: "px-2 py-1.5 bg-gray-800 border border-gray-600 rounded-lg text-sm",
+                    value: "{kind().name()}",
+                    onchange: move |e| {
+                        if let Some(parsed) = ChartKind::parse(&e.value()) {
+                            kind.set(parsed);
+                        }
+                    },
+                    option { value: "bar", "Bar" }
+                    option { value: "line", "Line" }
+                    option { value: "pie", "Pie" }
+                }
+                button {
+                    class: "px-3 py-1.5 rounded-lg bg-blue-600 hover:bg-blue-500 text-sm transition-colors",
+                    onclick: apply,
+                    "Apply"
+                }
+            }
+            textarea {
+                class: "w-full h-28 px-3 py-2 bg-gray-800/50 border border-gray-700 rounded-xl font-mono text-xs text-gray-200 focus:outline-none focus:ring-2 focus:ring-blue-500",
+                value: "{table}",
+                oninput: move |e| table.set(e.value()),
+            }
+            if let Some(message) = error() {
+                p { class: "text-red-400 text-sm", "{message}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::agent::{get_agent_config, get_conversation, update_agent_config};
+use crate::notes::render_markdown;
+use crate::transcript::{current_run, RunStatus, TranscriptSummary};
+use dioxus::prelude::*;
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use gloo_timers::future::sleep;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::sleep;
+
+/// The research input. Once the agent has worked on the board, further
+/// messages continue that conversation as follow-up instructions (e.g. "add
+/// a section on costs", "make the timeline horizontal") unless the user
+/// chooses to start a fresh run.
+#[component]
+pub fn ResearchChat() -> Element {
+    let mut input = use_signal(String::new);
+    let mut is_researching = use_signal(|| false);
+    // The user's choice for "continue this board"; `None` follows whether there is a conversation
+    let mut continue_choice = use_signal(|| None::<bool>);
+    let mut conversation = use_resource(get_conversation);
+    // Usage of the run in progress, refreshed while waiting for it
+    let mut live_run = use_signal(|| None::<TranscriptSummary>);
+
+    let turns = conversation
+        .read()
+        .as_ref()
+        .and_then(|r| r.as_ref().ok())
+        .cloned()
+        .unwrap_or_default();
+    let follow_up = continue_choice().unwrap_or(!turns.is_empty());
+
+    let mut send = move || {
+        let instruction = input.read().trim().to_string();
+        if instruction.is_empty() || is_researching() {
+            return;
+        }
+        is_researching.set(true);
+        spawn(async move {
+            if let Ok(mut config) = get_agent_config().await {
+                config.research_topic = instruction;
+                config.follow_up = follow_up;
+                if update_agent_config(config).await.is_ok() {
+                    input.set(String::new());
+                    // The agent clears the topic once the run is over
+                    loop {
+                        sleep(Duration::from_secs(2)).await;
+                        if let Ok(run) = current_run().await {
+                            live_run.set(run);
+                        }
+                        match get_agent_config().await {
+                            Ok(config) if !config.research_topic.is_empty() => continue,
+                            _ => break,
+                        }
+                    }
+                }
+            }
+            is_researching.set(false);
+            live_run.set(None);
+            continue_choice.set(None);
+            conversation.restart();
+        });
+    };
+
+    rsx! {
+        div { class: "max-w-3xl mx-auto mb-8",
+            if !turns.is_empty() {
+                ol { class: "mb-4 space-y-3 max-h-80 overflow-y-auto pr-1",
+                    for (i, turn) in turns.iter().enumerate() {
+                        li { key: "{i}", class: "space-y-1",
+                            div { class: "ml-auto w-fit max-w-[80%] px-4 py-2 rounded-xl bg-blue-600/40 text-sm",
+                                "{turn.instruction}"
+                            }
+                            div {
+                                class: "notes-preview max-w-[90%] px-4 py-2 rounded-xl bg-gray-800/60 border border-gray-700 text-sm text-gray-200",
+                                dangerous_inner_html: "{render_markdown(&turn.answer)}",
+                            }
+                            if let Some(run) = &turn.run {
+                                {run_usage(run)}
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "flex gap-3",
+                input {
+                    class: "flex-1 px-5 py-4 bg-gray-800/50 border border-gray-700 rounded-xl text-lg placeholder-gray-500 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent transition-all",
+                    r#type: "text",
+                    placeholder: if follow_up { "What should the agent change or add?" } else { "What would you like to research?" },
+                    value: "{input}",
+                    oninput: move |e| input.set(e.value()),
+                    onkeypress: move |e| {
+                        if e.key() == Key::Enter {
+                            send();
+                        }
+                    }
+                }
+                button {
+                    class: "px-8 py-4 bg-gradient-to-r from-blue-600 to-purple-600 hover:from-blue-500 hover:to-purple-500 rounded-xl font-semibold transition-all transform hover:scale-105 disabled:opacity-50 disabled:cursor-not-allowed",
+                    disabled: input.read().trim().is_empty() || is_researching(),
+                    onclick: move |_| send(),
+                    if is_researching() { "Researching..." } else if follow_up { "Send →" } else { "Go →" }
+                }
+            }
+            if is_researching() {
+                if let Some(run) = live_run() {
+                    {run_usage(&run)}
+                }
+            }
+            label { class: "mt-2 flex items-center gap-2 text-sm text-gray-400",
+                input {
+                    r#type: "checkbox",
+                    checked: follow_up,
+                    onchange: move |_| continue_choice.set(Some(!follow_up)),
+                }
+                "Continue this board (the agent sees the conversation and what is drawn)"
+            }
+        }
+    }
+}
+
+/// What a run used, and the limit it stopped at, if any
+fn run_usage(run: &TranscriptSummary) -> Element {
+    rsx! {
+        div { class: "px-1 text-xs text-gray-500",
+            "{run.usage.describe()}"
+            if let RunStatus::LimitReached(reason) = &run.status {
+                span { class: "text-amber-400", " · stopped at {reason}" }
+            }
+        }
+    }
+}
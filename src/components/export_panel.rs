@@ -0,0 +1,126 @@
+use crate::export::{AnimationFormat, AnimationOptions, PdfPages};
+use dioxus::prelude::*;
+
+const LINK: &str = "px-4 py-2 bg-gray-700 hover:bg-green-600 rounded-xl font-medium transition-colors";
+const FIELD: &str = "px-2 py-1 bg-gray-800 border border-gray-600 rounded-lg";
+
+/// Download links for the server-side exporters, with their settings
+#[component]
+pub fn ExportPanel() -> Element {
+    let mut animation_options = use_signal(AnimationOptions::default);
+    let mut png_scale = use_signal(|| 2.0f32);
+    let mut pdf_pages = use_signal(PdfPages::default);
+
+    let animation = animation_options();
+    let animation_url = format!(
+        "/api/export/animation?format={}&delay={}&width={}",
+        animation.format.extension(),
+        animation.frame_delay_ms,
+        animation.width
+    );
+
+    rsx! {
+        div { class: "flex flex-col items-center gap-3 mt-6 text-sm text-gray-400",
+            div { class: "flex flex-wrap justify-center items-center gap-3",
+                a { class: LINK, href: "/api/export/svg", download: "board.svg", "⬇ SVG" }
+                a {
+                    class: LINK,
+                    href: "/api/export/png?scale={png_scale}",
+                    download: "board.png",
+                    "⬇ PNG"
+                }
+                select {
+                    class: FIELD,
+                    value: "{png_scale}",
+                    onchange: move |e| {
+                        if let Ok(scale) = e.value().parse() {
+                            png_scale.set(scale);
+                        }
+                    },
+                    option { value: "1", "1×" }
+                    option { value: "2", "2×" }
+                    option { value: "4", "4×" }
+                }
+                a {
+                    class: LINK,
+                    href: "/api/export/pdf?pages={pdf_pages().name()}",
+                    download: "board.pdf",
+                    "⬇ PDF"
+                }
+                select {
+                    class: FIELD,
+                    value: "{pdf_pages().name()}",
+                    onchange: move |e| {
+                        if let Some(pages) = PdfPages::parse(&e.value()) {
+                            pdf_pages.set(pages);
+                        }
+                    },
+                    option { value: "board", "Whole board" }
+                    option { value: "frames", "Page per frame" }
+                }
+            }
+            div { class: "flex flex-wrap justify-center items-center gap-3",
+                a {
+                    class: LINK,
+                    title: "Download the research notes as Markdown",
+                    href: "/api/export/notes",
+                    download: "notes.md",
+                    "⬇ Notes"
+                }
+                a {
+                    class: LINK,
+                    title: "Download the board and its notes as one HTML page",
+                    href: "/api/export/report",
+                    download: "report.html",
+                    "⬇ Report"
+                }
+            }
+            div { class: "flex flex-wrap justify-center items-center gap-3",
+                a {
+                    class: LINK,
+                    title: "Download the drawing history as an animation",
+                    href: "{animation_url}",
+                    download: "presentation.{animation.format.extension()}",
+                    "📹 Export"
+                }
+                select {
+                    class: FIELD,
+                    value: "{animation.format.extension()}",
+                    onchange: move |e| {
+                        if let Some(format) = AnimationFormat::parse(&e.value()) {
+                            animation_options.write().format = format;
+                        }
+                    },
+                    option { value: "gif", "GIF" }
+                    option { value: "png", "APNG" }
+                }
+                label { "Frame delay (ms)" }
+                input {
+                    class: "w-20 {FIELD}",
+                    r#type: "number",
+                    min: "20",
+                    step: "50",
+                    value: "{animation.frame_delay_ms}",
+                    onchange: move |e| {
+                        if let Ok(delay) = e.value().parse() {
+                            animation_options.write().frame_delay_ms = delay;
+                        }
+                    },
+                }
+                label { "Width (px)" }
+                input {
+                    class: "w-20 {FIELD}",
+                    r#type: "number",
+                    min: "64",
+                    step: "100",
+                    value: "{animation.width}",
+                    onchange: move |e| {
+                        if let Ok(width) = e.value().parse() {
+                            animation_options.write().width = width;
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::formats::{diagram, excalidraw};
+use crate::model::Board;
+use crate::state::BoardState;
+use dioxus::prelude::*;
+
+/// Bring content from other tools onto the board
+#[component]
+pub fn ImportPanel() -> Element {
+    let mut state = use_context::<BoardState>();
+    let mut error = use_signal(|| None::<String>);
+    let mut diagram_source = use_signal(String::new);
+
+    let import_file = move |e: FormEvent| async move {
+        for file in e.files() {
+            let name = file.name().to_lowercase();
+            let is_diagram = [".mmd", ".mermaid", ".dot", ".gv"].iter().any(|ext| name.ends_with(ext));
+            let result = match file.read_string().await {
+                Ok(text) if is_diagram => diagram_board(&state, &text),
+                Ok(text) => excalidraw::import_json(&text),
+                Err(e) => Err(format!("Could not read {}: {}", file.name(), e)),
+            };
+            match result {
+                Ok(board) => {
+                    error.set(None);
+                    state.import_board(board);
+                }
+                Err(message) => error.set(Some(message)),
+            }
+        }
+    };
+
+    let import_diagram = move |_| match diagram_board(&state, &diagram_source.read()) {
+        Ok(board) => {
+            error.set(None);
+            state.import_board(board);
+        }
+        Err(message) => error.set(Some(message)),
+    };
+
+    rsx! {
+        div { class: "flex flex-col items-center gap-2 mt-4 text-sm text-gray-400",
+            div { class: "flex flex-wrap justify-center items-center gap-3",
+                label { class: "px-4 py-2 bg-gray-700 hover:bg-blue-600 rounded-xl font-medium text-white cursor-pointer transition-colors",
+                    "⬆ Import file"
+                    input {
+                        class: "hidden",
+                        r#type: "file",
+                        accept: ".excalidraw,.json,application/json,.mmd,.mermaid,.dot,.gv",
+                        onchange: import_file,
+                    }
+                }
+                a {
+                    class: "px-4 py-2 bg-gray-700 hover:bg-green-600 rounded-xl font-medium text-white transition-colors",
+                    href: "/api/export/excalidraw",
+                    download: "board.excalidraw",
+                    "⬇ .excalidraw"
+                }
+            }
+            div { class: "flex flex-col w-full max-w-xl gap-2",
+                textarea {
+                    class: "w-full h-28 px-3 py-2 bg-gray-800/50 border border-gray-700 rounded-xl font-mono text-xs text-gray-200 placeholder-gray-500 focus:outline-none focus:ring-2 focus:ring-blue-500",
+                    placeholder: "Paste a Mermaid flowchart or Graphviz DOT graph, e.g.\nflowchart LR\n  A[Idea] --> B{{Decide}} -->|yes| C(Ship)",
+                    value: "{diagram_source}",
+                    oninput: move |e| diagram_source.set(e.value()),
+                }
+                button {
+                    class: "self-end px-4 py-2 bg-gray-700 hover:bg-blue-600 rounded-xl font-medium text-white transition-colors disabled:opacity-50",
+                    disabled: diagram_source.read().trim().is_empty(),
+                    onclick: import_diagram,
+                    "⬆ Import diagram"
+                }
+            }
+            if let Some(message) = error() {
+                p { class: "text-red-400", "{message}" }
+            }
+        }
+    }
+}
+
+/// Parse Mermaid/DOT source into a laid-out board placed in the visible area
+fn diagram_board(state: &BoardState, source: &str) -> Result<Board, String> {
+    let parsed = diagram::parse(source, None)?;
+    let view = state.board.read().viewport.bounds();
+    Ok(parsed.to_board((view.min_x + 40.0, view.min_y + 40.0)).0)
+}
@@ -0,0 +1,88 @@
+use crate::model::{Action, Layer};
+use crate::state::BoardState;
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+/// Lists named layers (topmost first) with visibility/lock toggles, and lets
+/// the user move the current selection onto a layer.
+#[component]
+pub fn LayersPanel() -> Element {
+    let mut state = use_context::<BoardState>();
+
+    let layers = state.board.read().layers.clone();
+    let has_selection = !state.selection.read().is_empty();
+
+    let mut assign_selection = move |layer: Option<Uuid>| {
+        let ids = state.selection.read().clone();
+        state.dispatch(Action::AssignLayer { ids, layer });
+    };
+
+    rsx! {
+        div { class: "mt-4 bg-gray-800/60 border border-gray-700 rounded-xl p-3 text-sm",
+            div { class: "flex items-center justify-between mb-2",
+                h3 { class: "font-semibold text-gray-200", "Layers" }
+                button {
+                    class: "px-2 py-1 rounded bg-gray-700 hover:bg-gray-600",
+                    onclick: move |_| {
+                        let count = state.board.read().layers.len();
+                        state.dispatch(Action::PutLayer(Layer::new(format!("Layer {}", count + 1))));
+                    },
+                    "＋ Layer"
+                }
+            }
+            for layer in layers.iter().rev().cloned() {
+                div { key: "{layer.id}", class: "flex items-center gap-2 py-1",
+                    button {
+                        class: "w-7",
+                        title: if layer.visible { "Hide layer" } else { "Show layer" },
+                        onclick: {
+                            let layer = layer.clone();
+                            move |_| state.dispatch(Action::PutLayer(Layer { visible: !layer.visible, ..layer.clone() }))
+                        },
+                        if layer.visible { "👁" } else { "◌" }
+                    }
+                    button {
+                        class: "w-7",
+                        title: if layer.locked { "Unlock layer" } else { "Lock layer" },
+                        onclick: {
+                            let layer = layer.clone();
+                            move |_| state.dispatch(Action::PutLayer(Layer { locked: !layer.locked, ..layer.clone() }))
+                        },
+                        if layer.locked { "🔒" } else { "🔓" }
+                    }
+                    input {
+                        class: "flex-1 px-2 py-1 bg-gray-900/60 border border-gray-700 rounded focus:outline-none focus:ring-1 focus:ring-blue-500",
+                        r#type: "text",
+                        value: "{layer.name}",
+                        onchange: {
+                            let layer = layer.clone();
+                            move |e: FormEvent| state.dispatch(Action::PutLayer(Layer { name: e.value(), ..layer.clone() }))
+                        },
+                    }
+                    button {
+                        class: "px-2 py-1 rounded bg-gray-700 hover:bg-gray-600 disabled:opacity-40",
+                        disabled: !has_selection,
+                        title: "Move selected shapes to this layer",
+                        onclick: move |_| assign_selection(Some(layer.id)),
+                        "Move here"
+                    }
+                    button {
+                        class: "px-2 py-1 rounded text-gray-400 hover:text-red-400",
+                        title: "Delete layer (shapes move to the base layer)",
+                        onclick: move |_| state.dispatch(Action::RemoveLayer(layer.id)),
+                        "✕"
+                    }
+                }
+            }
+            div { class: "flex items-center gap-2 py-1 text-gray-400",
+                span { class: "flex-1 pl-16", "Base layer" }
+                button {
+                    class: "px-2 py-1 rounded bg-gray-700 hover:bg-gray-600 disabled:opacity-40 text-white",
+                    disabled: !has_selection,
+                    onclick: move |_| assign_selection(None),
+                    "Move here"
+                }
+            }
+        }
+    }
+}
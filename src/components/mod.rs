@@ -1 +1,11 @@
+pub mod canvas;
+pub mod chat;
+pub mod chart_editor;
+pub mod export_panel;
+pub mod import_panel;
+pub mod layers;
+pub mod notes;
 pub mod settings;
+pub mod shape;
+pub mod toolbar;
+pub mod transcripts;
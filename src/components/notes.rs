@@ -0,0 +1,69 @@
+use crate::model::Action;
+use crate::notes::render_markdown;
+use crate::state::BoardState;
+use dioxus::prelude::*;
+
+const BUTTON: &str = "px-2 py-1 rounded bg-gray-700 hover:bg-gray-600";
+
+/// The board's research notes: rendered Markdown, or a text area while the
+/// user edits them. Saving is one undoable step.
+#[component]
+pub fn NotesPanel() -> Element {
+    let mut state = use_context::<BoardState>();
+    // Text being edited; `None` while showing the saved notes
+    let mut draft = use_signal(|| None::<String>);
+
+    let notes = state.board.read().notes.clone();
+    let rendered = render_markdown(&notes);
+
+    rsx! {
+        div { class: "bg-gray-800/60 border border-gray-700 rounded-xl p-3 text-sm flex flex-col min-h-0",
+            div { class: "flex items-center justify-between mb-2",
+                h3 { class: "font-semibold text-gray-200", "Research notes" }
+                if draft.read().is_some() {
+                    div { class: "flex gap-2",
+                        button {
+                            class: BUTTON,
+                            onclick: move |_| draft.set(None),
+                            "Cancel"
+                        }
+                        button {
+                            class: "px-2 py-1 rounded bg-blue-600 hover:bg-blue-500",
+                            onclick: move |_| {
+                                if let Some(text) = draft.take() {
+                                    if text != state.board.read().notes {
+                                        state.dispatch(Action::SetNotes(text));
+                                    }
+                                }
+                            },
+                            "Save"
+                        }
+                    }
+                } else {
+                    button {
+                        class: BUTTON,
+                        onclick: move |_| draft.set(Some(state.board.read().notes.clone())),
+                        "✎ Edit"
+                    }
+                }
+            }
+            if let Some(text) = draft() {
+                textarea {
+                    class: "flex-1 min-h-96 w-full p-2 bg-gray-900 border border-gray-600 rounded-lg font-mono text-xs text-gray-100 focus:outline-none focus:ring-2 focus:ring-blue-500",
+                    spellcheck: "true",
+                    value: "{text}",
+                    oninput: move |e| draft.set(Some(e.value())),
+                }
+            } else if notes.trim().is_empty() {
+                p { class: "text-gray-500 italic",
+                    "The agent's findings, answer and sources appear here after a research run."
+                }
+            } else {
+                div {
+                    class: "notes-preview overflow-y-auto max-h-[36rem] text-gray-200",
+                    dangerous_inner_html: "{rendered}",
+                }
+            }
+        }
+    }
+}
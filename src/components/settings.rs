@@ -1,12 +1,12 @@
 use crate::agent::{get_agent_config, update_agent_config};
-use crate::model::{AgentConfig, AgentProvider};
+use crate::model::{AgentConfig, AgentProvider, SearchBackend};
 use dioxus::prelude::*;
 
 #[component]
 pub fn Settings(on_close: EventHandler<()>) -> Element {
-    let mut config = use_signal(|| AgentConfig::default());
+    let mut config = use_signal(AgentConfig::default);
     let mut is_loading = use_signal(|| true);
-    let mut save_status = use_signal(|| String::new());
+    let mut save_status = use_signal(String::new);
 
     // Fetch initial config
     use_effect(move || {
@@ -35,6 +35,7 @@ pub fn Settings(on_close: EventHandler<()>) -> Element {
     }
 
     let current_provider = config.read().provider.clone();
+    let search_backend = config.read().search.backend.clone();
 
     rsx! {
         div { class: "p-6 space-y-4",
@@ -112,6 +113,93 @@ pub fn Settings(on_close: EventHandler<()>) -> Element {
                 }
             }
 
+            // Search backend
+            div {
+                label { class: "block text-sm font-medium text-gray-300 mb-1", "Web Search" }
+                select {
+                    class: "w-full p-3 bg-gray-700 border border-gray-600 rounded-lg text-white focus:ring-2 focus:ring-blue-500",
+                    onchange: move |evt| {
+                        let val = evt.value();
+                        if let Some(backend) = SearchBackend::all().into_iter().find(|b| b.display_name() == val) {
+                            config.write().search.backend = backend;
+                        }
+                    },
+                    for backend in SearchBackend::all() {
+                        option {
+                            value: "{backend.display_name()}",
+                            selected: search_backend == backend,
+                            "{backend.display_name()}"
+                        }
+                    }
+                }
+            }
+
+            if let Some(hint) = search_backend.endpoint_hint() {
+                div {
+                    label { class: "block text-sm font-medium text-gray-300 mb-1", "Search Endpoint" }
+                    input {
+                        class: "w-full p-3 bg-gray-700 border border-gray-600 rounded-lg text-white placeholder-gray-500 focus:ring-2 focus:ring-blue-500",
+                        r#type: "text",
+                        placeholder: hint,
+                        value: "{config.read().search.endpoint}",
+                        oninput: move |evt| config.write().search.endpoint = evt.value()
+                    }
+                }
+            }
+
+            if search_backend.needs_api_key() {
+                div {
+                    label { class: "block text-sm font-medium text-gray-300 mb-1", "Search API Key" }
+                    input {
+                        class: "w-full p-3 bg-gray-700 border border-gray-600 rounded-lg text-white placeholder-gray-500 focus:ring-2 focus:ring-blue-500",
+                        r#type: "password",
+                        placeholder: "Enter your search API key",
+                        value: "{config.read().search.api_key}",
+                        oninput: move |evt| config.write().search.api_key = evt.value()
+                    }
+                }
+            }
+
+            // Local documents
+            div {
+                label { class: "block text-sm font-medium text-gray-300 mb-1", "Document Folder" }
+                input {
+                    class: "w-full p-3 bg-gray-700 border border-gray-600 rounded-lg text-white placeholder-gray-500 focus:ring-2 focus:ring-blue-500",
+                    r#type: "text",
+                    placeholder: "Server path to notes and PDFs (optional)",
+                    value: "{config.read().documents_dir}",
+                    oninput: move |evt| config.write().documents_dir = evt.value()
+                }
+            }
+
+            // Run limits
+            div {
+                label { class: "block text-sm font-medium text-gray-300 mb-1", "Run Limits" }
+                p { class: "text-xs text-gray-500 mb-2",
+                    "Once a run reaches any limit, the agent gets one last turn to answer. 0 means no limit; tokens and cost come from the usage the provider reports."
+                }
+                div { class: "grid grid-cols-2 gap-2",
+                    {limit_input("Model turns", config.read().limits.max_iterations.to_string(), move |v| {
+                        config.write().limits.max_iterations = v.parse().unwrap_or(0)
+                    })}
+                    {limit_input("Tool calls", config.read().limits.max_tool_calls.to_string(), move |v| {
+                        config.write().limits.max_tool_calls = v.parse().unwrap_or(0)
+                    })}
+                    {limit_input("Tokens", config.read().limits.max_tokens.to_string(), move |v| {
+                        config.write().limits.max_tokens = v.parse().unwrap_or(0)
+                    })}
+                    {limit_input("Cost (USD)", config.read().limits.max_cost.to_string(), move |v| {
+                        config.write().limits.max_cost = v.parse().unwrap_or(0.0)
+                    })}
+                    {limit_input("Input $ / 1M tokens", config.read().limits.input_price.to_string(), move |v| {
+                        config.write().limits.input_price = v.parse().unwrap_or(0.0)
+                    })}
+                    {limit_input("Output $ / 1M tokens", config.read().limits.output_price.to_string(), move |v| {
+                        config.write().limits.output_price = v.parse().unwrap_or(0.0)
+                    })}
+                }
+            }
+
             // Save
             button {
                 class: "w-full py-3 bg-blue-600 hover:bg-blue-500 text-white rounded-lg font-semibold transition-colors",
@@ -128,3 +216,20 @@ pub fn Settings(on_close: EventHandler<()>) -> Element {
         }
     }
 }
+
+/// A small labelled number field for one of the run limits
+fn limit_input(label: &'static str, value: String, mut on_change: impl FnMut(String) + 'static) -> Element {
+    rsx! {
+        div {
+            span { class: "block text-xs text-gray-400 mb-1", "{label}" }
+            input {
+                class: "w-full p-2 bg-gray-700 border border-gray-600 rounded-lg text-white focus:ring-2 focus:ring-blue-500",
+                r#type: "number",
+                min: "0",
+                step: "any",
+                value: "{value}",
+                oninput: move |evt| on_change(evt.value())
+            }
+        }
+    }
+}
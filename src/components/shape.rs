@@ -0,0 +1,267 @@
+use crate::export::chart::{chart_elements, ChartElement, TICK_FONT_SIZE, TICK_LABEL_COLOR};
+use crate::export::svg::{
+    arrow_head_points, citation_marker, label_layout, path_data, points_attr, CHART_BORDER,
+    CITATION_FILL, CITATION_FONT_SIZE, CITATION_TEXT_COLOR, FONT_FAMILY, LABEL_COLOR,
+    LABEL_FONT_SIZE, STROKE,
+};
+use crate::model::{Shape, ShapeType};
+use dioxus::prelude::*;
+
+/// Renders a single board shape (and its label) as SVG. `citations` are the
+/// board-wide numbers of the shape's sources, shown as a badge whose hover
+/// tooltip is `citation_note`.
+#[component]
+pub fn ShapeView(
+    shape: Shape,
+    citations: Vec<usize>,
+    citation_note: String,
+    onselect: EventHandler<MouseEvent>,
+) -> Element {
+    let body = match &shape.shape_type {
+        ShapeType::Rectangle => rsx! {
+            rect {
+                x: "{shape.x}",
+                y: "{shape.y}",
+                width: "{shape.width}",
+                height: "{shape.height}",
+                fill: "{shape.color}",
+                stroke: STROKE,
+                stroke_width: "2",
+                rx: "8"
+            }
+        },
+        ShapeType::Circle => rsx! {
+            circle {
+                cx: "{shape.x + shape.width / 2.0}",
+                cy: "{shape.y + shape.height / 2.0}",
+                r: "{shape.width / 2.0}",
+                fill: "{shape.color}",
+                stroke: STROKE,
+                stroke_width: "2"
+            }
+        },
+        ShapeType::Ellipse => rsx! {
+            ellipse {
+                cx: "{shape.x + shape.width / 2.0}",
+                cy: "{shape.y + shape.height / 2.0}",
+                rx: "{shape.width / 2.0}",
+                ry: "{shape.height / 2.0}",
+                fill: "{shape.color}",
+                stroke: STROKE,
+                stroke_width: "2"
+            }
+        },
+        ShapeType::Line => rsx! {
+            line {
+                x1: "{shape.x}",
+                y1: "{shape.y}",
+                x2: "{shape.x + shape.width}",
+                y2: "{shape.y + shape.height}",
+                stroke: "{shape.color}",
+                stroke_width: "3",
+                stroke_linecap: "round"
+            }
+        },
+        ShapeType::Arrow { headless, .. } => rsx! {
+            line {
+                x1: "{shape.x}",
+                y1: "{shape.y}",
+                x2: "{shape.x + shape.width}",
+                y2: "{shape.y + shape.height}",
+                stroke: "{shape.color}",
+                stroke_width: "3",
+                stroke_linecap: "round"
+            }
+            if !headless {
+                polygon {
+                    points: "{points_attr(&arrow_head_points(&shape))}",
+                    fill: "{shape.color}",
+                    stroke: "{shape.color}",
+                    stroke_width: "1",
+                    stroke_linejoin: "round"
+                }
+            }
+        },
+        ShapeType::Diamond | ShapeType::Polygon { .. } => rsx! {
+            polygon {
+                points: "{points_attr(&shape.absolute_points())}",
+                fill: "{shape.color}",
+                stroke: STROKE,
+                stroke_width: "2",
+                stroke_linejoin: "round"
+            }
+        },
+        ShapeType::Freehand { .. } => rsx! {
+            path {
+                d: "{path_data(&shape.absolute_points())}",
+                fill: "none",
+                stroke: "{shape.color}",
+                stroke_width: "3",
+                stroke_linecap: "round",
+                stroke_linejoin: "round"
+            }
+        },
+        ShapeType::StickyNote => {
+            let fold = (shape.width.min(shape.height) * 0.15).min(24.0);
+            let right = shape.x + shape.width;
+            let bottom = shape.y + shape.height;
+            rsx! {
+                rect {
+                    x: "{shape.x + 3.0}",
+                    y: "{shape.y + 4.0}",
+                    width: "{shape.width}",
+                    height: "{shape.height}",
+                    fill: "rgba(0, 0, 0, 0.12)",
+                    rx: "2"
+                }
+                path {
+                    d: "M{shape.x} {shape.y} L{right} {shape.y} L{right} {bottom - fold} L{right - fold} {bottom} L{shape.x} {bottom} Z",
+                    fill: "{shape.color}",
+                    stroke: "rgba(0, 0, 0, 0.2)",
+                    stroke_width: "1"
+                }
+                path {
+                    d: "M{right} {bottom - fold} L{right - fold} {bottom - fold} L{right - fold} {bottom} Z",
+                    fill: "rgba(0, 0, 0, 0.15)"
+                }
+            }
+        }
+        ShapeType::Image { href } => rsx! {
+            image {
+                href: "{href}",
+                x: "{shape.x}",
+                y: "{shape.y}",
+                width: "{shape.width}",
+                height: "{shape.height}",
+                preserve_aspect_ratio: "xMidYMid meet"
+            }
+            rect {
+                x: "{shape.x}",
+                y: "{shape.y}",
+                width: "{shape.width}",
+                height: "{shape.height}",
+                fill: "none",
+                stroke: "#d1d5db",
+                stroke_width: "1"
+            }
+        },
+        // Invisible hit area so the text can be clicked between glyphs
+        ShapeType::Text => rsx! {
+            rect {
+                x: "{shape.x}",
+                y: "{shape.y}",
+                width: "{shape.width}",
+                height: "{shape.height}",
+                fill: "transparent"
+            }
+        },
+        ShapeType::Chart { data } => {
+            let elements = chart_elements(&shape, data);
+            rsx! {
+                rect {
+                    x: "{shape.x}",
+                    y: "{shape.y}",
+                    width: "{shape.width}",
+                    height: "{shape.height}",
+                    fill: "{shape.color}",
+                    stroke: CHART_BORDER,
+                    stroke_width: "1",
+                    rx: "8"
+                }
+                for element in elements {
+                    {chart_element(element)}
+                }
+            }
+        }
+    };
+
+    let label = label_layout(&shape);
+    let marker = citation_marker(&shape, &citations);
+
+    rsx! {
+        g {
+            "data-shape-id": "{shape.id}",
+            cursor: "pointer",
+            onclick: move |e| {
+                e.stop_propagation();
+                onselect.call(e);
+            },
+            {body}
+            if !label.lines.is_empty() {
+                text {
+                    text_anchor: label.anchor,
+                    font_family: FONT_FAMILY,
+                    font_size: "{LABEL_FONT_SIZE}",
+                    fill: LABEL_COLOR,
+                    pointer_events: "none",
+                    for (line_y, line) in label.lines {
+                        tspan { x: "{label.x}", y: "{line_y}", "{line}" }
+                    }
+                }
+            }
+            if let Some(marker) = marker {
+                g {
+                    title { "{citation_note}" }
+                    rect {
+                        x: "{marker.x}",
+                        y: "{marker.y}",
+                        width: "{marker.width}",
+                        height: "{marker.height}",
+                        rx: "{marker.height / 2.0}",
+                        fill: CITATION_FILL
+                    }
+                    text {
+                        x: "{marker.text_x()}",
+                        y: "{marker.text_y()}",
+                        text_anchor: "middle",
+                        font_family: FONT_FAMILY,
+                        font_size: "{CITATION_FONT_SIZE}",
+                        font_weight: "600",
+                        fill: CITATION_TEXT_COLOR,
+                        pointer_events: "none",
+                        "{marker.text}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn chart_element(element: ChartElement) -> Element {
+    match element {
+        ChartElement::Rect { x, y, width, height, fill } => rsx! {
+            rect { x: "{x}", y: "{y}", width: "{width}", height: "{height}", fill: "{fill}" }
+        },
+        ChartElement::Line { x1, y1, x2, y2, stroke } => rsx! {
+            line { x1: "{x1}", y1: "{y1}", x2: "{x2}", y2: "{y2}", stroke, stroke_width: "1" }
+        },
+        ChartElement::Polyline { points, stroke } => rsx! {
+            polyline {
+                points: "{points}",
+                fill: "none",
+                stroke: "{stroke}",
+                stroke_width: "2.5",
+                stroke_linejoin: "round",
+                stroke_linecap: "round"
+            }
+        },
+        ChartElement::Dot { cx, cy, fill } => rsx! {
+            circle { cx: "{cx}", cy: "{cy}", r: "3.5", fill: "{fill}" }
+        },
+        ChartElement::Wedge { d, fill } => rsx! {
+            path { d: "{d}", fill: "{fill}", stroke: "white", stroke_width: "1.5" }
+        },
+        ChartElement::Text { x, y, anchor, text } => rsx! {
+            text {
+                x: "{x}",
+                y: "{y}",
+                text_anchor: anchor,
+                font_family: FONT_FAMILY,
+                font_size: "{TICK_FONT_SIZE}",
+                fill: TICK_LABEL_COLOR,
+                pointer_events: "none",
+                "{text}"
+            }
+        },
+    }
+}
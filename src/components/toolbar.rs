@@ -0,0 +1,215 @@
+use crate::components::chart_editor::ChartEditor;
+use crate::layout::LayoutKind;
+use crate::model::{Action, Bounds, Group, GroupKind, ShapeType, ZOrder};
+use crate::state::BoardState;
+use dioxus::prelude::*;
+
+/// Padding added around the selection when wrapping it in a frame
+const FRAME_PADDING: f64 = 30.0;
+
+const BUTTON: &str = "px-3 py-1.5 rounded-lg bg-gray-700 hover:bg-gray-600 disabled:opacity-40 disabled:cursor-not-allowed text-sm transition-colors";
+
+/// Undo/redo and actions on the current selection: clipboard, grouping, framing, renaming frames, stacking order and layout
+#[component]
+pub fn SelectionToolbar() -> Element {
+    let mut state = use_context::<BoardState>();
+
+    let selection_len = state.selection.read().len();
+    // The chart editor appears when exactly one chart is selected
+    let selected_chart = match state.selection.read().as_slice() {
+        [id] => state
+            .board
+            .read()
+            .shape(*id)
+            .filter(|s| matches!(s.shape_type, ShapeType::Chart { .. }))
+            .cloned(),
+        _ => None,
+    };
+    let selected_group = state
+        .selected_group
+        .read()
+        .and_then(|id| state.board.read().group(id).cloned());
+
+    let group_selection = move |_| {
+        let members = state.selection.read().clone();
+        let group = Group::plain(members);
+        let id = group.id;
+        state.dispatch(Action::CreateGroup(group));
+        state.selected_group.set(Some(id));
+    };
+
+    let frame_selection = move |_| {
+        let members = state.selection.read().clone();
+        let board = state.board.read();
+        let frame_count = board.groups.iter().filter(|g| g.is_frame()).count();
+        // Wrap the selection, or drop an empty frame in the middle of the view
+        let bounds = board
+            .members_bounds(&members)
+            .map(|b| b.padded(FRAME_PADDING))
+            .unwrap_or_else(|| {
+                let view = board.viewport.bounds();
+                let (cx, cy) = (view.min_x + view.width() / 2.0, view.min_y + view.height() / 2.0);
+                Bounds {
+                    min_x: cx - 200.0,
+                    min_y: cy - 150.0,
+                    max_x: cx + 200.0,
+                    max_y: cy + 150.0,
+                }
+            });
+        drop(board);
+        let frame = Group::frame(format!("Frame {}", frame_count + 1), members, bounds);
+        let id = frame.id;
+        state.dispatch(Action::CreateGroup(frame));
+        state.selected_group.set(Some(id));
+    };
+
+    let mut layout_name = use_signal(|| "grid".to_string());
+    let arrange = move |_| {
+        if let Some(kind) = LayoutKind::parse(&layout_name.read()) {
+            state.arrange_selection(kind);
+        }
+    };
+
+    let mut reorder = move |op: ZOrder| {
+        let ids = state.selection.read().clone();
+        state.dispatch(Action::Reorder { ids, op });
+    };
+
+    rsx! {
+        div { class: "flex flex-wrap items-center justify-center gap-2 mb-3 text-white",
+            button {
+                class: BUTTON,
+                title: "Undo (Ctrl+Z)",
+                disabled: state.undo_stack.read().is_empty(),
+                onclick: move |_| state.undo(),
+                "↶ Undo"
+            }
+            button {
+                class: BUTTON,
+                title: "Redo (Ctrl+Shift+Z)",
+                disabled: state.redo_stack.read().is_empty(),
+                onclick: move |_| state.redo(),
+                "↷ Redo"
+            }
+            button {
+                class: BUTTON,
+                title: "Copy (Ctrl+C)",
+                disabled: selection_len == 0,
+                onclick: move |_| state.copy_selection(),
+                "⧉ Copy"
+            }
+            button {
+                class: BUTTON,
+                title: "Cut (Ctrl+X)",
+                disabled: selection_len == 0,
+                onclick: move |_| state.cut_selection(),
+                "✂ Cut"
+            }
+            button {
+                class: BUTTON,
+                title: "Paste shapes or text (Ctrl+V)",
+                onclick: move |_| state.paste(),
+                "📋 Paste"
+            }
+            button {
+                class: BUTTON,
+                title: "Duplicate (Ctrl+D)",
+                disabled: selection_len == 0,
+                onclick: move |_| state.duplicate_selection(),
+                "⊕ Duplicate"
+            }
+            button {
+                class: BUTTON,
+                title: "Delete (Del)",
+                disabled: selection_len == 0,
+                onclick: move |_| state.delete_selection(),
+                "✕ Delete"
+            }
+            button {
+                class: BUTTON,
+                title: "Bring to front",
+                disabled: selection_len == 0,
+                onclick: move |_| reorder(ZOrder::BringToFront),
+                "⤒"
+            }
+            button {
+                class: BUTTON,
+                title: "Bring forward",
+                disabled: selection_len == 0,
+                onclick: move |_| reorder(ZOrder::BringForward),
+                "↑"
+            }
+            button {
+                class: BUTTON,
+                title: "Send backward",
+                disabled: selection_len == 0,
+                onclick: move |_| reorder(ZOrder::SendBackward),
+                "↓"
+            }
+            button {
+                class: BUTTON,
+                title: "Send to back",
+                disabled: selection_len == 0,
+                onclick: move |_| reorder(ZOrder::SendToBack),
+                "⤓"
+            }
+            button {
+                class: BUTTON,
+                title: "Group selected shapes so they move together",
+                disabled: selection_len < 2,
+                onclick: group_selection,
+                "▣ Group"
+            }
+            button {
+                class: BUTTON,
+                title: "Put the selection in a titled frame",
+                onclick: frame_selection,
+                "⬚ Frame"
+            }
+            select {
+                class: "px-2 py-1.5 bg-gray-800 border border-gray-600 rounded-lg text-sm",
+                title: "Layout",
+                value: "{layout_name}",
+                onchange: move |e| layout_name.set(e.value()),
+                option { value: "grid", "Grid" }
+                option { value: "tree", "Tree" }
+                option { value: "radial", "Radial" }
+                option { value: "layered", "Layered" }
+                option { value: "remove_overlaps", "Remove overlaps" }
+            }
+            button {
+                class: BUTTON,
+                title: "Arrange the selection (or the whole board) with the chosen layout",
+                onclick: arrange,
+                "⚹ Arrange"
+            }
+            if let Some(group) = selected_group {
+                button {
+                    class: BUTTON,
+                    onclick: move |_| {
+                        state.dispatch(Action::Ungroup(group.id));
+                        state.clear_selection();
+                    },
+                    if group.kind == GroupKind::Frame { "Remove frame" } else { "Ungroup" }
+                }
+                if group.kind == GroupKind::Frame {
+                    input {
+                        class: "px-3 py-1.5 bg-gray-800 border border-gray-600 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                        r#type: "text",
+                        placeholder: "Frame title",
+                        value: "{group.title}",
+                        onchange: move |e| {
+                            state.dispatch(Action::RenameGroup { id: group.id, title: e.value() });
+                        },
+                    }
+                }
+            }
+            if selection_len > 0 {
+                span { class: "text-gray-400 text-sm", "{selection_len} selected" }
+            }
+            if let Some(chart) = selected_chart {
+                ChartEditor { key: "{chart.id}", shape: chart }
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::transcript::{
+    format_time, get_transcript, list_transcripts, RunStatus, Transcript, TranscriptEntry,
+};
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+const BUTTON: &str = "px-3 py-1 rounded-lg bg-gray-700 hover:bg-gray-600 text-sm";
+const BLOCK: &str = "mt-1 p-2 bg-gray-900 rounded text-xs text-gray-300 whitespace-pre-wrap break-words max-h-64 overflow-y-auto";
+
+/// Lists past agent runs and shows one run's full transcript: the prompt,
+/// each model message with its reported usage, each tool call with its
+/// arguments, result and timing, and the answer.
+#[component]
+pub fn TranscriptViewer(on_close: EventHandler<()>) -> Element {
+    let mut selected = use_signal(|| None::<Uuid>);
+    let mut runs = use_resource(list_transcripts);
+    let transcript = use_resource(move || async move {
+        match selected() {
+            Some(id) => get_transcript(id).await.ok(),
+            None => None,
+        }
+    });
+
+    let listing = match &*runs.read() {
+        None => rsx! { p { class: "text-gray-400", "Loading..." } },
+        Some(Err(e)) => rsx! { p { class: "text-red-400", "Error: {e}" } },
+        Some(Ok(list)) if list.is_empty() => rsx! {
+            p { class: "text-gray-400", "No runs yet. Transcripts appear here once the agent has researched a topic." }
+        },
+        Some(Ok(list)) => rsx! {
+            ul { class: "space-y-1",
+                for run in list.iter().cloned() {
+                    li { key: "{run.id}",
+                        button {
+                            class: if selected() == Some(run.id) { "w-full text-left p-2 rounded-lg bg-blue-600/40" } else { "w-full text-left p-2 rounded-lg hover:bg-gray-700" },
+                            onclick: move |_| selected.set(Some(run.id)),
+                            div { class: "font-medium text-white truncate", "{run.topic}" }
+                            div { class: "text-xs text-gray-400",
+                                "{format_time(run.started_at)} · {status_text(&run.status)}"
+                            }
+                            div { class: "text-xs text-gray-500", "{run.usage.describe()}" }
+                        }
+                    }
+                }
+            }
+        },
+    };
+
+    let detail = match &*transcript.read() {
+        Some(Some(transcript)) => transcript_detail(transcript),
+        Some(None) if selected().is_some() => rsx! { p { class: "text-red-400", "Transcript not found" } },
+        _ => rsx! { p { class: "text-gray-400", "Select a run to see what the agent did." } },
+    };
+
+    rsx! {
+        div { class: "p-6 flex flex-col gap-4 max-h-[85vh]",
+            div { class: "flex justify-between items-center",
+                h2 { class: "text-xl font-bold text-white", "Agent Transcripts" }
+                div { class: "flex items-center gap-2",
+                    button { class: BUTTON, onclick: move |_| runs.restart(), "↻ Refresh" }
+                    button {
+                        class: "text-gray-400 hover:text-white text-2xl",
+                        onclick: move |_| on_close.call(()),
+                        "×"
+                    }
+                }
+            }
+            div { class: "flex gap-4 min-h-0 flex-1",
+                div { class: "w-64 shrink-0 overflow-y-auto", {listing} }
+                div { class: "flex-1 min-w-0 overflow-y-auto text-sm", {detail} }
+            }
+        }
+    }
+}
+
+fn status_text(status: &RunStatus) -> String {
+    match status {
+        RunStatus::Running => "running".to_string(),
+        RunStatus::Finished => "finished".to_string(),
+        RunStatus::Failed(_) => "failed".to_string(),
+        RunStatus::LimitReached(_) => "stopped at limit".to_string(),
+    }
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1000.0)
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// The token counts a provider reported for one model turn
+fn reported_tokens(input: Option<u32>, output: Option<u32>) -> String {
+    match (input, output) {
+        (None, None) => "no usage reported".to_string(),
+        (input, output) => format!(
+            "{} in / {} out tokens",
+            input.map_or("?".to_string(), |t| t.to_string()),
+            output.map_or("?".to_string(), |t| t.to_string())
+        ),
+    }
+}
+
+fn transcript_detail(transcript: &Transcript) -> Element {
+    let usage = transcript.usage();
+    let provider = format!("{:?}", transcript.provider);
+    rsx! {
+        div { class: "flex items-start justify-between gap-3 mb-3",
+            div {
+                h3 { class: "font-semibold text-white", "{transcript.topic}" }
+                p { class: "text-xs text-gray-400",
+                    "{provider} · {transcript.model} · {format_time(transcript.started_at)} · {seconds(transcript.duration_ms)}"
+                }
+                p { class: "text-xs text-gray-400",
+                    "{usage.describe()} ({usage.input_tokens} input / {usage.output_tokens} output tokens"
+                    if usage.estimated { ", partly estimated" } else { " reported" }
+                    ")"
+                }
+                if let RunStatus::LimitReached(reason) = &transcript.status {
+                    p { class: "text-xs text-amber-400", "Stopped at {reason}" }
+                }
+            }
+            a {
+                class: BUTTON,
+                href: "/api/transcript?id={transcript.id}",
+                download: "transcript-{transcript.id}.json",
+                "⬇ JSON"
+            }
+        }
+        ol { class: "space-y-2",
+            for (i, entry) in transcript.entries.iter().enumerate() {
+                li { key: "{i}", class: "p-2 rounded-lg bg-gray-700/50", {entry_view(entry)} }
+            }
+        }
+    }
+}
+
+fn entry_view(entry: &TranscriptEntry) -> Element {
+    match entry {
+        TranscriptEntry::Prompt { text } => rsx! {
+            details {
+                summary { class: "cursor-pointer font-medium text-blue-300", "Prompt" }
+                pre { class: BLOCK, "{text}" }
+            }
+        },
+        TranscriptEntry::ToolCall { name, args, result, at_ms, duration_ms, refused } => rsx! {
+            details {
+                summary { class: "cursor-pointer",
+                    span { class: "font-medium text-green-300", "{name}" }
+                    span { class: "text-xs text-gray-400", " at {seconds(*at_ms)}, took {duration_ms} ms" }
+                    if *refused {
+                        span { class: "text-xs text-amber-400", " · refused at limit" }
+                    }
+                }
+                div { class: "text-xs text-gray-400 mt-1", "Arguments" }
+                pre { class: BLOCK, "{pretty(args)}" }
+                div { class: "text-xs text-gray-400 mt-1", "Result" }
+                pre { class: BLOCK, "{pretty(result)}" }
+            }
+        },
+        TranscriptEntry::ModelMessage { text, tool_calls, input_tokens, output_tokens, at_ms, duration_ms } => rsx! {
+            details {
+                summary { class: "cursor-pointer",
+                    span { class: "font-medium text-sky-300", "Model" }
+                    span { class: "text-xs text-gray-400",
+                        " at {seconds(*at_ms)}, took {duration_ms} ms · {reported_tokens(*input_tokens, *output_tokens)}"
+                    }
+                }
+                if !tool_calls.is_empty() {
+                    div { class: "text-xs text-gray-400 mt-1", "Calls {tool_calls.join(\", \")}" }
+                }
+                if !text.is_empty() {
+                    pre { class: BLOCK, "{text}" }
+                }
+            }
+        },
+        TranscriptEntry::Answer { text, at_ms } => rsx! {
+            div { class: "font-medium text-purple-300",
+                "Answer"
+                span { class: "text-xs text-gray-400", " at {seconds(*at_ms)}" }
+            }
+            pre { class: BLOCK, "{text}" }
+        },
+        TranscriptEntry::Error { message, at_ms } => rsx! {
+            div { class: "font-medium text-red-400",
+                "Error"
+                span { class: "text-xs text-gray-400", " at {seconds(*at_ms)}" }
+            }
+            pre { class: BLOCK, "{message}" }
+        },
+    }
+}
@@ -0,0 +1,229 @@
+//! Animated export of the drawing history: each action becomes a frame.
+
+use super::raster::rasterize_region;
+use super::svg::content_bounds;
+use super::{AnimationFormat, AnimationOptions};
+use crate::model::{Action, Board, Bounds};
+use resvg::tiny_skia;
+
+/// How long the finished board stays up before the animation loops, in frames
+const HOLD_LAST_FRAMES: u32 = 5;
+/// Most frames in one animation; longer histories are sampled evenly
+const MAX_FRAMES: usize = 300;
+/// Pixels rendered for one animation across all its frames, so a wide export
+/// of a long history can't tie up the server
+const MAX_TOTAL_PIXELS: u64 = 200_000_000;
+
+/// The part of the history that builds the current board: everything after
+/// the last `Wipe` or `NewBoard`
+fn current_run(actions: &[Action]) -> &[Action] {
+    let start = actions
+        .iter()
+        .rposition(|a| matches!(a, Action::Wipe | Action::NewBoard))
+        .map_or(0, |i| i + 1);
+    &actions[start..]
+}
+
+/// Replay `actions` onto an empty board and encode one frame per action.
+/// Frames are encoded as they are rendered; long histories are sampled evenly
+/// down to [`MAX_FRAMES`] and to the [`MAX_TOTAL_PIXELS`] budget.
+pub fn render_animation(actions: &[Action], options: &AnimationOptions) -> Result<Vec<u8>, String> {
+    let actions = current_run(actions);
+
+    // A fixed region for every frame so the picture doesn't jump around
+    let mut board = Board::default();
+    let mut region: Option<Bounds> = None;
+    for action in actions {
+        board.apply(action);
+        if !board.shapes.is_empty() || !board.groups.is_empty() {
+            let bounds = content_bounds(&board);
+            region = Some(region.map_or(bounds, |r| r.union(&bounds)));
+        }
+    }
+    let region = region.unwrap_or_else(|| content_bounds(&Board::default()));
+    let scale = options.width.max(1) as f32 / region.width() as f32;
+
+    // The empty board, then one step per action
+    let steps = actions.len() + 1;
+    let frame_pixels = options.width.max(1) as u64 * (region.height() * scale as f64).ceil().max(1.0) as u64;
+    let budget = (MAX_TOTAL_PIXELS / frame_pixels) as usize;
+    let shown = sample_steps(steps, budget.clamp(1, MAX_FRAMES));
+
+    // Replay lazily, rendering each frame only when the encoder is ready for it
+    let mut board = Board::default();
+    let mut applied = 0;
+    let mut frames = shown.iter().map(|&step| {
+        for action in &actions[applied..step] {
+            board.apply(action);
+        }
+        applied = step;
+        rasterize_region(&board, &region, scale)
+    });
+    let first = frames.next().ok_or("Nothing to animate")??;
+
+    let mut bytes = Vec::new();
+    let mut writer = FrameWriter::new(&mut bytes, options.format, &first, shown.len())?;
+    for (i, pixmap) in std::iter::once(Ok(first)).chain(frames).enumerate() {
+        let delay = if i + 1 == shown.len() {
+            options.frame_delay_ms.saturating_mul(HOLD_LAST_FRAMES)
+        } else {
+            options.frame_delay_ms
+        };
+        writer.write(&pixmap?, delay)?;
+    }
+    writer.finish()?;
+    Ok(bytes)
+}
+
+/// Which of `steps` board states become frames: all of them when they fit in
+/// `frames`, otherwise an even spread that always keeps the first and last
+fn sample_steps(steps: usize, frames: usize) -> Vec<usize> {
+    if steps <= frames {
+        return (0..steps).collect();
+    }
+    if frames <= 1 {
+        return vec![steps - 1];
+    }
+    (0..frames).map(|i| i * (steps - 1) / (frames - 1)).collect()
+}
+
+/// An animation encoder that takes frames one at a time
+enum FrameWriter<'a> {
+    Gif(gif::Encoder<&'a mut Vec<u8>>),
+    Apng(png::Writer<&'a mut Vec<u8>>),
+}
+
+impl<'a> FrameWriter<'a> {
+    /// Start an animation of `frames` frames the size of `first`
+    fn new(
+        out: &'a mut Vec<u8>,
+        format: AnimationFormat,
+        first: &tiny_skia::Pixmap,
+        frames: usize,
+    ) -> Result<Self, String> {
+        match format {
+            AnimationFormat::Gif => {
+                let gif_error = |e: gif::EncodingError| format!("GIF encoding failed: {}", e);
+                let mut encoder =
+                    gif::Encoder::new(out, first.width() as u16, first.height() as u16, &[]).map_err(gif_error)?;
+                encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?;
+                Ok(Self::Gif(encoder))
+            }
+            AnimationFormat::Apng => {
+                let png_error = |e: png::EncodingError| format!("APNG encoding failed: {}", e);
+                let mut encoder = png::Encoder::new(out, first.width(), first.height());
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(frames as u32, 0).map_err(png_error)?;
+                Ok(Self::Apng(encoder.write_header().map_err(png_error)?))
+            }
+        }
+    }
+
+    fn write(&mut self, pixmap: &tiny_skia::Pixmap, delay_ms: u32) -> Result<(), String> {
+        match self {
+            Self::Gif(encoder) => {
+                // Backgrounds are opaque, so premultiplied RGBA equals straight RGBA
+                let mut rgba = pixmap.data().to_vec();
+                let mut frame =
+                    gif::Frame::from_rgba_speed(pixmap.width() as u16, pixmap.height() as u16, &mut rgba, 10);
+                // GIF delays are in hundredths of a second
+                frame.delay = (delay_ms / 10).clamp(1, u16::MAX as u32) as u16;
+                encoder
+                    .write_frame(&frame)
+                    .map_err(|e| format!("GIF encoding failed: {}", e))
+            }
+            Self::Apng(writer) => {
+                writer
+                    .set_frame_delay(delay_ms.clamp(1, u16::MAX as u32) as u16, 1000)
+                    .map_err(|e| format!("APNG encoding failed: {}", e))?;
+                writer
+                    .write_image_data(pixmap.data())
+                    .map_err(|e| format!("APNG encoding failed: {}", e))
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            Self::Gif(encoder) => encoder
+                .into_inner()
+                .map(drop)
+                .map_err(|e| format!("GIF encoding failed: {}", e)),
+            Self::Apng(writer) => writer
+                .finish()
+                .map_err(|e| format!("APNG encoding failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Shape, ShapeType};
+
+    fn draws(count: usize) -> Vec<Action> {
+        (0..count)
+            .map(|i| {
+                Action::Draw(Shape::new(
+                    ShapeType::Rectangle,
+                    i as f64 * 120.0,
+                    0.0,
+                    100.0,
+                    60.0,
+                    "#3b82f6".to_string(),
+                ))
+            })
+            .collect()
+    }
+
+    fn options(format: AnimationFormat) -> AnimationOptions {
+        AnimationOptions {
+            format,
+            frame_delay_ms: 100,
+            width: 64,
+        }
+    }
+
+    #[test]
+    fn sample_steps_keeps_short_histories_whole() {
+        assert_eq!(sample_steps(4, 300), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn sample_steps_spreads_long_histories_evenly() {
+        let shown = sample_steps(1001, 5);
+        assert_eq!(shown, vec![0, 250, 500, 750, 1000]);
+        assert_eq!(sample_steps(1001, MAX_FRAMES).len(), MAX_FRAMES);
+        assert_eq!(sample_steps(10, 1), vec![9]);
+    }
+
+    #[test]
+    fn animation_starts_after_the_last_wipe() {
+        let mut actions = draws(2);
+        actions.push(Action::Wipe);
+        actions.extend(draws(3));
+        assert_eq!(current_run(&actions).len(), 3);
+    }
+
+    #[test]
+    fn gif_has_a_frame_per_action_plus_the_empty_board() {
+        let bytes = render_animation(&draws(3), &options(AnimationFormat::Gif)).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+        assert_eq!(decoder.width(), 64);
+    }
+
+    #[test]
+    fn apng_declares_its_frame_count() {
+        let bytes = render_animation(&draws(3), &options(AnimationFormat::Apng)).unwrap();
+        let reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!(control.num_frames, 4);
+        assert_eq!(reader.info().width, 64);
+    }
+}
@@ -0,0 +1,446 @@
+//! Geometry of chart shapes. Charts are computed into plain SVG primitives
+//! here so the canvas and the exporters draw them identically.
+
+use super::svg::{LABEL_FONT_SIZE, LINE_HEIGHT};
+#[cfg(feature = "server")]
+use super::svg::{escape, FONT_FAMILY};
+use crate::model::{ChartData, ChartKind, Shape};
+use std::f64::consts::{PI, TAU};
+
+/// Series (and pie slice) colors, in order
+pub const PALETTE: &[&str] = &[
+    "#3b82f6", "#f59e0b", "#10b981", "#ef4444", "#8b5cf6", "#ec4899", "#06b6d4", "#84cc16",
+];
+pub const AXIS_COLOR: &str = "#9ca3af";
+pub const GRID_COLOR: &str = "#e5e7eb";
+pub const TICK_LABEL_COLOR: &str = "#6b7280";
+pub const TICK_FONT_SIZE: f64 = 11.0;
+
+const PADDING: f64 = 14.0;
+/// Room left of the plot for value labels
+const AXIS_LABEL_WIDTH: f64 = 40.0;
+const LEGEND_SWATCH: f64 = 10.0;
+const LEGEND_ROW_HEIGHT: f64 = 18.0;
+const GRID_LINES: usize = 4;
+
+pub enum ChartElement {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        fill: String,
+    },
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        stroke: &'static str,
+    },
+    Polyline {
+        points: String,
+        stroke: String,
+    },
+    Dot {
+        cx: f64,
+        cy: f64,
+        fill: String,
+    },
+    /// Pie slice
+    Wedge {
+        d: String,
+        fill: String,
+    },
+    Text {
+        x: f64,
+        y: f64,
+        anchor: &'static str,
+        text: String,
+    },
+}
+
+pub fn series_color(data: &ChartData, index: usize) -> String {
+    data.series
+        .get(index)
+        .and_then(|s| s.color.clone())
+        .unwrap_or_else(|| PALETTE[index % PALETTE.len()].to_string())
+}
+
+/// Lay out the chart inside the shape's box. The title (the shape's label)
+/// is drawn by the usual label code along the top edge.
+pub fn chart_elements(shape: &Shape, data: &ChartData) -> Vec<ChartElement> {
+    let (x, y, w, h) = (shape.x, shape.y, shape.width.abs(), shape.height.abs());
+    let title_lines = shape.label_lines(LABEL_FONT_SIZE).len() as f64;
+    let top = y + PADDING + title_lines * LABEL_FONT_SIZE * LINE_HEIGHT;
+    let mut elements = Vec::new();
+    if data.validate().is_err() {
+        elements.push(ChartElement::Text {
+            x: x + w / 2.0,
+            y: y + h / 2.0,
+            anchor: "middle",
+            text: "No chart data".to_string(),
+        });
+        return elements;
+    }
+
+    let legend: Vec<(String, String)> = match data.kind {
+        ChartKind::Pie => data
+            .categories
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.clone(), PALETTE[i % PALETTE.len()].to_string()))
+            .collect(),
+        _ if data.series.len() > 1 => data
+            .series
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.name.clone(), series_color(data, i)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let legend_height = write_legend(&mut elements, &legend, x + PADDING, y + h - PADDING, w - 2.0 * PADDING);
+    let bottom = y + h - PADDING - legend_height;
+
+    match data.kind {
+        ChartKind::Pie => pie(&mut elements, data, x + PADDING, top, w - 2.0 * PADDING, bottom - top),
+        ChartKind::Bar | ChartKind::Line => axes(&mut elements, data, x + PADDING, top, w - 2.0 * PADDING, bottom - top),
+    }
+    elements
+}
+
+/// Legend rows bottom-up from `bottom`, wrapping at `width`. Returns the height used.
+fn write_legend(elements: &mut Vec<ChartElement>, entries: &[(String, String)], left: f64, bottom: f64, width: f64) -> f64 {
+    if entries.is_empty() {
+        return 0.0;
+    }
+    let char_width = TICK_FONT_SIZE * 0.55;
+    let mut rows: Vec<Vec<(f64, &(String, String))>> = vec![Vec::new()];
+    let mut cursor = 0.0;
+    for entry in entries {
+        let entry_width = LEGEND_SWATCH + 4.0 + entry.0.chars().count() as f64 * char_width + 12.0;
+        if cursor > 0.0 && cursor + entry_width > width {
+            rows.push(Vec::new());
+            cursor = 0.0;
+        }
+        rows.last_mut().unwrap().push((cursor, entry));
+        cursor += entry_width;
+    }
+    let height = rows.len() as f64 * LEGEND_ROW_HEIGHT;
+    for (r, row) in rows.iter().enumerate() {
+        let row_y = bottom - height + r as f64 * LEGEND_ROW_HEIGHT + 4.0;
+        for &(offset, (name, color)) in row {
+            elements.push(ChartElement::Rect {
+                x: left + offset,
+                y: row_y,
+                width: LEGEND_SWATCH,
+                height: LEGEND_SWATCH,
+                fill: color.clone(),
+            });
+            elements.push(ChartElement::Text {
+                x: left + offset + LEGEND_SWATCH + 4.0,
+                y: row_y + LEGEND_SWATCH - 1.0,
+                anchor: "start",
+                text: name.clone(),
+            });
+        }
+    }
+    height + 4.0
+}
+
+/// Bar and line charts: value axis with grid lines, categories along the bottom
+fn axes(elements: &mut Vec<ChartElement>, data: &ChartData, left: f64, top: f64, width: f64, height: f64) {
+    let values = data.series.iter().flat_map(|s| s.values.iter().copied());
+    let (min, max) = values.fold((0.0f64, 0.0f64), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let max = nice_ceiling(max);
+    let min = -nice_ceiling(-min);
+    let span = if max - min > 0.0 { max - min } else { 1.0 };
+
+    let plot_left = left + AXIS_LABEL_WIDTH;
+    let plot_width = (width - AXIS_LABEL_WIDTH).max(1.0);
+    let plot_bottom = top + height - TICK_FONT_SIZE * 1.6;
+    let plot_height = (plot_bottom - top).max(1.0);
+    let to_y = |v: f64| plot_bottom - (v - min) / span * plot_height;
+
+    for i in 0..=GRID_LINES {
+        let value = min + span * i as f64 / GRID_LINES as f64;
+        let line_y = to_y(value);
+        elements.push(ChartElement::Line {
+            x1: plot_left,
+            y1: line_y,
+            x2: plot_left + plot_width,
+            y2: line_y,
+            stroke: GRID_COLOR,
+        });
+        elements.push(ChartElement::Text {
+            x: plot_left - 6.0,
+            y: line_y + TICK_FONT_SIZE * 0.35,
+            anchor: "end",
+            text: format_value(value),
+        });
+    }
+
+    let count = data.categories.len();
+    let slot = plot_width / count as f64;
+    let max_chars = ((slot - 4.0) / (TICK_FONT_SIZE * 0.55)).max(3.0) as usize;
+    for (i, category) in data.categories.iter().enumerate() {
+        elements.push(ChartElement::Text {
+            x: plot_left + slot * (i as f64 + 0.5),
+            y: plot_bottom + TICK_FONT_SIZE * 1.3,
+            anchor: "middle",
+            text: truncate(category, max_chars),
+        });
+    }
+
+    match data.kind {
+        ChartKind::Bar => {
+            let bar_width = slot * 0.7 / data.series.len() as f64;
+            for (s, series) in data.series.iter().enumerate() {
+                for (i, &value) in series.values.iter().enumerate() {
+                    let (y0, y1) = (to_y(value.max(0.0)), to_y(value.min(0.0)));
+                    elements.push(ChartElement::Rect {
+                        x: plot_left + slot * i as f64 + slot * 0.15 + bar_width * s as f64,
+                        y: y0,
+                        width: bar_width,
+                        height: (y1 - y0).max(0.5),
+                        fill: series_color(data, s),
+                    });
+                }
+            }
+        }
+        _ => {
+            for (s, series) in data.series.iter().enumerate() {
+                let color = series_color(data, s);
+                let points: Vec<(f64, f64)> = series
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| (plot_left + slot * (i as f64 + 0.5), to_y(v)))
+                    .collect();
+                elements.push(ChartElement::Polyline {
+                    points: points.iter().map(|(px, py)| format!("{},{}", px, py)).collect::<Vec<_>>().join(" "),
+                    stroke: color.clone(),
+                });
+                for (cx, cy) in points {
+                    elements.push(ChartElement::Dot {
+                        cx,
+                        cy,
+                        fill: color.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    elements.push(ChartElement::Line {
+        x1: plot_left,
+        y1: to_y(0.0),
+        x2: plot_left + plot_width,
+        y2: to_y(0.0),
+        stroke: AXIS_COLOR,
+    });
+}
+
+/// Pie of the first series; slices start at 12 o'clock and go clockwise
+fn pie(elements: &mut Vec<ChartElement>, data: &ChartData, left: f64, top: f64, width: f64, height: f64) {
+    let values: Vec<f64> = data.series[0].values.iter().map(|v| v.max(0.0)).collect();
+    let total: f64 = values.iter().sum();
+    let radius = (width.min(height) / 2.0).max(1.0);
+    let (cx, cy) = (left + width / 2.0, top + height / 2.0);
+    let mut angle = -PI / 2.0;
+    for (i, value) in values.iter().enumerate() {
+        let sweep = value / total * TAU;
+        if sweep <= 0.0 {
+            continue;
+        }
+        let fill = PALETTE[i % PALETTE.len()].to_string();
+        let d = if sweep >= TAU - 1e-9 {
+            // A full circle can't be a single arc
+            format!(
+                "M{} {} A{r} {r} 0 1 1 {} {} A{r} {r} 0 1 1 {} {} Z",
+                cx,
+                cy - radius,
+                cx,
+                cy + radius,
+                cx,
+                cy - radius,
+                r = radius
+            )
+        } else {
+            let (x0, y0) = (cx + radius * angle.cos(), cy + radius * angle.sin());
+            let end = angle + sweep;
+            let (x1, y1) = (cx + radius * end.cos(), cy + radius * end.sin());
+            let large = if sweep > PI { 1 } else { 0 };
+            format!("M{cx} {cy} L{x0} {y0} A{radius} {radius} 0 {large} 1 {x1} {y1} Z")
+        };
+        elements.push(ChartElement::Wedge { d, fill });
+        // Percentages on slices big enough to hold them
+        if sweep > 0.35 {
+            let mid = angle + sweep / 2.0;
+            elements.push(ChartElement::Text {
+                x: cx + radius * 0.65 * mid.cos(),
+                y: cy + radius * 0.65 * mid.sin() + TICK_FONT_SIZE * 0.35,
+                anchor: "middle",
+                text: format!("{:.0}%", value / total * 100.0),
+            });
+        }
+        angle += sweep;
+    }
+}
+
+/// Round up to 1, 2, 2.5 or 5 times a power of ten, so grid lines land on round values
+fn nice_ceiling(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .iter()
+        .map(|step| step * magnitude)
+        .find(|&nice| nice >= value * (1.0 - 1e-9))
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Compact tick label: 1200 -> "1.2k", 3000000 -> "3M", 0.25 -> "0.25"
+pub fn format_value(value: f64) -> String {
+    let abs = value.abs();
+    let (scaled, suffix) = if abs >= 1e9 {
+        (value / 1e9, "B")
+    } else if abs >= 1e6 {
+        (value / 1e6, "M")
+    } else if abs >= 1e3 {
+        (value / 1e3, "k")
+    } else {
+        (value, "")
+    };
+    let text = format!("{:.2}", scaled);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    let text = if text == "-0" { "0" } else { text };
+    format!("{}{}", text, suffix)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// SVG markup for a chart's elements
+#[cfg(feature = "server")]
+pub fn chart_markup(elements: &[ChartElement]) -> String {
+    let mut out = String::new();
+    for element in elements {
+        let line = match element {
+            ChartElement::Rect { x, y, width, height, fill } => {
+                format!(r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" fill="{}"/>"#, escape(fill))
+            }
+            ChartElement::Line { x1, y1, x2, y2, stroke } => {
+                format!(r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{stroke}" stroke-width="1"/>"#)
+            }
+            ChartElement::Polyline { points, stroke } => format!(
+                r#"<polyline points="{points}" fill="none" stroke="{}" stroke-width="2.5" stroke-linejoin="round" stroke-linecap="round"/>"#,
+                escape(stroke)
+            ),
+            ChartElement::Dot { cx, cy, fill } => {
+                format!(r#"<circle cx="{cx}" cy="{cy}" r="3.5" fill="{}"/>"#, escape(fill))
+            }
+            ChartElement::Wedge { d, fill } => {
+                format!(r#"<path d="{d}" fill="{fill}" stroke="white" stroke-width="1.5"/>"#)
+            }
+            ChartElement::Text { x, y, anchor, text } => format!(
+                r#"<text x="{x}" y="{y}" text-anchor="{anchor}" font-family="{FONT_FAMILY}" font-size="{TICK_FONT_SIZE}" fill="{TICK_LABEL_COLOR}">{}</text>"#,
+                escape(text)
+            ),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Series, ShapeType};
+
+    fn chart(kind: ChartKind, values: &[f64]) -> ChartData {
+        ChartData {
+            kind,
+            categories: (1..=values.len()).map(|i| format!("Q{}", i)).collect(),
+            series: vec![Series {
+                name: "Sales".to_string(),
+                values: values.to_vec(),
+                color: None,
+            }],
+        }
+    }
+
+    fn shape() -> Shape {
+        Shape::new(ShapeType::Rectangle, 0.0, 0.0, 400.0, 300.0, "#fff".to_string())
+    }
+
+    #[test]
+    fn nice_ceiling_rounds_up_to_friendly_steps() {
+        for (value, nice) in [
+            (0.0, 0.0),
+            (-3.0, 0.0),
+            (1.0, 1.0),
+            (1.3, 2.0),
+            (2.2, 2.5),
+            (3.0, 5.0),
+            (7.0, 10.0),
+            (42.0, 50.0),
+            (100.0, 100.0),
+            (0.07, 0.1),
+        ] {
+            assert!((nice_ceiling(value) - nice).abs() < 1e-12, "{} -> {}", value, nice_ceiling(value));
+        }
+    }
+
+    #[test]
+    fn values_are_formatted_compactly() {
+        for (value, text) in [
+            (1200.0, "1.2k"),
+            (3_000_000.0, "3M"),
+            (2.5e9, "2.5B"),
+            (0.25, "0.25"),
+            (-1500.0, "-1.5k"),
+            (10.0, "10"),
+            (-0.0, "0"),
+            (-0.001, "0"),
+        ] {
+            assert_eq!(format_value(value), text);
+        }
+    }
+
+    #[test]
+    fn bar_charts_draw_one_bar_per_value_and_value_ticks() {
+        let elements = chart_elements(&shape(), &chart(ChartKind::Bar, &[3.0, -1.0, 7.0]));
+        let bars = elements.iter().filter(|e| matches!(e, ChartElement::Rect { .. })).count();
+        assert_eq!(bars, 3);
+        let labels: Vec<&str> = elements
+            .iter()
+            .filter_map(|e| match e {
+                ChartElement::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        // Axis runs from -1 to 10 in four steps, then the categories
+        assert_eq!(labels, vec!["-1", "1.75", "4.5", "7.25", "10", "Q1", "Q2", "Q3"]);
+    }
+
+    #[test]
+    fn pie_charts_draw_a_wedge_per_category() {
+        let elements = chart_elements(&shape(), &chart(ChartKind::Pie, &[1.0, 2.0, 3.0]));
+        let wedges = elements.iter().filter(|e| matches!(e, ChartElement::Wedge { .. })).count();
+        assert_eq!(wedges, 3);
+    }
+
+    #[test]
+    fn invalid_data_shows_a_placeholder() {
+        let elements = chart_elements(&shape(), &chart(ChartKind::Pie, &[0.0, 0.0]));
+        assert!(matches!(elements.as_slice(), [ChartElement::Text { text, .. }] if text == "No chart data"));
+    }
+}
@@ -0,0 +1,265 @@
+//! Board export: pure-Rust renderers plus the server functions and download
+//! routes that expose them.
+
+pub mod chart;
+pub mod svg;
+
+#[cfg(feature = "server")]
+pub mod animation;
+#[cfg(feature = "server")]
+pub mod pdf;
+#[cfg(feature = "server")]
+pub mod raster;
+#[cfg(feature = "server")]
+pub mod report;
+
+use crate::model::Board;
+use dioxus::fullstack::response::Response;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum AnimationFormat {
+    #[default]
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "gif" => Some(Self::Gif),
+            "apng" | "png" => Some(Self::Apng),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Gif => "gif",
+            Self::Apng => "png",
+        }
+    }
+
+    #[cfg(feature = "server")]
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Gif => "image/gif",
+            Self::Apng => "image/apng",
+        }
+    }
+}
+
+/// How a PDF export is split into pages
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PdfPages {
+    /// A single page with the whole board
+    #[default]
+    Board,
+    /// One page per frame (falls back to the whole board when there are none)
+    Frames,
+}
+
+impl PdfPages {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "board" => Some(Self::Board),
+            "frames" | "frame" => Some(Self::Frames),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Board => "board",
+            Self::Frames => "frames",
+        }
+    }
+}
+
+/// Settings for the animated playback export
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnimationOptions {
+    pub format: AnimationFormat,
+    /// Time each action stays on screen
+    pub frame_delay_ms: u32,
+    /// Output width in pixels; height follows the board's aspect ratio
+    pub width: u32,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            format: AnimationFormat::Gif,
+            frame_delay_ms: 400,
+            width: 800,
+        }
+    }
+}
+
+/// Render a board to a standalone SVG document.
+/// Pass `None` to export the server's copy of the live board.
+#[server]
+pub async fn export_svg(board: Option<Board>) -> Result<String, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        let board = match board {
+            Some(board) => board,
+            None => live_board()?,
+        };
+        Ok(svg::render_svg(&board))
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download the live board as `board.svg`
+#[get("/api/export/svg")]
+pub async fn download_svg() -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        let svg = svg::render_svg(&live_board()?);
+        attachment(svg.into_bytes(), "image/svg+xml", "board.svg")
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download the live board as an Excalidraw scene
+#[get("/api/export/excalidraw")]
+pub async fn download_excalidraw() -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        use crate::formats::excalidraw;
+        let json = excalidraw::export_json(&live_board()?);
+        attachment(json.into_bytes(), "application/json", "board.excalidraw")
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download the live board as `board.png`; `scale` multiplies the pixel size (default 2)
+#[get("/api/export/png?scale")]
+pub async fn download_png(scale: Option<f32>) -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        let board = live_board()?;
+        let scale = scale.unwrap_or(2.0).clamp(0.1, 8.0);
+        let bytes = tokio::task::spawn_blocking(move || raster::render_png(&board, scale))
+            .await
+            .map_err(|e| ServerFnError::new(format!("Rendering failed: {}", e)))?
+            .map_err(ServerFnError::new)?;
+        attachment(bytes, "image/png", "board.png")
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download the live board as `board.pdf`; `pages` is `board` (default) or `frames`
+#[get("/api/export/pdf?pages")]
+pub async fn download_pdf(pages: Option<String>) -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        let pages = match pages.as_deref() {
+            Some(name) => PdfPages::parse(name)
+                .ok_or_else(|| ServerFnError::new(format!("Unknown page mode '{}'", name)))?,
+            None => PdfPages::default(),
+        };
+        let board = live_board()?;
+        let bytes = tokio::task::spawn_blocking(move || pdf::render_pdf(&board, pages))
+            .await
+            .map_err(|e| ServerFnError::new(format!("Rendering failed: {}", e)))?
+            .map_err(ServerFnError::new)?;
+        attachment(bytes, "application/pdf", "board.pdf")
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download the live board's research notes as `notes.md`
+#[get("/api/export/notes")]
+pub async fn download_notes() -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        let notes = live_board()?.notes;
+        attachment(notes.into_bytes(), "text/markdown; charset=utf-8", "notes.md")
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download the live board and its notes as a single HTML page, `report.html`
+#[get("/api/export/report")]
+pub async fn download_report() -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        let html = report::render_report(&live_board()?);
+        attachment(html.into_bytes(), "text/html; charset=utf-8", "report.html")
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download an animation of the drawing history, one frame per action.
+/// Query parameters: `format` (gif or apng), `delay` (ms per frame), `width` (pixels).
+#[get("/api/export/animation?format&delay&width")]
+pub async fn download_animation(
+    format: Option<String>,
+    delay: Option<u32>,
+    width: Option<u32>,
+) -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        use crate::server_state::AGENT_HISTORY;
+        let defaults = AnimationOptions::default();
+        let format = match format.as_deref() {
+            Some(name) => AnimationFormat::parse(name)
+                .ok_or_else(|| ServerFnError::new(format!("Unknown animation format '{}'", name)))?,
+            None => defaults.format,
+        };
+        let options = AnimationOptions {
+            format,
+            frame_delay_ms: delay.unwrap_or(defaults.frame_delay_ms).clamp(20, 10_000),
+            width: width.unwrap_or(defaults.width).clamp(64, 4096),
+        };
+        let actions: Vec<_> = AGENT_HISTORY
+            .lock()
+            .map(|h| h.iter().map(|entry| entry.action.clone()).collect())
+            .map_err(|e| ServerFnError::new(format!("History unavailable: {}", e)))?;
+        println!("Server: Rendering {} animation of {} actions", format.extension(), actions.len());
+        // Rasterising every frame is CPU-bound; keep it off the async workers
+        let bytes = tokio::task::spawn_blocking(move || animation::render_animation(&actions, &options))
+            .await
+            .map_err(|e| ServerFnError::new(format!("Rendering failed: {}", e)))?
+            .map_err(ServerFnError::new)?;
+        let filename = format!("presentation.{}", format.extension());
+        attachment(bytes, format.mime_type(), &filename)
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Snapshot of the server-side board mirror
+#[cfg(feature = "server")]
+fn live_board() -> Result<Board, ServerFnError> {
+    use crate::server_state::AGENT_BOARD;
+    AGENT_BOARD
+        .lock()
+        .map(|b| b.clone())
+        .map_err(|e| ServerFnError::new(format!("Board unavailable: {}", e)))
+}
+
+/// Build a file download response
+#[cfg(feature = "server")]
+pub(crate) fn attachment(bytes: Vec<u8>, content_type: &str, filename: &str) -> Result<Response, ServerFnError> {
+    use dioxus::fullstack::body::Body;
+    use dioxus::fullstack::http::header;
+    Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(Body::from(bytes))
+        .map_err(|e| ServerFnError::new(format!("Failed to build response: {}", e)))
+}
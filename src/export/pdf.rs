@@ -0,0 +1,147 @@
+//! Vector PDF export: each page is a board region converted with svg2pdf and
+//! placed as a form XObject, so text stays selectable and shapes stay sharp.
+
+use super::raster::FONTS;
+use super::svg::{
+    bibliography_bounds, export_bounds, render_svg_page, EXPORT_PADDING, FRAME_TITLE_HEIGHT,
+};
+use super::PdfPages;
+use crate::model::{Board, Bounds};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+use resvg::usvg;
+use std::collections::HashMap;
+
+/// Regions of the board that become pages. Per-frame exports end with a
+/// page for the bibliography when the board cites sources.
+fn page_regions(board: &Board, pages: PdfPages) -> Vec<Bounds> {
+    let frames: Vec<Bounds> = board
+        .groups
+        .iter()
+        .filter(|g| g.is_frame())
+        .map(|f| {
+            Bounds {
+                min_x: f.x,
+                min_y: f.y - FRAME_TITLE_HEIGHT,
+                max_x: f.x + f.width,
+                max_y: f.y + f.height,
+            }
+            .padded(EXPORT_PADDING / 2.0)
+        })
+        .collect();
+    match pages {
+        // Boards without frames still get a page
+        PdfPages::Frames if !frames.is_empty() => frames
+            .into_iter()
+            .chain(bibliography_bounds(board).map(|b| b.padded(EXPORT_PADDING)))
+            .collect(),
+        _ => vec![export_bounds(board)],
+    }
+}
+
+/// Render the board as a PDF with one page per region
+pub fn render_pdf(board: &Board, pages: PdfPages) -> Result<Vec<u8>, String> {
+    let regions = page_regions(board, pages);
+    let options = usvg::Options {
+        fontdb: FONTS.clone(),
+        ..Default::default()
+    };
+
+    let mut alloc = Ref::new(1);
+    let catalog_id = alloc.bump();
+    let page_tree_id = alloc.bump();
+    let mut pdf = Pdf::new();
+    let mut page_ids = Vec::new();
+    let svg_name = Name(b"S1");
+
+    for region in &regions {
+        let svg = render_svg_page(board, region);
+        let tree = usvg::Tree::from_str(&svg, &options).map_err(|e| format!("Invalid SVG: {}", e))?;
+        let (chunk, svg_id) = svg2pdf::to_chunk(&tree, svg2pdf::ConversionOptions::default())
+            .map_err(|e| format!("PDF conversion failed: {}", e))?;
+        // Give the chunk's objects ids that don't collide with earlier pages
+        let mut renumbered = HashMap::new();
+        let chunk = chunk.renumber(|old| *renumbered.entry(old).or_insert_with(|| alloc.bump()));
+        let svg_id = renumbered
+            .get(&svg_id)
+            .copied()
+            .ok_or_else(|| "PDF conversion lost the page content".to_string())?;
+
+        let page_id = alloc.bump();
+        let content_id = alloc.bump();
+        let (width, height) = (region.width() as f32, region.height() as f32);
+
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, width, height));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        page.resources().x_objects().pair(svg_name, svg_id);
+        page.finish();
+
+        // The converted SVG is a unit-square XObject; stretch it over the page
+        let mut content = Content::new();
+        content.save_state();
+        content.transform([width, 0.0, 0.0, height, 0.0, 0.0]);
+        content.x_object(svg_name);
+        content.restore_state();
+        pdf.stream(content_id, &content.finish());
+
+        pdf.extend(&chunk);
+        page_ids.push(page_id);
+    }
+
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .count(page_ids.len() as i32)
+        .kids(page_ids);
+    Ok(pdf.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Source;
+
+    fn framed_board() -> Board {
+        serde_json::from_str(include_str!("../../tests/golden/frames.json")).unwrap()
+    }
+
+    /// The page count declared in the page tree
+    fn page_count(pdf: &[u8]) -> usize {
+        let text = String::from_utf8_lossy(pdf);
+        assert!(text.starts_with("%PDF-"));
+        let count = &text[text.find("/Count ").expect("no page tree") + "/Count ".len()..];
+        count[..count.find(|c: char| !c.is_ascii_digit()).unwrap()].parse().unwrap()
+    }
+
+    #[test]
+    fn board_export_is_one_page() {
+        let pdf = render_pdf(&framed_board(), PdfPages::Board).unwrap();
+        assert_eq!(page_count(&pdf), 1);
+    }
+
+    #[test]
+    fn frames_export_has_a_page_per_frame() {
+        let pdf = render_pdf(&framed_board(), PdfPages::Frames).unwrap();
+        assert_eq!(page_count(&pdf), 2);
+    }
+
+    #[test]
+    fn frames_export_adds_a_bibliography_page() {
+        let mut board = framed_board();
+        board.shapes[0].sources.push(Source {
+            url: "https://example.com/costs".to_string(),
+            title: Some("Cost report".to_string()),
+            quote: None,
+        });
+        let pdf = render_pdf(&board, PdfPages::Frames).unwrap();
+        assert_eq!(page_count(&pdf), 3);
+    }
+
+    #[test]
+    fn frames_export_without_frames_is_one_page() {
+        let mut board = framed_board();
+        board.groups.clear();
+        let pdf = render_pdf(&board, PdfPages::Frames).unwrap();
+        assert_eq!(page_count(&pdf), 1);
+    }
+}
@@ -0,0 +1,125 @@
+//! Rasterisation of rendered SVG with resvg, so images can be produced
+//! without a browser.
+
+use super::svg;
+use crate::model::{Board, Bounds};
+use once_cell::sync::Lazy;
+use resvg::{tiny_skia, usvg};
+use std::sync::Arc;
+
+/// Largest raster edge we will produce, in pixels
+pub const MAX_DIMENSION: u32 = 8192;
+
+/// System fonts, loaded once. `FONT_FAMILY` ends in the generic `sans-serif`,
+/// which fontdb maps to Arial by default; DejaVu is far more common on servers.
+pub(super) static FONTS: Lazy<Arc<usvg::fontdb::Database>> = Lazy::new(|| {
+    let mut db = usvg::fontdb::Database::new();
+    db.load_system_fonts();
+    db.set_sans_serif_family("DejaVu Sans");
+    Arc::new(db)
+});
+
+/// Rasterise an SVG document, scaling its intrinsic size by `scale`
+pub fn rasterize(svg: &str, scale: f32) -> Result<tiny_skia::Pixmap, String> {
+    let options = usvg::Options {
+        fontdb: FONTS.clone(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| format!("Invalid SVG: {}", e))?;
+    let size = tree.size();
+    let width = (size.width() * scale).round() as u32;
+    let height = (size.height() * scale).round() as u32;
+    if width == 0 || height == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(format!(
+            "Image size {}x{} is outside 1..={} pixels; pick a smaller scale",
+            width, height, MAX_DIMENSION
+        ));
+    }
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| format!("Cannot allocate a {}x{} image", width, height))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap)
+}
+
+/// Render `region` of the board to pixels at the given scale
+pub fn rasterize_region(board: &Board, region: &Bounds, scale: f32) -> Result<tiny_skia::Pixmap, String> {
+    rasterize(&svg::render_svg_region(board, region), scale)
+}
+
+/// Render the whole board to a PNG at the given scale (1.0 = one pixel per world unit)
+pub fn render_png(board: &Board, scale: f32) -> Result<Vec<u8>, String> {
+    rasterize(&svg::render_svg_page(board, &svg::export_bounds(board)), scale)?
+        .encode_png()
+        .map_err(|e| format!("PNG encoding failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Largest per-channel difference for two pixels to count as equal
+    const CHANNEL_TOLERANCE: u8 = 24;
+    /// Share of pixels allowed to differ, mostly anti-aliased text edges
+    /// that vary with the installed fonts
+    const MAX_DIFFERING: f64 = 0.01;
+
+    fn golden(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name)
+    }
+
+    /// Render `<name>.json` and compare it with `<name>.png`.
+    /// Set `UPDATE_GOLDEN=1` to rewrite the reference images.
+    fn check_golden(name: &str) {
+        let json = std::fs::read_to_string(golden(&format!("{}.json", name))).unwrap();
+        let board: Board = serde_json::from_str(&json).unwrap();
+        let png = render_png(&board, 0.5).unwrap();
+        let expected_path = golden(&format!("{}.png", name));
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&expected_path, &png).unwrap();
+        }
+
+        let actual = tiny_skia::Pixmap::decode_png(&png).unwrap();
+        let expected = tiny_skia::Pixmap::load_png(&expected_path).unwrap();
+        assert_eq!(
+            (actual.width(), actual.height()),
+            (expected.width(), expected.height()),
+            "{} changed size",
+            name
+        );
+        let differing = actual
+            .data()
+            .chunks(4)
+            .zip(expected.data().chunks(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE))
+            .count();
+        let share = differing as f64 / (actual.width() * actual.height()) as f64;
+        assert!(
+            share <= MAX_DIFFERING,
+            "{}: {:.2}% of pixels differ from the golden image",
+            name,
+            share * 100.0
+        );
+    }
+
+    #[test]
+    fn shapes_match_golden() {
+        check_golden("shapes");
+    }
+
+    #[test]
+    fn frames_match_golden() {
+        check_golden("frames");
+    }
+
+    #[test]
+    fn rasterize_rejects_oversized_images() {
+        let board = Board::default();
+        let svg = svg::render_svg(&board);
+        assert!(rasterize(&svg, 1000.0).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+//! HTML report: the board drawing followed by its research notes, in one
+//! self-contained file that opens in any browser.
+
+use super::svg::{escape, render_svg};
+use crate::model::Board;
+use crate::notes::render_markdown;
+
+const REPORT_TITLE: &str = "Research board";
+
+const REPORT_STYLE: &str = "body{font-family:ui-sans-serif,system-ui,sans-serif;color:#1f2937;max-width:960px;margin:2rem auto;padding:0 1rem;line-height:1.55}\
+figure{margin:0 0 2rem}figure svg{max-width:100%;height:auto;border:1px solid #e5e7eb;border-radius:8px}\
+a{color:#2563eb}pre{background:#f3f4f6;padding:.75rem;border-radius:6px;overflow-x:auto}\
+table{border-collapse:collapse}td,th{border:1px solid #d1d5db;padding:.25rem .5rem}";
+
+/// Render the board and its notes as a standalone HTML document
+pub fn render_report(board: &Board) -> String {
+    // The first notes heading makes a better page title than a generic one
+    let title = board
+        .notes
+        .lines()
+        .find_map(|line| line.trim_start().strip_prefix('#'))
+        .map(|heading| heading.trim_start_matches('#').trim())
+        .filter(|heading| !heading.is_empty())
+        .unwrap_or(REPORT_TITLE);
+    let svg = render_svg(board);
+    let notes = if board.notes.trim().is_empty() {
+        "<p><em>No notes yet.</em></p>\n".to_string()
+    } else {
+        render_markdown(&board.notes)
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<figure>\n{}</figure>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        REPORT_STYLE,
+        svg,
+        notes,
+    )
+}
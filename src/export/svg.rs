@@ -0,0 +1,596 @@
+//! Standalone SVG rendering of a board.
+//!
+//! The live canvas (`components::shape`) and this renderer share the style
+//! constants and label layout below, so exports look like the screen.
+
+use crate::model::{Point, Shape, ShapeType, Source};
+#[cfg(feature = "server")]
+use super::chart::{chart_elements, chart_markup};
+#[cfg(feature = "server")]
+use crate::model::{Board, Bounds, Group, CANVAS_HEIGHT, CANVAS_WIDTH};
+#[cfg(feature = "server")]
+use std::fmt::Write;
+
+pub const STROKE: &str = "#333";
+pub const LABEL_COLOR: &str = "#1f2937";
+pub const LABEL_FONT_SIZE: f64 = 14.0;
+pub const LINE_HEIGHT: f64 = 1.25;
+pub const FONT_FAMILY: &str = "ui-sans-serif, system-ui, sans-serif";
+#[cfg(feature = "server")]
+pub const BACKGROUND: &str = "#fafafa";
+
+pub const FRAME_FILL: &str = "rgba(255, 255, 255, 0.55)";
+pub const FRAME_STROKE: &str = "#9ca3af";
+pub const FRAME_TITLE_COLOR: &str = "#4b5563";
+/// Height of the strip above a frame that holds its title
+pub const FRAME_TITLE_HEIGHT: f64 = 22.0;
+
+pub const CHART_BORDER: &str = "#d1d5db";
+
+pub const CITATION_FILL: &str = "#2563eb";
+pub const CITATION_TEXT_COLOR: &str = "#ffffff";
+pub const CITATION_FONT_SIZE: f64 = 10.0;
+
+#[cfg(feature = "server")]
+const BIBLIOGRAPHY_TITLE: &str = "Sources";
+#[cfg(feature = "server")]
+const BIBLIOGRAPHY_FONT_SIZE: f64 = 12.0;
+#[cfg(feature = "server")]
+const BIBLIOGRAPHY_LINE_HEIGHT: f64 = 18.0;
+/// Space between the board content and the bibliography below it
+#[cfg(feature = "server")]
+const BIBLIOGRAPHY_GAP: f64 = 40.0;
+/// Longest bibliography entry before it is cut with an ellipsis
+#[cfg(feature = "server")]
+const BIBLIOGRAPHY_MAX_CHARS: usize = 110;
+
+/// Margin (world units) around the content of an exported board
+#[cfg(feature = "server")]
+pub const EXPORT_PADDING: f64 = 40.0;
+
+/// Where a shape's label goes: anchor point, `text-anchor` and one baseline per line
+pub struct LabelLayout {
+    pub x: f64,
+    pub anchor: &'static str,
+    pub lines: Vec<(f64, String)>,
+}
+
+/// Lay out a shape's label. Notes and text read top-down like paper, chart
+/// titles sit at the top, and everything else is centered.
+pub fn label_layout(shape: &Shape) -> LabelLayout {
+    let lines = shape.label_lines(LABEL_FONT_SIZE);
+    let line_step = LABEL_FONT_SIZE * LINE_HEIGHT;
+    let (x, y, anchor) = if matches!(shape.shape_type, ShapeType::StickyNote | ShapeType::Text) {
+        (shape.x + 12.0, shape.y + 12.0 + LABEL_FONT_SIZE, "start")
+    } else if matches!(shape.shape_type, ShapeType::Chart { .. }) {
+        // Chart titles run along the top edge
+        (shape.x + shape.width / 2.0, shape.y + 10.0 + LABEL_FONT_SIZE, "middle")
+    } else {
+        let (cx, cy) = shape.center();
+        let block_height = line_step * (lines.len() as f64 - 1.0);
+        (cx, cy - block_height / 2.0 + LABEL_FONT_SIZE * 0.35, "middle")
+    };
+    let lines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| (y + i as f64 * line_step, line))
+        .collect();
+    LabelLayout { x, anchor, lines }
+}
+
+/// Badge at a shape's top-right corner listing its citation numbers
+pub struct CitationMarker {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub text: String,
+}
+
+impl CitationMarker {
+    /// Baseline of the badge text, centered in the badge
+    pub fn text_x(&self) -> f64 {
+        self.x + self.width / 2.0
+    }
+
+    pub fn text_y(&self) -> f64 {
+        self.y + self.height / 2.0 + CITATION_FONT_SIZE * 0.35
+    }
+}
+
+/// Lay out the citation badge for a shape, or `None` when it cites nothing
+pub fn citation_marker(shape: &Shape, numbers: &[usize]) -> Option<CitationMarker> {
+    if numbers.is_empty() {
+        return None;
+    }
+    let text = numbers.iter().map(usize::to_string).collect::<Vec<_>>().join(",");
+    let height = CITATION_FONT_SIZE + 6.0;
+    let width = (text.chars().count() as f64 * CITATION_FONT_SIZE * 0.6 + 8.0).max(height);
+    let bounds = shape.bounds();
+    Some(CitationMarker {
+        x: bounds.max_x - width / 2.0,
+        y: bounds.min_y - height / 2.0,
+        width,
+        height,
+        text,
+    })
+}
+
+/// Hover text for a shape's citations: one entry per source with its quote
+pub fn citation_tooltip(shape: &Shape, bibliography: &[&Source]) -> String {
+    let mut entries: Vec<(usize, String)> = Vec::new();
+    for source in &shape.sources {
+        let Some(n) = bibliography.iter().position(|b| b.url == source.url).map(|i| i + 1) else {
+            continue;
+        };
+        let mut entry = format!("[{}] {}", n, source.display_title());
+        if source.title.is_some() {
+            entry.push_str(&format!("\n{}", source.url));
+        }
+        if let Some(quote) = source.quote.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            entry.push_str(&format!("\n\u{201c}{}\u{201d}", quote));
+        }
+        if !entries.iter().any(|(m, _)| *m == n) {
+            entries.push((n, entry));
+        }
+    }
+    entries.sort_by_key(|(n, _)| *n);
+    entries.into_iter().map(|(_, entry)| entry).collect::<Vec<_>>().join("\n\n")
+}
+
+pub fn points_attr(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn path_data(points: &[Point]) -> String {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| format!("{}{} {}", if i == 0 { "M" } else { "L" }, p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Length of an arrowhead along the shaft
+pub const ARROW_HEAD_SIZE: f64 = 14.0;
+
+/// Triangle for the head of an arrow, tip first
+pub fn arrow_head_points(shape: &Shape) -> [Point; 3] {
+    let ((x1, y1), (x2, y2)) = shape.endpoints();
+    let angle = (y2 - y1).atan2(x2 - x1);
+    let spread = std::f64::consts::PI / 7.0;
+    let wing = |a: f64| Point {
+        x: x2 - ARROW_HEAD_SIZE * a.cos(),
+        y: y2 - ARROW_HEAD_SIZE * a.sin(),
+    };
+    [Point { x: x2, y: y2 }, wing(angle - spread), wing(angle + spread)]
+}
+
+/// Region covering every visible shape and frame (including frame titles),
+/// or `None` when nothing is drawn
+#[cfg(feature = "server")]
+fn drawn_bounds(board: &Board) -> Option<Bounds> {
+    let shapes = board.render_order().into_iter().map(Shape::bounds);
+    let frames = board.groups.iter().filter(|g| g.is_frame()).map(|f| Bounds {
+        min_x: f.x,
+        min_y: f.y - FRAME_TITLE_HEIGHT,
+        max_x: f.x + f.width,
+        max_y: f.y + f.height,
+    });
+    shapes.chain(frames).reduce(|a, b| a.union(&b))
+}
+
+/// Region covering every visible shape and frame (including frame titles),
+/// padded for export. Empty boards export the default canvas area.
+#[cfg(feature = "server")]
+pub fn content_bounds(board: &Board) -> Bounds {
+    drawn_bounds(board)
+        .map(|b| b.padded(EXPORT_PADDING))
+        .unwrap_or(Bounds {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: CANVAS_WIDTH,
+            max_y: CANVAS_HEIGHT,
+        })
+}
+
+/// Text lines of the bibliography: a heading, then one numbered entry per source
+#[cfg(feature = "server")]
+fn bibliography_lines(bibliography: &[&Source]) -> Vec<String> {
+    let entries = bibliography.iter().enumerate().map(|(i, source)| {
+        let entry = if source.title.is_some() {
+            format!("{}. {} \u{2014} {}", i + 1, source.display_title(), source.url)
+        } else {
+            format!("{}. {}", i + 1, source.url)
+        };
+        if entry.chars().count() > BIBLIOGRAPHY_MAX_CHARS {
+            let cut: String = entry.chars().take(BIBLIOGRAPHY_MAX_CHARS - 1).collect();
+            format!("{}\u{2026}", cut)
+        } else {
+            entry
+        }
+    });
+    std::iter::once(BIBLIOGRAPHY_TITLE.to_string()).chain(entries).collect()
+}
+
+/// Region holding the bibliography, left-aligned under the board content, or
+/// `None` when no visible shape cites a source
+#[cfg(feature = "server")]
+pub fn bibliography_bounds(board: &Board) -> Option<Bounds> {
+    let bibliography = board.bibliography();
+    if bibliography.is_empty() {
+        return None;
+    }
+    let content = drawn_bounds(board)?;
+    let lines = bibliography_lines(&bibliography);
+    let longest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as f64;
+    let top = content.max_y + BIBLIOGRAPHY_GAP;
+    Some(Bounds {
+        min_x: content.min_x,
+        min_y: top,
+        max_x: content.min_x + longest * BIBLIOGRAPHY_FONT_SIZE * 0.55,
+        max_y: top + lines.len() as f64 * BIBLIOGRAPHY_LINE_HEIGHT,
+    })
+}
+
+/// Region exported for the whole board: the content plus its bibliography
+#[cfg(feature = "server")]
+pub fn export_bounds(board: &Board) -> Bounds {
+    let content = content_bounds(board);
+    match bibliography_bounds(board) {
+        Some(bibliography) => content.union(&bibliography.padded(EXPORT_PADDING)),
+        None => content,
+    }
+}
+
+/// Render the whole board, with its bibliography, as a standalone SVG document
+#[cfg(feature = "server")]
+pub fn render_svg(board: &Board) -> String {
+    render_svg_page(board, &export_bounds(board))
+}
+
+/// Render the part of the board inside `region` as a standalone SVG document.
+/// One world unit maps to one pixel of the document's intrinsic size.
+#[cfg(feature = "server")]
+pub fn render_svg_region(board: &Board, region: &Bounds) -> String {
+    render_document(board, region, false)
+}
+
+/// Like [`render_svg_region`], but also draws the bibliography under the
+/// content for the parts of it that fall inside `region`
+#[cfg(feature = "server")]
+pub fn render_svg_page(board: &Board, region: &Bounds) -> String {
+    render_document(board, region, true)
+}
+
+#[cfg(feature = "server")]
+fn render_document(board: &Board, region: &Bounds, with_bibliography: bool) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{w}" height="{h}" viewBox="{x} {y} {w} {h}">"#,
+        x = region.min_x,
+        y = region.min_y,
+        w = region.width(),
+        h = region.height(),
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{BACKGROUND}"/>"#,
+        region.min_x,
+        region.min_y,
+        region.width(),
+        region.height(),
+    );
+    for frame in board.groups.iter().filter(|g| g.is_frame()) {
+        write_frame(&mut out, frame);
+    }
+    let bibliography = board.bibliography();
+    for shape in board.render_order() {
+        write_shape(&mut out, shape, &bibliography);
+    }
+    if let Some(bounds) = bibliography_bounds(board).filter(|_| with_bibliography) {
+        write_bibliography(&mut out, &bibliography, &bounds);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(feature = "server")]
+fn write_frame(out: &mut String, frame: &Group) {
+    let _ = writeln!(
+        out,
+        r#"<text x="{}" y="{}" font-family="{FONT_FAMILY}" font-size="13" font-weight="600" fill="{FRAME_TITLE_COLOR}">{}</text>"#,
+        frame.x + 4.0,
+        frame.y - 7.0,
+        escape(&frame.title),
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="8" fill="{FRAME_FILL}" stroke="{FRAME_STROKE}" stroke-width="1.5"/>"#,
+        frame.x, frame.y, frame.width, frame.height,
+    );
+}
+
+#[cfg(feature = "server")]
+fn write_shape(out: &mut String, shape: &Shape, bibliography: &[&Source]) {
+    let (x, y, w, h) = (shape.x, shape.y, shape.width, shape.height);
+    let color = escape(&shape.color);
+    let _ = writeln!(out, r#"<g data-shape-id="{}">"#, shape.id);
+    let _ = match &shape.shape_type {
+        ShapeType::Rectangle => writeln!(
+            out,
+            r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{color}" stroke="{STROKE}" stroke-width="2" rx="8"/>"#
+        ),
+        ShapeType::Circle => writeln!(
+            out,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{color}" stroke="{STROKE}" stroke-width="2"/>"#,
+            x + w / 2.0,
+            y + h / 2.0,
+            w / 2.0,
+        ),
+        ShapeType::Ellipse => writeln!(
+            out,
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" fill="{color}" stroke="{STROKE}" stroke-width="2"/>"#,
+            x + w / 2.0,
+            y + h / 2.0,
+            w / 2.0,
+            h / 2.0,
+        ),
+        ShapeType::Line => writeln!(
+            out,
+            r#"<line x1="{x}" y1="{y}" x2="{}" y2="{}" stroke="{color}" stroke-width="3" stroke-linecap="round"/>"#,
+            x + w,
+            y + h,
+        ),
+        ShapeType::Arrow { headless, .. } => {
+            let _ = writeln!(
+                out,
+                r#"<line x1="{x}" y1="{y}" x2="{}" y2="{}" stroke="{color}" stroke-width="3" stroke-linecap="round"/>"#,
+                x + w,
+                y + h,
+            );
+            if *headless {
+                Ok(())
+            } else {
+                writeln!(
+                    out,
+                    r#"<polygon points="{}" fill="{color}" stroke="{color}" stroke-width="1" stroke-linejoin="round"/>"#,
+                    points_attr(&arrow_head_points(shape)),
+                )
+            }
+        }
+        ShapeType::Diamond | ShapeType::Polygon { .. } => writeln!(
+            out,
+            r#"<polygon points="{}" fill="{color}" stroke="{STROKE}" stroke-width="2" stroke-linejoin="round"/>"#,
+            points_attr(&shape.absolute_points()),
+        ),
+        ShapeType::Freehand { .. } => writeln!(
+            out,
+            r#"<path d="{}" fill="none" stroke="{color}" stroke-width="3" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            path_data(&shape.absolute_points()),
+        ),
+        ShapeType::StickyNote => {
+            let fold = (w.min(h) * 0.15).min(24.0);
+            let (right, bottom) = (x + w, y + h);
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{w}" height="{h}" fill="rgba(0, 0, 0, 0.12)" rx="2"/>
+<path d="M{x} {y} L{right} {y} L{right} {} L{} {bottom} L{x} {bottom} Z" fill="{color}" stroke="rgba(0, 0, 0, 0.2)" stroke-width="1"/>
+<path d="M{right} {} L{} {} L{} {bottom} Z" fill="rgba(0, 0, 0, 0.15)"/>"#,
+                x + 3.0,
+                y + 4.0,
+                bottom - fold,
+                right - fold,
+                bottom - fold,
+                right - fold,
+                bottom - fold,
+                right - fold,
+            )
+        }
+        ShapeType::Image { href } => writeln!(
+            out,
+            r##"<image href="{href}" xlink:href="{href}" x="{x}" y="{y}" width="{w}" height="{h}" preserveAspectRatio="xMidYMid meet"/>
+<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="none" stroke="#d1d5db" stroke-width="1"/>"##,
+            href = escape(href),
+        ),
+        // Text has no body of its own, only the label
+        ShapeType::Text => Ok(()),
+        ShapeType::Chart { data } => {
+            let _ = writeln!(
+                out,
+                r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{color}" stroke="{CHART_BORDER}" stroke-width="1" rx="8"/>"#
+            );
+            out.push_str(&chart_markup(&chart_elements(shape, data)));
+            Ok(())
+        }
+    };
+    write_label(out, shape);
+    write_citation(out, shape, bibliography);
+    out.push_str("</g>\n");
+}
+
+#[cfg(feature = "server")]
+fn write_citation(out: &mut String, shape: &Shape, bibliography: &[&Source]) {
+    let Some(marker) = citation_marker(shape, &shape.citation_numbers(bibliography)) else {
+        return;
+    };
+    let _ = writeln!(
+        out,
+        r#"<g><title>{}</title><rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{CITATION_FILL}"/><text x="{}" y="{}" text-anchor="middle" font-family="{FONT_FAMILY}" font-size="{CITATION_FONT_SIZE}" font-weight="600" fill="{CITATION_TEXT_COLOR}">{}</text></g>"#,
+        escape(&citation_tooltip(shape, bibliography)),
+        marker.x,
+        marker.y,
+        marker.width,
+        marker.height,
+        marker.height / 2.0,
+        marker.text_x(),
+        marker.text_y(),
+        escape(&marker.text),
+    );
+}
+
+#[cfg(feature = "server")]
+fn write_bibliography(out: &mut String, bibliography: &[&Source], bounds: &Bounds) {
+    for (i, line) in bibliography_lines(bibliography).iter().enumerate() {
+        let y = bounds.min_y + BIBLIOGRAPHY_FONT_SIZE + i as f64 * BIBLIOGRAPHY_LINE_HEIGHT;
+        let text = format!(
+            r#"<text x="{}" y="{y}" font-family="{FONT_FAMILY}" font-size="{BIBLIOGRAPHY_FONT_SIZE}"{} fill="{LABEL_COLOR}">{}</text>"#,
+            bounds.min_x,
+            if i == 0 { r#" font-weight="600""# } else { "" },
+            escape(line),
+        );
+        // Entries link to their source; the heading is plain text
+        let _ = match i.checked_sub(1).and_then(|n| bibliography.get(n)) {
+            Some(source) => writeln!(out, r#"<a href="{}">{}</a>"#, escape(&source.url), text),
+            None => writeln!(out, "{}", text),
+        };
+    }
+}
+
+#[cfg(feature = "server")]
+fn write_label(out: &mut String, shape: &Shape) {
+    let layout = label_layout(shape);
+    if layout.lines.is_empty() {
+        return;
+    }
+    let _ = write!(
+        out,
+        r#"<text text-anchor="{}" font-family="{FONT_FAMILY}" font-size="{LABEL_FONT_SIZE}" fill="{LABEL_COLOR}">"#,
+        layout.anchor,
+    );
+    for (line_y, line) in &layout.lines {
+        let _ = write!(out, r#"<tspan x="{}" y="{}">{}</tspan>"#, layout.x, line_y, escape(line));
+    }
+    out.push_str("</text>\n");
+}
+
+/// Escape text for use in XML content and attribute values
+#[cfg(feature = "server")]
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::model::Layer;
+    use resvg::usvg;
+
+    fn golden_board(name: &str) -> Board {
+        let path = format!("{}/tests/golden/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn parse(svg: &str) -> usvg::Tree {
+        usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap()
+    }
+
+    #[test]
+    fn renders_every_shape_into_a_valid_document() {
+        let board = golden_board("shapes");
+        let svg = render_svg(&board);
+        let tree = parse(&svg);
+        let bounds = export_bounds(&board);
+        assert_eq!(
+            (tree.size().width(), tree.size().height()),
+            (bounds.width() as f32, bounds.height() as f32)
+        );
+        assert!(svg.starts_with(&format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{}" height="{}" viewBox="{} {} "#,
+            bounds.width(),
+            bounds.height(),
+            bounds.min_x,
+            bounds.min_y
+        )));
+        for shape in &board.shapes {
+            assert!(svg.contains(&format!(r#"<g data-shape-id="{}">"#, shape.id)), "{:?} is missing", shape.shape_type);
+        }
+        assert_eq!(svg.matches("<g data-shape-id=").count(), board.shapes.len());
+    }
+
+    #[test]
+    fn frames_headless_arrows_and_titles() {
+        let board = golden_board("frames");
+        let svg = render_svg(&board);
+        parse(&svg);
+        for frame in board.groups.iter().filter(|g| g.is_frame()) {
+            assert!(svg.contains(&format!(">{}</text>", frame.title)));
+        }
+        let arrows = board.shapes.iter().filter(|s| matches!(s.shape_type, ShapeType::Arrow { .. }));
+        let headed = arrows.filter(|s| matches!(s.shape_type, ShapeType::Arrow { headless: false, .. }));
+        assert_eq!(svg.matches(r#"stroke-width="1" stroke-linejoin="round"/>"#).count(), headed.count());
+        // Frame titles sit above the frame and are inside the exported region
+        let top = board.groups.iter().map(|g| g.y).fold(f64::INFINITY, f64::min);
+        assert!(export_bounds(&board).min_y <= top - FRAME_TITLE_HEIGHT);
+    }
+
+    #[test]
+    fn escapes_text_and_skips_hidden_layers() {
+        let mut board = Board::default();
+        let mut shown = Shape::new(ShapeType::Rectangle, 0.0, 0.0, 400.0, 80.0, "#fff".to_string());
+        shown.label = Some("Costs <falling> & \"fast\"".to_string());
+        let mut layer = Layer::new("Hidden".to_string());
+        layer.visible = false;
+        let mut hidden = Shape::new(ShapeType::Rectangle, 500.0, 0.0, 100.0, 80.0, "#fff".to_string());
+        hidden.layer = Some(layer.id);
+        board.layers.push(layer);
+        board.shapes.extend([shown.clone(), hidden.clone()]);
+
+        let svg = render_svg(&board);
+        parse(&svg);
+        assert!(svg.contains("Costs &lt;falling&gt; &amp; &quot;fast&quot;"));
+        assert!(!svg.contains(&hidden.id.to_string()));
+        // The hidden shape does not widen the export either
+        assert_eq!(export_bounds(&board).max_x, 400.0 + EXPORT_PADDING);
+    }
+
+    #[test]
+    fn cited_sources_get_markers_and_a_bibliography() {
+        let mut board = Board::default();
+        let mut shape = Shape::new(ShapeType::Rectangle, 0.0, 0.0, 200.0, 80.0, "#fff".to_string());
+        shape.sources = vec![Source {
+            url: "https://example.org/solar?a=1&b=2".to_string(),
+            title: Some("Solar report".to_string()),
+            quote: None,
+        }];
+        board.shapes.push(shape);
+
+        let svg = render_svg(&board);
+        parse(&svg);
+        assert!(svg.contains(&format!(r#"fill="{}"/><text"#, CITATION_FILL)));
+        assert!(svg.contains("<title>[1] Solar report"));
+        assert!(svg.contains(r#"<a href="https://example.org/solar?a=1&amp;b=2">"#));
+        assert!(svg.contains(">1. Solar report \u{2014} https://example.org/solar?a=1&amp;b=2</text>"));
+        assert!(svg.contains(&format!(">{}</text>", BIBLIOGRAPHY_TITLE)));
+        // Regions without the bibliography leave it out
+        assert!(!render_svg_region(&board, &content_bounds(&board)).contains("<a href"));
+        assert!(export_bounds(&board).max_y > content_bounds(&board).max_y);
+    }
+
+    #[test]
+    fn an_empty_board_exports_the_default_canvas() {
+        let svg = render_svg(&Board::default());
+        let tree = parse(&svg);
+        assert_eq!(
+            (tree.size().width(), tree.size().height()),
+            (CANVAS_WIDTH as f32, CANVAS_HEIGHT as f32)
+        );
+        assert!(!svg.contains("data-shape-id"));
+    }
+}
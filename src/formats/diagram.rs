@@ -0,0 +1,294 @@
+//! Graph description shared by the Mermaid and DOT parsers, and its
+//! conversion into laid-out board shapes.
+
+use super::{dot, mermaid};
+use crate::layout::{self, Direction};
+use crate::model::{Board, Group, Shape, ShapeType};
+use std::collections::HashMap;
+
+/// Padding between a subgraph's nodes and its frame
+const CLUSTER_PADDING: f64 = 30.0;
+const NODE_HEIGHT: f64 = 60.0;
+const MIN_NODE_WIDTH: f64 = 120.0;
+const MAX_NODE_WIDTH: f64 = 260.0;
+const EDGE_COLOR: &str = "#475569";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NodeShape {
+    #[default]
+    Rectangle,
+    Ellipse,
+    Circle,
+    Diamond,
+    Note,
+    Text,
+}
+
+impl NodeShape {
+    fn default_fill(&self) -> &'static str {
+        match self {
+            NodeShape::Rectangle => "#dbeafe",
+            NodeShape::Ellipse | NodeShape::Circle => "#dcfce7",
+            NodeShape::Diamond => "#fef3c7",
+            NodeShape::Note => "#fef08a",
+            NodeShape::Text => "transparent",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub id: String,
+    pub label: String,
+    pub shape: NodeShape,
+    pub fill: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+    pub directed: bool,
+}
+
+/// A titled group of nodes (Mermaid `subgraph`, DOT `cluster_*`), drawn as a frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cluster {
+    pub title: String,
+    pub nodes: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diagram {
+    pub direction: Direction,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub clusters: Vec<Cluster>,
+}
+
+impl Diagram {
+    /// Add a node, or update the label/shape of an existing one when given
+    pub fn upsert_node(&mut self, id: &str, label: Option<String>, shape: Option<NodeShape>) {
+        match self.nodes.iter_mut().find(|n| n.id == id) {
+            Some(node) => {
+                if let Some(label) = label {
+                    node.label = label;
+                }
+                if let Some(shape) = shape {
+                    node.shape = shape;
+                }
+            }
+            None => self.nodes.push(Node {
+                id: id.to_string(),
+                label: label.unwrap_or_else(|| id.to_string()),
+                shape: shape.unwrap_or_default(),
+                fill: None,
+            }),
+        }
+    }
+
+    pub fn node_mut(&mut self, id: &str) -> Option<&mut Node> {
+        self.nodes.iter_mut().find(|n| n.id == id)
+    }
+
+    /// Lay the diagram out with its top-left corner at `origin` and build the
+    /// shapes: one per node, an arrow bound to both ends per edge, and a frame
+    /// per cluster. Also returns each node id's shape.
+    pub fn to_board(&self, origin: (f64, f64)) -> (Board, HashMap<String, Shape>) {
+        let sizes: Vec<(f64, f64)> = self.nodes.iter().map(node_size).collect();
+        let index: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect();
+        let edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .filter_map(|e| Some((*index.get(e.from.as_str())?, *index.get(e.to.as_str())?)))
+            .collect();
+        let positions = layout::layered(&sizes, &edges, self.direction);
+
+        let mut board = Board::default();
+        let mut shapes = HashMap::new();
+        for ((node, (w, h)), (x, y)) in self.nodes.iter().zip(&sizes).zip(&positions) {
+            let shape_type = match node.shape {
+                NodeShape::Rectangle => ShapeType::Rectangle,
+                NodeShape::Ellipse => ShapeType::Ellipse,
+                NodeShape::Circle => ShapeType::Circle,
+                NodeShape::Diamond => ShapeType::Diamond,
+                NodeShape::Note => ShapeType::StickyNote,
+                NodeShape::Text => ShapeType::Text,
+            };
+            let fill = node.fill.clone().unwrap_or_else(|| node.shape.default_fill().to_string());
+            let mut shape = Shape::new(shape_type, origin.0 + x, origin.1 + y, *w, *h, fill);
+            shape.label = Some(node.label.clone()).filter(|l| !l.is_empty());
+            shapes.insert(node.id.clone(), shape.clone());
+            board.shapes.push(shape);
+        }
+        for edge in &self.edges {
+            let (Some(from), Some(to)) = (shapes.get(&edge.from), shapes.get(&edge.to)) else {
+                continue;
+            };
+            if from.id == to.id {
+                continue;
+            }
+            let mut arrow = Shape::connector(from, to, EDGE_COLOR.to_string());
+            if let ShapeType::Arrow { headless, .. } = &mut arrow.shape_type {
+                *headless = !edge.directed;
+            }
+            arrow.label = edge.label.clone();
+            board.shapes.push(arrow);
+        }
+        for cluster in &self.clusters {
+            let members: Vec<_> = cluster
+                .nodes
+                .iter()
+                .filter_map(|id| shapes.get(id).map(|s| s.id))
+                .collect();
+            let Some(bounds) = board.members_bounds(&members) else { continue };
+            board
+                .groups
+                .push(Group::frame(cluster.title.clone(), members, bounds.padded(CLUSTER_PADDING)));
+        }
+        (board, shapes)
+    }
+}
+
+/// Node size from its label: wide enough for one line up to a limit, then wrapped
+fn node_size(node: &Node) -> (f64, f64) {
+    let chars = node.label.chars().count() as f64;
+    let width = (chars * 8.0 + 40.0).clamp(MIN_NODE_WIDTH, MAX_NODE_WIDTH);
+    let lines = (chars * 8.0 / (width - 40.0)).ceil().max(1.0);
+    let height = NODE_HEIGHT.max(lines * 18.0 + 24.0);
+    match node.shape {
+        // Text inside a diamond or circle only fits in the middle
+        NodeShape::Diamond => (width * 1.3, height * 1.4),
+        NodeShape::Circle => {
+            let size = width.max(height);
+            (size, size)
+        }
+        _ => (width, height),
+    }
+}
+
+/// Source language of a diagram
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagramFormat {
+    Mermaid,
+    Dot,
+}
+
+impl DiagramFormat {
+    #[cfg(feature = "server")]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "mermaid" | "flowchart" => Some(Self::Mermaid),
+            "dot" | "graphviz" | "gv" => Some(Self::Dot),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the first statement
+    pub fn detect(source: &str) -> Option<Self> {
+        let first = source
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with("%%") && !l.starts_with("//") && !l.starts_with('#'))?;
+        let keyword = first.split_whitespace().next()?.to_lowercase();
+        match keyword.as_str() {
+            "digraph" | "strict" => Some(Self::Dot),
+            // `graph [name] {` is DOT; Mermaid braces only appear in node shapes
+            "graph" if first.split('{').next().is_some_and(|head| {
+                first.contains('{') && !head.contains(';') && head.split_whitespace().count() <= 2
+            }) =>
+            {
+                Some(Self::Dot)
+            }
+            "graph" | "flowchart" => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+/// Parse Mermaid or DOT source, detecting the format when not given
+pub fn parse(source: &str, format: Option<DiagramFormat>) -> Result<Diagram, String> {
+    let format = format
+        .or_else(|| DiagramFormat::detect(source))
+        .ok_or_else(|| "Unrecognised diagram: expected a Mermaid `flowchart`/`graph` or a DOT `digraph`/`graph`".to_string())?;
+    let diagram = match format {
+        DiagramFormat::Mermaid => mermaid::parse(source)?,
+        DiagramFormat::Dot => dot::parse(source)?,
+    };
+    if diagram.nodes.is_empty() {
+        return Err("The diagram has no nodes".to_string());
+    }
+    Ok(diagram)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_the_format_from_the_first_statement() {
+        assert_eq!(DiagramFormat::detect("%% notes\nflowchart TD\nA-->B"), Some(DiagramFormat::Mermaid));
+        assert_eq!(DiagramFormat::detect("graph LR\nA-->B"), Some(DiagramFormat::Mermaid));
+        assert_eq!(DiagramFormat::detect("graph TD; A-->B{x}"), Some(DiagramFormat::Mermaid));
+        assert_eq!(DiagramFormat::detect("digraph G { a -> b }"), Some(DiagramFormat::Dot));
+        assert_eq!(DiagramFormat::detect("graph G {\n a -- b }"), Some(DiagramFormat::Dot));
+        assert_eq!(DiagramFormat::detect("// comment\ngraph {"), Some(DiagramFormat::Dot));
+        assert_eq!(DiagramFormat::detect("sequenceDiagram"), None);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn format_names_are_parsed() {
+        assert_eq!(DiagramFormat::parse("Graphviz"), Some(DiagramFormat::Dot));
+        assert_eq!(DiagramFormat::parse("flowchart"), Some(DiagramFormat::Mermaid));
+        assert_eq!(DiagramFormat::parse("plantuml"), None);
+    }
+
+    #[test]
+    fn parse_needs_a_known_format_and_nodes() {
+        assert!(parse("pie title Pets", None).unwrap_err().starts_with("Unrecognised diagram"));
+        assert_eq!(parse("digraph {}", None).unwrap_err(), "The diagram has no nodes");
+        assert_eq!(parse("graph TD\nA --> B", Some(DiagramFormat::Mermaid)).unwrap().nodes.len(), 2);
+    }
+
+    #[test]
+    fn builds_bound_arrows_and_frames_in_flow_order() {
+        let diagram = parse(
+            "flowchart TD\nsubgraph Supply\nA[Panel] --> B{{Inverter}}\nend\nB --- C[Home]\nC --> C",
+            None,
+        )
+        .unwrap();
+        let (board, shapes) = diagram.to_board((100.0, 50.0));
+        let (a, b, c) = (&shapes["A"], &shapes["B"], &shapes["C"]);
+        assert_eq!(a.shape_type, ShapeType::Rectangle);
+        assert_eq!(b.shape_type, ShapeType::Diamond);
+        assert_eq!(a.label.as_deref(), Some("Panel"));
+        // Top-down: every edge points further down the board
+        assert!(a.y + a.height <= b.y && b.y + b.height <= c.y);
+        let top_left = board.shapes.iter().map(|s| s.bounds().min_y).fold(f64::INFINITY, f64::min);
+        assert_eq!(top_left, 50.0);
+
+        // The self-loop is dropped; the undirected edge has no head
+        let arrows: Vec<_> = board
+            .shapes
+            .iter()
+            .filter_map(|s| match s.shape_type {
+                ShapeType::Arrow { start, end, headless } => Some((start, end, headless)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(arrows, vec![(Some(a.id), Some(b.id), false), (Some(b.id), Some(c.id), true)]);
+
+        assert_eq!(board.groups.len(), 1);
+        let frame = &board.groups[0];
+        assert!(frame.is_frame());
+        assert_eq!(frame.members, vec![a.id, b.id]);
+    }
+}
@@ -0,0 +1,520 @@
+//! Parser for a useful subset of Graphviz DOT.
+//!
+//! Supported: `graph`/`digraph` (optionally `strict`), node statements with
+//! `label`, `shape`, `fillcolor`/`color`, `node`/`edge` defaults, edge chains
+//! with labels, `rankdir`, and `subgraph cluster_*` as frames. Other
+//! attributes are ignored.
+
+use super::diagram::{Cluster, Diagram, Edge, NodeShape};
+use crate::layout::Direction;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// Identifier, number or quoted/HTML string
+    Id(String),
+    Arrow,
+    Line,
+    Open(char),
+    Close(char),
+    Equals,
+    Semicolon,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line_start = true;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\n' => {
+                line_start = true;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            // `#` lines are C preprocessor output, treated as comments
+            '#' if line_start => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            '-' if chars.get(i + 1) == Some(&'>') => {
+                tokens.push(Token::Arrow);
+                i += 2;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                tokens.push(Token::Line);
+                i += 2;
+            }
+            '[' | '{' => {
+                tokens.push(Token::Open(c));
+                i += 1;
+            }
+            ']' | '}' => {
+                tokens.push(Token::Close(c));
+                i += 1;
+            }
+            // Ports (`node:port:compass`) don't affect the layout
+            ':' => {
+                i += 1;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                continue;
+            }
+            '=' => {
+                tokens.push(Token::Equals);
+                i += 1;
+            }
+            ';' => {
+                tokens.push(Token::Semicolon);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '"' => {
+                let mut value = String::new();
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        i += 1;
+                        match chars[i] {
+                            'n' | 'l' | 'r' => value.push('\n'),
+                            '\n' => {}
+                            other => value.push(other),
+                        }
+                    } else {
+                        value.push(chars[i]);
+                    }
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err("Unterminated string".to_string());
+                }
+                i += 1;
+                tokens.push(Token::Id(value));
+            }
+            '<' => {
+                // HTML-like label: keep the text, drop the tags
+                let mut depth = 0;
+                let mut value = String::new();
+                let mut in_tag = false;
+                while i < chars.len() {
+                    match chars[i] {
+                        '<' => {
+                            depth += 1;
+                            if depth > 1 {
+                                in_tag = true;
+                            }
+                        }
+                        '>' => {
+                            depth -= 1;
+                            in_tag = false;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        ch if !in_tag => value.push(ch),
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                i += 1;
+                tokens.push(Token::Id(value.trim().to_string()));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                    i += 1;
+                }
+                // A leading '-' belongs to a negative number
+                if i == start {
+                    i += 1;
+                    while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                        i += 1;
+                    }
+                }
+                tokens.push(Token::Id(chars[start..i].iter().collect()));
+            }
+            other => return Err(format!("Unexpected character '{}'", other)),
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+type Attributes = HashMap<String, String>;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    diagram: Diagram,
+    directed: bool,
+    /// Indices into `diagram.clusters` of the enclosing clusters
+    clusters: Vec<usize>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            other => Err(format!("Expected {:?}, found {:?}", token, other)),
+        }
+    }
+
+    fn id(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Id(id)) => Ok(id),
+            other => Err(format!("Expected an identifier, found {:?}", other)),
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), String> {
+        if matches!(self.peek(), Some(Token::Id(k)) if k.eq_ignore_ascii_case("strict")) {
+            self.pos += 1;
+        }
+        let kind = self.id()?.to_lowercase();
+        self.directed = match kind.as_str() {
+            "digraph" => true,
+            "graph" => false,
+            _ => return Err(format!("Expected `graph` or `digraph`, found '{}'", kind)),
+        };
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.pos += 1;
+        }
+        self.expect(Token::Open('{'))?;
+        self.statements(&mut Attributes::new(), &mut Attributes::new())
+    }
+
+    /// Statements up to the closing `}`. Defaults are scoped to the block.
+    fn statements(&mut self, node_defaults: &mut Attributes, edge_defaults: &mut Attributes) -> Result<(), String> {
+        loop {
+            match self.peek() {
+                None => return Err("Missing closing '}'".to_string()),
+                Some(Token::Close('}')) => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(Token::Semicolon) | Some(Token::Comma) => {
+                    self.pos += 1;
+                }
+                _ => self.statement(node_defaults, edge_defaults)?,
+            }
+        }
+    }
+
+    fn statement(&mut self, node_defaults: &mut Attributes, edge_defaults: &mut Attributes) -> Result<(), String> {
+        if let Some(Token::Id(keyword)) = self.peek() {
+            match keyword.to_lowercase().as_str() {
+                "node" | "edge" | "graph" if self.tokens.get(self.pos + 1) == Some(&Token::Open('[')) => {
+                    let keyword = keyword.to_lowercase();
+                    self.pos += 1;
+                    let attributes = self.attribute_list()?;
+                    match keyword.as_str() {
+                        "node" => node_defaults.extend(attributes),
+                        "edge" => edge_defaults.extend(attributes),
+                        _ => self.graph_attributes(&attributes),
+                    }
+                    return Ok(());
+                }
+                _ => {}
+            }
+            if self.tokens.get(self.pos + 1) == Some(&Token::Equals) {
+                let key = self.id()?;
+                self.pos += 1;
+                let value = self.id()?;
+                self.graph_attributes(&Attributes::from([(key, value)]));
+                return Ok(());
+            }
+        }
+        let mut chain = vec![self.endpoint(node_defaults, edge_defaults)?];
+        let mut directed_links = Vec::new();
+        while let Some(link @ (Token::Arrow | Token::Line)) = self.peek().cloned() {
+            self.pos += 1;
+            directed_links.push(link == Token::Arrow);
+            chain.push(self.endpoint(node_defaults, edge_defaults)?);
+        }
+        let attributes = if self.peek() == Some(&Token::Open('[')) {
+            self.attribute_list()?
+        } else {
+            Attributes::new()
+        };
+        if chain.len() == 1 {
+            for id in &chain[0] {
+                self.apply_node_attributes(id, &attributes);
+            }
+            return Ok(());
+        }
+        let mut edge_attributes = edge_defaults.clone();
+        edge_attributes.extend(attributes);
+        let label = edge_attributes.get("label").cloned().filter(|l| !l.is_empty());
+        let undirected_style = edge_attributes
+            .get("dir")
+            .is_some_and(|d| d == "none");
+        for (pair, directed) in chain.windows(2).zip(directed_links) {
+            for from in &pair[0] {
+                for to in &pair[1] {
+                    self.diagram.edges.push(Edge {
+                        from: from.clone(),
+                        to: to.clone(),
+                        label: label.clone(),
+                        directed: directed && self.directed && !undirected_style,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A node id, or a subgraph standing for all of its nodes
+    fn endpoint(&mut self, node_defaults: &Attributes, edge_defaults: &Attributes) -> Result<Vec<String>, String> {
+        let is_subgraph = match self.peek() {
+            Some(Token::Open('{')) => true,
+            Some(Token::Id(k)) => k.eq_ignore_ascii_case("subgraph"),
+            _ => false,
+        };
+        if is_subgraph {
+            return self.subgraph(node_defaults, edge_defaults);
+        }
+        let id = self.id()?;
+        if !self.diagram.nodes.iter().any(|n| n.id == id) {
+            self.diagram.upsert_node(&id, None, Some(NodeShape::Ellipse));
+            self.apply_node_attributes(&id, node_defaults);
+            if let Some(&cluster) = self.clusters.last() {
+                self.diagram.clusters[cluster].nodes.push(id.clone());
+            }
+        }
+        Ok(vec![id])
+    }
+
+    fn subgraph(&mut self, node_defaults: &Attributes, edge_defaults: &Attributes) -> Result<Vec<String>, String> {
+        let mut name = None;
+        if self.eat(&Token::Id("subgraph".to_string())) {
+            if let Some(Token::Id(id)) = self.peek().cloned() {
+                self.pos += 1;
+                name = Some(id);
+            }
+        }
+        self.expect(Token::Open('{'))?;
+        let is_cluster = name.as_deref().is_some_and(|n| n.starts_with("cluster"));
+        if is_cluster {
+            let fallback = name.as_deref().unwrap_or_default().trim_start_matches("cluster");
+            self.diagram.clusters.push(Cluster {
+                title: fallback.trim_start_matches('_').to_string(),
+                nodes: Vec::new(),
+            });
+            self.clusters.push(self.diagram.clusters.len() - 1);
+        }
+        let before = self.diagram.nodes.len();
+        let edges_before = self.diagram.edges.len();
+        self.statements(&mut node_defaults.clone(), &mut edge_defaults.clone())?;
+        if is_cluster {
+            self.clusters.pop();
+        }
+        // Nodes declared in the block, plus existing nodes it mentions
+        let mut members: Vec<String> = self.diagram.nodes[before..].iter().map(|n| n.id.clone()).collect();
+        for edge in &self.diagram.edges[edges_before..] {
+            for id in [&edge.from, &edge.to] {
+                if !members.contains(id) {
+                    members.push(id.clone());
+                }
+            }
+        }
+        Ok(members)
+    }
+
+    fn attribute_list(&mut self) -> Result<Attributes, String> {
+        let mut attributes = Attributes::new();
+        while self.eat(&Token::Open('[')) {
+            loop {
+                match self.peek() {
+                    Some(Token::Close(']')) => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some(Token::Comma) | Some(Token::Semicolon) => {
+                        self.pos += 1;
+                    }
+                    _ => {
+                        let key = self.id()?;
+                        let value = if self.eat(&Token::Equals) { self.id()? } else { "true".to_string() };
+                        attributes.insert(key.to_lowercase(), value);
+                    }
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// `rankdir` on the root graph, `label` on a cluster
+    fn graph_attributes(&mut self, attributes: &Attributes) {
+        for (key, value) in attributes {
+            match (key.to_lowercase().as_str(), self.clusters.last()) {
+                ("rankdir", None) => {
+                    if let Some(direction) = Direction::parse(value) {
+                        self.diagram.direction = direction;
+                    }
+                }
+                ("label", Some(&cluster)) => self.diagram.clusters[cluster].title = value.clone(),
+                _ => {}
+            }
+        }
+    }
+
+    fn apply_node_attributes(&mut self, id: &str, attributes: &Attributes) {
+        let Some(node) = self.diagram.node_mut(id) else { return };
+        if let Some(label) = attributes.get("label") {
+            // `\N` stands for the node's name
+            node.label = label.replace("\\N", id);
+        }
+        if let Some(shape) = attributes.get("shape") {
+            node.shape = match shape.to_lowercase().as_str() {
+                "box" | "rect" | "rectangle" | "square" | "component" | "box3d" | "cylinder" | "folder" | "tab" => {
+                    NodeShape::Rectangle
+                }
+                "circle" | "doublecircle" | "point" => NodeShape::Circle,
+                "diamond" | "mdiamond" => NodeShape::Diamond,
+                "note" => NodeShape::Note,
+                "plaintext" | "plain" | "none" | "underline" => NodeShape::Text,
+                _ => NodeShape::Ellipse,
+            };
+        }
+        let filled = attributes.get("style").is_some_and(|s| s.contains("filled"));
+        if let Some(color) = attributes
+            .get("fillcolor")
+            .or_else(|| attributes.get("color").filter(|_| filled))
+        {
+            node.fill = Some(color.clone());
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Diagram, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        diagram: Diagram::default(),
+        directed: false,
+        clusters: Vec::new(),
+    };
+    parser.graph()?;
+    Ok(parser.diagram)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nodes_edges_and_attributes() {
+        let diagram = parse(
+            r##"
+            // Energy flow
+            strict digraph energy {
+                rankdir = LR;
+                node [shape=box];
+                sun [label="The Sun", shape=circle];
+                panel [style=filled, color="#fde68a"];
+                /* edges */
+                sun -> panel -> battery [label="charges"];
+                battery -> home
+            }
+            "##,
+        )
+        .unwrap();
+        assert_eq!(diagram.direction, Direction::LeftRight);
+        let nodes: Vec<_> = diagram
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n.label.as_str(), n.shape, n.fill.as_deref()))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("sun", "The Sun", NodeShape::Circle, None),
+                ("panel", "panel", NodeShape::Rectangle, Some("#fde68a")),
+                ("battery", "battery", NodeShape::Rectangle, None),
+                ("home", "home", NodeShape::Rectangle, None),
+            ]
+        );
+        let edges: Vec<_> = diagram
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.label.as_deref(), e.directed))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("sun", "panel", Some("charges"), true),
+                ("panel", "battery", Some("charges"), true),
+                ("battery", "home", None, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn undirected_graphs_and_clusters() {
+        let diagram = parse(
+            "graph {\n\
+               subgraph cluster_grid { label=\"Grid\"; plant -- line }\n\
+               line -- town\n\
+             }",
+        )
+        .unwrap();
+        assert!(diagram.edges.iter().all(|e| !e.directed));
+        assert_eq!(diagram.clusters.len(), 1);
+        assert_eq!(diagram.clusters[0].title, "Grid");
+        assert_eq!(diagram.clusters[0].nodes, vec!["plant".to_string(), "line".to_string()]);
+        assert_eq!(diagram.nodes.len(), 3);
+    }
+
+    #[test]
+    fn rejects_malformed_source() {
+        assert!(parse("digraph { a -> }").is_err());
+        assert!(parse("digraph { a -> b").is_err());
+        assert!(parse("flowchart TD").is_err());
+    }
+}
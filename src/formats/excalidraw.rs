@@ -0,0 +1,781 @@
+//! `.excalidraw` JSON import and export.
+//!
+//! Covers rectangles, ellipses, diamonds, lines, arrows (with bindings),
+//! free drawing, text (free-standing and bound to containers), embedded
+//! images and frames. Charts are exported as images. Rotation, roughness and stroke styles have no board
+//! equivalent and are dropped on import.
+
+use crate::model::{Board, Bounds, Group, Shape, ShapeType};
+#[cfg(feature = "server")]
+use crate::export::svg::render_svg_region;
+#[cfg(feature = "server")]
+use crate::model::{GroupKind, Point};
+#[cfg(feature = "server")]
+use base64::engine::general_purpose::STANDARD as BASE64;
+#[cfg(feature = "server")]
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub const FILE_TYPE: &str = "excalidraw";
+/// `type` of Excalidraw's own clipboard payload
+pub const CLIPBOARD_TYPE: &str = "excalidraw/clipboard";
+#[cfg(feature = "server")]
+const SOURCE: &str = "agent-excalidraw";
+const DEFAULT_STROKE: &str = "#1e1e1e";
+#[cfg(feature = "server")]
+const FONT_SIZE: f64 = 20.0;
+/// Excalidraw's default font family id (Virgil / Excalifont)
+#[cfg(feature = "server")]
+const FONT_FAMILY: u8 = 1;
+#[cfg(feature = "server")]
+const LINE_HEIGHT: f64 = 1.25;
+/// Gap Excalidraw leaves between a bound arrow and its shape
+#[cfg(feature = "server")]
+const BINDING_GAP: f64 = 4.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcalidrawFile {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub source: String,
+    pub elements: Vec<Element>,
+    #[serde(default)]
+    pub app_state: Value,
+    /// Binary files (images) keyed by file id
+    #[serde(default)]
+    pub files: HashMap<String, FileEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    pub id: String,
+    pub mime_type: String,
+    #[serde(rename = "dataURL")]
+    pub data_url: String,
+    #[serde(default)]
+    pub created: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Binding {
+    pub element_id: String,
+    #[serde(default)]
+    pub focus: f64,
+    #[serde(default)]
+    pub gap: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BoundElement {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// One Excalidraw element. Type-specific fields are optional; unknown fields are ignored.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Element {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub width: f64,
+    #[serde(default)]
+    pub height: f64,
+    #[serde(default)]
+    pub angle: f64,
+    #[serde(default = "default_stroke")]
+    pub stroke_color: String,
+    #[serde(default = "transparent")]
+    pub background_color: String,
+    #[serde(default = "solid")]
+    pub fill_style: String,
+    #[serde(default = "default_stroke_width")]
+    pub stroke_width: f64,
+    #[serde(default = "solid")]
+    pub stroke_style: String,
+    #[serde(default)]
+    pub roughness: f64,
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+    #[serde(default)]
+    pub group_ids: Vec<String>,
+    #[serde(default)]
+    pub frame_id: Option<String>,
+    #[serde(default)]
+    pub roundness: Option<Value>,
+    #[serde(default)]
+    pub seed: u32,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub version_nonce: u32,
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub bound_elements: Option<Vec<BoundElement>>,
+    #[serde(default)]
+    pub updated: u64,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default)]
+    pub locked: bool,
+    /// App-specific data Excalidraw keeps untouched; carries a shape's sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_data: Option<Value>,
+
+    // text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertical_align: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_height: Option<f64>,
+
+    // line, arrow, freedraw
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<Vec<[f64; 2]>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_binding: Option<Binding>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_binding: Option<Binding>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_arrowhead: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_arrowhead: Option<String>,
+
+    // image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    // frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+fn default_stroke() -> String {
+    DEFAULT_STROKE.to_string()
+}
+
+fn transparent() -> String {
+    "transparent".to_string()
+}
+
+fn solid() -> String {
+    "solid".to_string()
+}
+
+fn default_stroke_width() -> f64 {
+    2.0
+}
+
+fn default_opacity() -> f64 {
+    100.0
+}
+
+impl Element {
+    #[cfg(feature = "server")]
+    fn new(id: Uuid, kind: &str, x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            x,
+            y,
+            width,
+            height,
+            angle: 0.0,
+            stroke_color: default_stroke(),
+            background_color: transparent(),
+            fill_style: solid(),
+            stroke_width: default_stroke_width(),
+            stroke_style: solid(),
+            roughness: 1.0,
+            opacity: default_opacity(),
+            group_ids: Vec::new(),
+            frame_id: None,
+            roundness: None,
+            seed: seed_for(&id),
+            version: 1,
+            version_nonce: seed_for(&id).wrapping_mul(31),
+            is_deleted: false,
+            bound_elements: None,
+            updated: 1,
+            link: None,
+            locked: false,
+            custom_data: None,
+            text: None,
+            original_text: None,
+            font_size: None,
+            font_family: None,
+            text_align: None,
+            vertical_align: None,
+            container_id: None,
+            line_height: None,
+            points: None,
+            start_binding: None,
+            end_binding: None,
+            start_arrowhead: None,
+            end_arrowhead: None,
+            file_id: None,
+            status: None,
+            name: None,
+        }
+    }
+
+    #[cfg(feature = "server")]
+    fn text_element(id: Uuid, text: &str, x: f64, y: f64, container: Option<&str>) -> Self {
+        let lines = text.lines().count().max(1) as f64;
+        let longest = text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as f64;
+        let mut element = Self::new(
+            id,
+            "text",
+            x,
+            y,
+            longest * FONT_SIZE * 0.55,
+            lines * FONT_SIZE * LINE_HEIGHT,
+        );
+        element.text = Some(text.to_string());
+        element.original_text = Some(text.to_string());
+        element.font_size = Some(FONT_SIZE);
+        element.font_family = Some(FONT_FAMILY);
+        element.line_height = Some(LINE_HEIGHT);
+        element.text_align = Some(if container.is_some() { "center" } else { "left" }.to_string());
+        element.vertical_align = Some(if container.is_some() { "middle" } else { "top" }.to_string());
+        element.container_id = container.map(str::to_string);
+        element
+    }
+
+    #[cfg(feature = "server")]
+    fn add_bound(&mut self, id: &str, kind: &str) {
+        self.bound_elements.get_or_insert_with(Vec::new).push(BoundElement {
+            id: id.to_string(),
+            kind: kind.to_string(),
+        });
+    }
+
+    /// Points in world coordinates (linear elements store them relative to x/y)
+    fn world_points(&self) -> Vec<(f64, f64)> {
+        self.points
+            .iter()
+            .flatten()
+            .map(|p| (self.x + p[0], self.y + p[1]))
+            .collect()
+    }
+}
+
+/// Deterministic id for an element generated from a shape (its label, image file)
+#[cfg(feature = "server")]
+fn derived_id(id: Uuid, salt: u128) -> Uuid {
+    Uuid::from_u128(id.as_u128() ^ salt)
+}
+
+/// Stable per-element seed so exports of the same board are identical
+#[cfg(feature = "server")]
+fn seed_for(id: &Uuid) -> u32 {
+    let bytes = id.as_bytes();
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) & 0x7fff_ffff
+}
+
+fn is_transparent(color: &str) -> bool {
+    color.is_empty() || color.eq_ignore_ascii_case("transparent") || color == "none"
+}
+
+// ============================================================================
+// EXPORT
+// ============================================================================
+
+/// Convert a board to an Excalidraw scene
+#[cfg(feature = "server")]
+pub fn to_excalidraw(board: &Board) -> ExcalidrawFile {
+    let mut elements: Vec<Element> = Vec::new();
+    let mut files = HashMap::new();
+    let mut index: HashMap<Uuid, usize> = HashMap::new();
+
+    // Frames come first so they sit behind their children
+    for frame in board.groups.iter().filter(|g| g.is_frame()) {
+        let mut element = Element::new(frame.id, "frame", frame.x, frame.y, frame.width, frame.height);
+        element.name = Some(frame.title.clone());
+        element.roughness = 0.0;
+        elements.push(element);
+    }
+    let frame_of = |id: Uuid| {
+        board
+            .groups
+            .iter()
+            .find(|g| g.is_frame() && g.members.contains(&id))
+            .map(|g| g.id.to_string())
+    };
+    let groups_of = |id: Uuid| {
+        board
+            .groups
+            .iter()
+            .filter(|g| g.kind == GroupKind::Group && g.members.contains(&id))
+            .map(|g| g.id.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    for shape in &board.shapes {
+        let mut element = shape_element(shape, &mut files);
+        if !shape.sources.is_empty() {
+            element.custom_data = Some(json!({ "sources": shape.sources }));
+        }
+        element.frame_id = frame_of(shape.id);
+        element.group_ids = groups_of(shape.id);
+        index.insert(shape.id, elements.len());
+        elements.push(element);
+
+        // Labels become text bound to the shape (free text is its own element,
+        // chart titles are part of the chart image)
+        let label = shape.label.as_deref().filter(|l| !l.trim().is_empty());
+        let has_own_text = matches!(shape.shape_type, ShapeType::Text | ShapeType::Chart { .. });
+        if let (Some(label), false) = (label, has_own_text) {
+            let text_id = derived_id(shape.id, 1);
+            let (cx, cy) = shape.center();
+            let mut text = Element::text_element(text_id, label, cx, cy, Some(&shape.id.to_string()));
+            text.x -= text.width / 2.0;
+            text.y -= text.height / 2.0;
+            text.frame_id = frame_of(shape.id);
+            text.group_ids = groups_of(shape.id);
+            elements[index[&shape.id]].add_bound(&text.id, "text");
+            elements.push(text);
+        }
+    }
+
+    // Arrow bindings are recorded on both ends
+    for shape in &board.shapes {
+        let ShapeType::Arrow { start, end, .. } = &shape.shape_type else { continue };
+        let arrow_id = shape.id.to_string();
+        for (bound, is_start) in [(start, true), (end, false)] {
+            let Some(target) = bound.filter(|id| index.contains_key(id)) else { continue };
+            let binding = Binding {
+                element_id: target.to_string(),
+                focus: 0.0,
+                gap: BINDING_GAP,
+            };
+            let arrow = &mut elements[index[&shape.id]];
+            if is_start {
+                arrow.start_binding = Some(binding);
+            } else {
+                arrow.end_binding = Some(binding);
+            }
+            elements[index[&target]].add_bound(&arrow_id, "arrow");
+        }
+    }
+
+    ExcalidrawFile {
+        kind: FILE_TYPE.to_string(),
+        version: 2,
+        source: SOURCE.to_string(),
+        elements,
+        app_state: json!({ "viewBackgroundColor": "#ffffff", "gridSize": null }),
+        files,
+    }
+}
+
+/// Serialize a board as `.excalidraw` JSON
+#[cfg(feature = "server")]
+pub fn export_json(board: &Board) -> String {
+    serde_json::to_string_pretty(&to_excalidraw(board)).unwrap_or_default()
+}
+
+#[cfg(feature = "server")]
+fn shape_element(shape: &Shape, files: &mut HashMap<String, FileEntry>) -> Element {
+    let (x, y, w, h) = (shape.x, shape.y, shape.width, shape.height);
+    let filled = |kind: &str| {
+        let mut element = Element::new(shape.id, kind, x, y, w, h);
+        element.background_color = shape.color.clone();
+        element
+    };
+    let linear = |kind: &str, points: Vec<Point>| {
+        let mut element = Element::new(shape.id, kind, x, y, w, h);
+        element.stroke_color = shape.color.clone();
+        element.points = Some(points.iter().map(|p| [p.x, p.y]).collect());
+        element.roundness = Some(json!({ "type": 2 }));
+        element
+    };
+    let origin = Point { x: 0.0, y: 0.0 };
+    match &shape.shape_type {
+        ShapeType::Rectangle => {
+            let mut element = filled("rectangle");
+            element.roundness = Some(json!({ "type": 3 }));
+            element
+        }
+        ShapeType::StickyNote => {
+            let mut element = filled("rectangle");
+            element.stroke_color = "transparent".to_string();
+            element
+        }
+        ShapeType::Circle | ShapeType::Ellipse => filled("ellipse"),
+        ShapeType::Diamond => filled("diamond"),
+        ShapeType::Line => linear("line", vec![origin, Point { x: w, y: h }]),
+        ShapeType::Arrow { headless, .. } => {
+            let mut element = linear("arrow", vec![origin, Point { x: w, y: h }]);
+            element.end_arrowhead = (!headless).then(|| "arrow".to_string());
+            element
+        }
+        // Excalidraw has no polygon; a closed, filled line is the equivalent
+        ShapeType::Polygon { points } => {
+            let mut closed = points.clone();
+            closed.extend(points.first().copied());
+            let mut element = linear("line", closed);
+            element.stroke_color = DEFAULT_STROKE.to_string();
+            element.background_color = shape.color.clone();
+            element
+        }
+        ShapeType::Freehand { points } => {
+            let mut element = linear("freedraw", points.clone());
+            element.roundness = None;
+            element
+        }
+        ShapeType::Text => {
+            let mut element = Element::text_element(shape.id, shape.label.as_deref().unwrap_or(""), x, y, None);
+            element.width = w;
+            element.height = h;
+            element
+        }
+        // Excalidraw has no charts; embed the rendered chart as an SVG image
+        ShapeType::Chart { .. } => {
+            // Citation badges stay off the image; the sources travel as custom data
+            let chart = Board {
+                shapes: vec![Shape {
+                    sources: Vec::new(),
+                    ..shape.clone()
+                }],
+                ..Board::default()
+            };
+            let svg = render_svg_region(&chart, &shape.bounds());
+            let file_id = derived_id(shape.id, 2).simple().to_string();
+            files.insert(
+                file_id.clone(),
+                FileEntry {
+                    id: file_id.clone(),
+                    mime_type: "image/svg+xml".to_string(),
+                    data_url: format!("data:image/svg+xml;base64,{}", BASE64.encode(svg)),
+                    created: 1,
+                },
+            );
+            let mut element = Element::new(shape.id, "image", x, y, w, h);
+            element.file_id = Some(file_id);
+            element.status = Some("saved".to_string());
+            element
+        }
+        // Only embedded images survive; linked ones become a placeholder carrying the URL
+        ShapeType::Image { href } => match href.strip_prefix("data:").and_then(|rest| rest.split_once(';')) {
+            Some((mime_type, _)) => {
+                let file_id = derived_id(shape.id, 2).simple().to_string();
+                files.insert(
+                    file_id.clone(),
+                    FileEntry {
+                        id: file_id.clone(),
+                        mime_type: mime_type.to_string(),
+                        data_url: href.clone(),
+                        created: 1,
+                    },
+                );
+                let mut element = Element::new(shape.id, "image", x, y, w, h);
+                element.file_id = Some(file_id);
+                element.status = Some("saved".to_string());
+                element
+            }
+            None => {
+                let mut element = Element::new(shape.id, "rectangle", x, y, w, h);
+                element.link = Some(href.clone());
+                element
+            }
+        },
+    }
+}
+
+// ============================================================================
+// IMPORT
+// ============================================================================
+
+/// Parse `.excalidraw` JSON (a scene file or Excalidraw's clipboard payload)
+pub fn import_json(text: &str) -> Result<Board, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let kind = value.get("type").and_then(Value::as_str).unwrap_or_default();
+    if kind != FILE_TYPE && kind != CLIPBOARD_TYPE {
+        return Err(format!("Not an Excalidraw file (type '{}')", kind));
+    }
+    let mut map: Map<String, Value> = match value {
+        Value::Object(map) => map,
+        _ => return Err("Not an Excalidraw file".to_string()),
+    };
+    map.entry("elements").or_insert_with(|| json!([]));
+    let file: ExcalidrawFile = serde_json::from_value(Value::Object(map))
+        .map_err(|e| format!("Unsupported Excalidraw content: {}", e))?;
+    Ok(from_excalidraw(&file))
+}
+
+/// Convert an Excalidraw scene to a board
+pub fn from_excalidraw(file: &ExcalidrawFile) -> Board {
+    let elements: Vec<&Element> = file.elements.iter().filter(|e| !e.is_deleted).collect();
+
+    // Keep ids that are already UUIDs so re-imports of our own exports are stable
+    let ids: HashMap<&str, Uuid> = elements
+        .iter()
+        .map(|e| (e.id.as_str(), Uuid::parse_str(&e.id).unwrap_or_else(|_| Uuid::new_v4())))
+        .collect();
+    let id_of = |raw: &str| ids.get(raw).copied();
+
+    // Text bound to a container becomes that shape's label
+    let mut labels: HashMap<&str, String> = HashMap::new();
+    for element in &elements {
+        if let (Some(container), Some(text)) = (&element.container_id, element_text(element)) {
+            labels.insert(container.as_str(), text);
+        }
+    }
+
+    let mut board = Board::default();
+    let mut frames: Vec<Group> = elements
+        .iter()
+        .filter(|e| e.kind == "frame" || e.kind == "magicframe")
+        .filter_map(|e| {
+            let bounds = Bounds {
+                min_x: e.x,
+                min_y: e.y,
+                max_x: e.x + e.width,
+                max_y: e.y + e.height,
+            };
+            let mut frame = Group::frame(e.name.clone().unwrap_or_default(), Vec::new(), bounds);
+            frame.id = id_of(&e.id)?;
+            Some(frame)
+        })
+        .collect();
+    let mut groups: Vec<(&str, Vec<Uuid>)> = Vec::new();
+
+    for element in &elements {
+        let Some(id) = id_of(&element.id) else { continue };
+        if element.kind == "text" && element.container_id.is_some() {
+            continue;
+        }
+        let Some(mut shape) = element_shape(element, file, &id_of) else { continue };
+        shape.id = id;
+        if let Some(label) = labels.get(element.id.as_str()) {
+            shape.label = Some(label.clone());
+        }
+        if let Some(sources) = element.custom_data.as_ref().and_then(|data| data.get("sources")) {
+            shape.sources = serde_json::from_value(sources.clone()).unwrap_or_default();
+        }
+        // The outermost group wins; nested groups have no board equivalent
+        if let Some(group_id) = element.group_ids.last() {
+            match groups.iter_mut().find(|(g, _)| g == group_id) {
+                Some((_, members)) => members.push(id),
+                None => groups.push((group_id, vec![id])),
+            }
+        }
+        let frame_id = element.frame_id.as_deref().and_then(id_of);
+        if let Some(frame) = frames.iter_mut().find(|f| Some(f.id) == frame_id) {
+            frame.members.push(id);
+        }
+        board.shapes.push(shape);
+    }
+
+    board.groups.extend(frames);
+    for (group_id, members) in groups.into_iter().filter(|(_, m)| m.len() > 1) {
+        let mut group = Group::plain(members);
+        if let Ok(id) = Uuid::parse_str(group_id) {
+            group.id = id;
+        }
+        board.groups.push(group);
+    }
+    board.route_arrows();
+    board
+}
+
+fn element_text(element: &Element) -> Option<String> {
+    element
+        .original_text
+        .clone()
+        .or_else(|| element.text.clone())
+        .filter(|t| !t.trim().is_empty())
+}
+
+fn fill_color(element: &Element) -> String {
+    if is_transparent(&element.background_color) {
+        "transparent".to_string()
+    } else {
+        element.background_color.clone()
+    }
+}
+
+fn element_shape(element: &Element, file: &ExcalidrawFile, id_of: &impl Fn(&str) -> Option<Uuid>) -> Option<Shape> {
+    let (x, y, w, h) = (element.x, element.y, element.width, element.height);
+    let shape = match element.kind.as_str() {
+        "rectangle" => {
+            let mut shape = Shape::new(ShapeType::Rectangle, x, y, w, h, fill_color(element));
+            // Our placeholder for linked images
+            if let Some(link) = element.link.as_ref().filter(|l| l.starts_with("http")) {
+                shape.shape_type = ShapeType::Image { href: link.clone() };
+            }
+            shape
+        }
+        "ellipse" => Shape::new(ShapeType::Ellipse, x, y, w, h, fill_color(element)),
+        "diamond" => Shape::new(ShapeType::Diamond, x, y, w, h, fill_color(element)),
+        "text" => {
+            let mut shape = Shape::text(x, y, element_text(element)?);
+            if w > 0.0 && h > 0.0 {
+                shape.width = w;
+                shape.height = h;
+            }
+            shape
+        }
+        "image" => {
+            let entry = file.files.get(element.file_id.as_deref()?)?;
+            Shape::new(ShapeType::Image { href: entry.data_url.clone() }, x, y, w, h, "transparent".to_string())
+        }
+        "arrow" => {
+            let points = element.world_points();
+            let (from, to) = (*points.first()?, *points.last()?);
+            let binding = |b: &Option<Binding>| b.as_ref().and_then(|b| id_of(&b.element_id));
+            let mut shape = Shape::arrow(
+                from,
+                to,
+                binding(&element.start_binding),
+                binding(&element.end_binding),
+                element.stroke_color.clone(),
+            );
+            if let ShapeType::Arrow { headless, .. } = &mut shape.shape_type {
+                *headless = element.end_arrowhead.is_none();
+            }
+            shape
+        }
+        "line" => {
+            let points = element.world_points();
+            let closed = points.len() > 3
+                && points.first().zip(points.last()).is_some_and(|(a, b)| {
+                    (a.0 - b.0).abs() < 1.0 && (a.1 - b.1).abs() < 1.0
+                });
+            if points.len() == 2 {
+                let (from, to) = (points[0], points[1]);
+                Shape::new(ShapeType::Line, from.0, from.1, to.0 - from.0, to.1 - from.1, element.stroke_color.clone())
+            } else if closed {
+                let fill = if is_transparent(&element.background_color) {
+                    element.stroke_color.clone()
+                } else {
+                    element.background_color.clone()
+                };
+                Shape::from_points(&points[..points.len() - 1], true, fill)
+            } else {
+                Shape::from_points(&points, false, element.stroke_color.clone())
+            }
+        }
+        "freedraw" => {
+            let points = element.world_points();
+            if points.len() < 2 {
+                return None;
+            }
+            Shape::from_points(&points, false, element.stroke_color.clone())
+        }
+        _ => return None,
+    };
+    Some(shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::GroupKind;
+
+    const FLOW: &str = include_str!("../../tests/fixtures/excalidraw/flow.excalidraw");
+
+    fn by_label<'a>(board: &'a Board, label: &str) -> &'a Shape {
+        board
+            .shapes
+            .iter()
+            .find(|s| s.label.as_deref() == Some(label))
+            .unwrap_or_else(|| panic!("no shape labelled '{}'", label))
+    }
+
+    #[test]
+    fn imports_shapes_labels_and_bindings() {
+        let board = import_json(FLOW).unwrap();
+        // The deleted element and the two bound texts don't become shapes
+        assert_eq!(board.shapes.len(), 6);
+
+        let client = by_label(&board, "Client");
+        assert_eq!(client.shape_type, ShapeType::Rectangle);
+        assert_eq!(client.color, "#a5d8ff");
+        let server = by_label(&board, "Server");
+        assert_eq!(server.shape_type, ShapeType::Ellipse);
+        let note = by_label(&board, "Retries use backoff\nand give up after 5");
+        assert_eq!(note.shape_type, ShapeType::Text);
+
+        let arrow = board
+            .shapes
+            .iter()
+            .find(|s| matches!(s.shape_type, ShapeType::Arrow { .. }))
+            .unwrap();
+        assert_eq!(
+            arrow.shape_type,
+            ShapeType::Arrow {
+                start: Some(client.id),
+                end: Some(server.id),
+                headless: false
+            }
+        );
+        let line = board.shapes.iter().find(|s| s.shape_type == ShapeType::Line).unwrap();
+        assert_eq!((line.x, line.y, line.width, line.height), (320.0, 380.0, 220.0, 60.0));
+        assert_eq!(line.color, "#e03131");
+        assert!(board.shapes.iter().any(|s| s.shape_type == ShapeType::Diamond));
+
+        let frame = board.groups.iter().find(|g| g.is_frame()).unwrap();
+        assert_eq!(frame.title, "Request flow");
+        assert_eq!(frame.members, vec![client.id, server.id, arrow.id]);
+        let group = board.groups.iter().find(|g| g.kind == GroupKind::Group).unwrap();
+        assert_eq!(group.members, vec![client.id, server.id]);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn import_export_import_round_trips() {
+        let imported = import_json(FLOW).unwrap();
+        let reimported = import_json(&export_json(&imported)).unwrap();
+        assert_eq!(reimported, imported);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn export_import_keeps_bindings_and_labels() {
+        let mut board: Board = serde_json::from_str(include_str!("../../tests/golden/frames.json")).unwrap();
+        board.route_arrows();
+        let file = to_excalidraw(&board);
+
+        // Bindings are recorded on the arrow and on both ends
+        let arrow = &board.shapes[3];
+        let element = |id: Uuid| file.elements.iter().find(|e| e.id == id.to_string()).unwrap();
+        let exported = element(arrow.id);
+        assert_eq!(exported.start_binding.as_ref().unwrap().element_id, board.shapes[0].id.to_string());
+        assert_eq!(exported.end_binding.as_ref().unwrap().element_id, board.shapes[2].id.to_string());
+        let costs = element(board.shapes[0].id).bound_elements.clone().unwrap();
+        assert!(costs.iter().any(|b| b.kind == "arrow" && b.id == arrow.id.to_string()));
+        assert!(costs.iter().any(|b| b.kind == "text"));
+
+        let reimported = import_json(&serde_json::to_string(&file).unwrap()).unwrap();
+        assert_eq!(reimported, board);
+    }
+}
@@ -0,0 +1,351 @@
+//! Parser for a useful subset of Mermaid flowcharts.
+//!
+//! Supported: the `flowchart`/`graph` header with a direction, node shapes
+//! (`[ ]`, `( )`, `([ ])`, `(( ))`, `{ }`, `{{ }}`, `[[ ]]`, `[( )]`, `> ]`),
+//! chained links (`-->`, `---`, `-.->`, `==>`, `--text-->`, `-->|text|`),
+//! `&` node lists and `subgraph ... end`. Styling statements are ignored.
+
+use super::diagram::{Cluster, Diagram, Edge, NodeShape};
+use crate::layout::Direction;
+
+/// Statements we accept but don't draw
+const IGNORED: &[&str] = &[
+    "classDef", "class", "style", "linkStyle", "click", "direction", "accTitle", "accDescr",
+];
+
+pub fn parse(source: &str) -> Result<Diagram, String> {
+    let mut diagram = Diagram::default();
+    let mut header_seen = false;
+    // Indices into `diagram.clusters` of the open subgraphs
+    let mut open: Vec<usize> = Vec::new();
+
+    for (line_no, line) in source.lines().enumerate() {
+        for statement in split_statements(line) {
+            let statement = statement.trim();
+            if statement.is_empty() || statement.starts_with("%%") {
+                continue;
+            }
+            let keyword = statement.split_whitespace().next().unwrap_or_default();
+            if !header_seen {
+                if keyword != "flowchart" && keyword != "graph" {
+                    return Err(format!("Line {}: expected `flowchart` or `graph`", line_no + 1));
+                }
+                if let Some(direction) = statement.split_whitespace().nth(1) {
+                    diagram.direction = Direction::parse(direction)
+                        .ok_or_else(|| format!("Line {}: unknown direction '{}'", line_no + 1, direction))?;
+                }
+                header_seen = true;
+                continue;
+            }
+            match keyword {
+                "subgraph" => {
+                    let rest = statement["subgraph".len()..].trim();
+                    diagram.clusters.push(Cluster {
+                        title: subgraph_title(rest),
+                        nodes: Vec::new(),
+                    });
+                    open.push(diagram.clusters.len() - 1);
+                }
+                "end" => {
+                    open.pop();
+                }
+                k if IGNORED.contains(&k) => {}
+                _ => parse_chain(statement, &mut diagram, &open)
+                    .map_err(|e| format!("Line {}: {}", line_no + 1, e))?,
+            }
+        }
+    }
+    if !header_seen {
+        return Err("Empty Mermaid source".to_string());
+    }
+    Ok(diagram)
+}
+
+/// Split on `;` outside of quotes and brackets
+fn split_statements(line: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut quoted, mut start) = (0i32, false, 0);
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' | '{' if !quoted => depth += 1,
+            ']' | ')' | '}' if !quoted => depth -= 1,
+            ';' if !quoted && depth <= 0 => {
+                parts.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&line[start..]);
+    parts
+}
+
+/// `id [title]`, `id["title"]` or just `title`
+fn subgraph_title(rest: &str) -> String {
+    match rest.find('[') {
+        Some(open) => clean_label(rest[open + 1..].trim_end().trim_end_matches(']')),
+        None => clean_label(rest),
+    }
+}
+
+fn clean_label(text: &str) -> String {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text);
+    let text = text
+        .strip_prefix('`')
+        .and_then(|t| t.strip_suffix('`'))
+        .unwrap_or(text);
+    text.replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .trim()
+        .to_string()
+}
+
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.text.len() - trimmed.len();
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.pos += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.rest().trim().is_empty()
+    }
+}
+
+/// Node shape delimiters, longest first so `((` wins over `(`
+const SHAPES: &[(&str, &str, NodeShape)] = &[
+    ("(((", ")))", NodeShape::Circle),
+    ("((", "))", NodeShape::Circle),
+    ("([", "])", NodeShape::Ellipse),
+    ("[[", "]]", NodeShape::Rectangle),
+    ("[(", ")]", NodeShape::Rectangle),
+    ("{{", "}}", NodeShape::Diamond),
+    ("[/", "/]", NodeShape::Rectangle),
+    ("[\\", "\\]", NodeShape::Rectangle),
+    ("[", "]", NodeShape::Rectangle),
+    ("(", ")", NodeShape::Rectangle),
+    ("{", "}", NodeShape::Diamond),
+    (">", "]", NodeShape::Rectangle),
+];
+
+/// `a --> b & c -- text --> d ...`
+fn parse_chain(statement: &str, diagram: &mut Diagram, open: &[usize]) -> Result<(), String> {
+    let mut cursor = Cursor { text: statement, pos: 0 };
+    let mut previous = parse_node_group(&mut cursor, diagram, open)?;
+    loop {
+        cursor.skip_spaces();
+        if cursor.is_done() {
+            return Ok(());
+        }
+        let (directed, mut label) = parse_link(&mut cursor)?;
+        cursor.skip_spaces();
+        if cursor.eat("|") {
+            let end = cursor
+                .rest()
+                .find('|')
+                .ok_or_else(|| "unclosed |label|".to_string())?;
+            label = Some(clean_label(&cursor.rest()[..end]));
+            cursor.pos += end + 1;
+        }
+        let next = parse_node_group(&mut cursor, diagram, open)?;
+        for from in &previous {
+            for to in &next {
+                diagram.edges.push(Edge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    label: label.clone().filter(|l| !l.is_empty()),
+                    directed,
+                });
+            }
+        }
+        previous = next;
+    }
+}
+
+fn parse_node_group(cursor: &mut Cursor, diagram: &mut Diagram, open: &[usize]) -> Result<Vec<String>, String> {
+    let mut ids = vec![parse_node(cursor, diagram, open)?];
+    loop {
+        cursor.skip_spaces();
+        if !cursor.eat("&") {
+            return Ok(ids);
+        }
+        ids.push(parse_node(cursor, diagram, open)?);
+    }
+}
+
+fn parse_node(cursor: &mut Cursor, diagram: &mut Diagram, open: &[usize]) -> Result<String, String> {
+    cursor.skip_spaces();
+    let id_len = cursor
+        .rest()
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(cursor.rest().len());
+    if id_len == 0 {
+        return Err(format!("expected a node id at '{}'", cursor.rest()));
+    }
+    let id = cursor.rest()[..id_len].to_string();
+    cursor.pos += id_len;
+
+    let mut label = None;
+    let mut shape = None;
+    for (open_delim, close_delim, node_shape) in SHAPES {
+        if cursor.eat(open_delim) {
+            let end = cursor
+                .rest()
+                .find(close_delim)
+                .ok_or_else(|| format!("unclosed '{}' for node '{}'", open_delim, id))?;
+            label = Some(clean_label(&cursor.rest()[..end]));
+            cursor.pos += end + close_delim.len();
+            shape = Some(*node_shape);
+            break;
+        }
+    }
+
+    let is_new = !diagram.nodes.iter().any(|n| n.id == id);
+    diagram.upsert_node(&id, label, shape);
+    // Nodes belong to the innermost subgraph they first appear in
+    if let (true, Some(&cluster)) = (is_new, open.last()) {
+        diagram.clusters[cluster].nodes.push(id.clone());
+    }
+    Ok(id)
+}
+
+/// A link token, with the text of `-- text -->` style links
+fn parse_link(cursor: &mut Cursor) -> Result<(bool, Option<String>), String> {
+    let token = take_link_token(cursor);
+    if token.is_empty() {
+        return Err(format!("expected a link like --> at '{}'", cursor.rest()));
+    }
+    let complete = token.ends_with('>') || token.len() >= 3;
+    if complete {
+        return Ok((token.contains('>'), None));
+    }
+    // `-- text -->`: the text runs up to the closing part of the link
+    let rest = cursor.rest();
+    let end = ["--", "==", ".-"]
+        .iter()
+        .filter_map(|p| rest.find(p))
+        .min()
+        .ok_or_else(|| format!("unterminated link text at '{}'", rest))?;
+    let label = clean_label(&rest[..end]);
+    cursor.pos += end;
+    let closing = take_link_token(cursor);
+    Ok((closing.contains('>'), Some(label)))
+}
+
+fn take_link_token(cursor: &mut Cursor) -> String {
+    let len = cursor
+        .rest()
+        .find(|c: char| !matches!(c, '-' | '=' | '.' | '<' | '>' | '~'))
+        .unwrap_or(cursor.rest().len());
+    let token = cursor.rest()[..len].to_string();
+    cursor.pos += len;
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(from: &str, to: &str, label: Option<&str>, directed: bool) -> Edge {
+        Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.map(str::to_string),
+            directed,
+        }
+    }
+
+    #[test]
+    fn parses_shapes_chains_and_labels() {
+        let diagram = parse(
+            "flowchart LR\n\
+             %% a comment\n\
+             A[Start] --> B{Is it cheap?}\n\
+             B -->|yes| C((Buy)) -- later --> D([\"Install<br>panels\"])\n\
+             B -.-> E; E --- A\n\
+             style A fill:#f9f\n",
+        )
+        .unwrap();
+        assert_eq!(diagram.direction, Direction::LeftRight);
+        let nodes: Vec<_> = diagram.nodes.iter().map(|n| (n.id.as_str(), n.label.as_str(), n.shape)).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("A", "Start", NodeShape::Rectangle),
+                ("B", "Is it cheap?", NodeShape::Diamond),
+                ("C", "Buy", NodeShape::Circle),
+                ("D", "Install\npanels", NodeShape::Ellipse),
+                ("E", "E", NodeShape::Rectangle),
+            ]
+        );
+        assert_eq!(
+            diagram.edges,
+            vec![
+                edge("A", "B", None, true),
+                edge("B", "C", Some("yes"), true),
+                edge("C", "D", Some("later"), true),
+                edge("B", "E", None, true),
+                edge("E", "A", None, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn node_lists_link_every_pair() {
+        let diagram = parse("graph TD\n  a & b --> c & d").unwrap();
+        assert_eq!(diagram.edges.len(), 4);
+        assert!(diagram.edges.contains(&edge("b", "d", None, true)));
+    }
+
+    #[test]
+    fn subgraphs_hold_the_nodes_first_seen_in_them() {
+        let diagram = parse(
+            "flowchart TB\n\
+             subgraph supply [Supply side]\n  panel --> inverter\nend\n\
+             subgraph Demand\n  home\n  inverter --> home\nend\n",
+        )
+        .unwrap();
+        let clusters: Vec<_> = diagram.clusters.iter().map(|c| (c.title.as_str(), c.nodes.clone())).collect();
+        assert_eq!(
+            clusters,
+            vec![
+                ("Supply side", vec!["panel".to_string(), "inverter".to_string()]),
+                ("Demand", vec!["home".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_an_error() {
+        assert_eq!(parse("").unwrap_err(), "Empty Mermaid source");
+        assert_eq!(parse("sequenceDiagram").unwrap_err(), "Line 1: expected `flowchart` or `graph`");
+        assert_eq!(parse("graph XY").unwrap_err(), "Line 1: unknown direction 'XY'");
+        assert_eq!(
+            parse("graph TD\nA[Start --> B").unwrap_err(),
+            "Line 2: unclosed '[' for node 'A'"
+        );
+    }
+}
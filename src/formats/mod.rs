@@ -0,0 +1,6 @@
+//! Conversions between boards and other diagram formats.
+
+pub mod diagram;
+pub mod dot;
+pub mod excalidraw;
+pub mod mermaid;
@@ -0,0 +1,785 @@
+//! Automatic placement of boxes. Layouts work on sizes and index-based
+//! edges and return top-left positions, so they can be used for parsed
+//! diagrams as well as shapes already on a board ([`arrange`]).
+
+use crate::model::{Action, Board, Bounds, Shape, ShapeType};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+use uuid::Uuid;
+
+/// Gap between neighbouring nodes in the same rank
+pub const NODE_GAP: f64 = 40.0;
+/// Gap between consecutive ranks
+pub const RANK_GAP: f64 = 70.0;
+
+/// Flow direction of a layered layout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+    #[default]
+    TopDown,
+    BottomUp,
+    LeftRight,
+    RightLeft,
+}
+
+impl Direction {
+    /// Parse Mermaid (`TD`, `TB`, `LR`, ...) and Graphviz `rankdir` values
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_uppercase().as_str() {
+            "TD" | "TB" => Some(Self::TopDown),
+            "BT" => Some(Self::BottomUp),
+            "LR" => Some(Self::LeftRight),
+            "RL" => Some(Self::RightLeft),
+            _ => None,
+        }
+    }
+
+    fn is_horizontal(&self) -> bool {
+        matches!(self, Self::LeftRight | Self::RightLeft)
+    }
+
+    fn is_reversed(&self) -> bool {
+        matches!(self, Self::BottomUp | Self::RightLeft)
+    }
+}
+
+/// Available layouts for [`arrange`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LayoutKind {
+    Grid,
+    Tree,
+    Radial,
+    Layered,
+    /// Keep positions, only push overlapping shapes apart
+    RemoveOverlaps,
+}
+
+impl LayoutKind {
+    /// Names accepted by the `auto_layout` tool
+    #[cfg(feature = "server")]
+    pub const NAMES: &'static [&'static str] = &["grid", "tree", "radial", "layered", "remove_overlaps"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace([' ', '-'], "_").as_str() {
+            "grid" => Some(Self::Grid),
+            "tree" | "hierarchy" => Some(Self::Tree),
+            "radial" | "mind_map" | "mindmap" => Some(Self::Radial),
+            "layered" | "graph" | "flow" | "sugiyama" => Some(Self::Layered),
+            "remove_overlaps" | "overlaps" | "declutter" => Some(Self::RemoveOverlaps),
+            _ => None,
+        }
+    }
+}
+
+/// Rows and columns as close to square as possible, in input order. Each
+/// column is as wide as its widest box and each row as tall as its tallest.
+pub fn grid(sizes: &[(f64, f64)], columns: Option<usize>) -> Vec<(f64, f64)> {
+    let n = sizes.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let columns = columns
+        .unwrap_or_else(|| (n as f64).sqrt().ceil() as usize)
+        .clamp(1, n);
+    let rows = n.div_ceil(columns);
+    let mut widths = vec![0.0f64; columns];
+    let mut heights = vec![0.0f64; rows];
+    for (i, &(w, h)) in sizes.iter().enumerate() {
+        widths[i % columns] = widths[i % columns].max(w);
+        heights[i / columns] = heights[i / columns].max(h);
+    }
+    let offsets = |extents: &[f64]| {
+        extents
+            .iter()
+            .scan(0.0, |offset, extent| {
+                let start = *offset;
+                *offset += extent + NODE_GAP;
+                Some(start)
+            })
+            .collect::<Vec<f64>>()
+    };
+    let (xs, ys) = (offsets(&widths), offsets(&heights));
+    (0..n).map(|i| (xs[i % columns], ys[i / columns])).collect()
+}
+
+/// Tidy tree: each node is centred over its children and subtrees never
+/// overlap. Graphs are reduced to a spanning forest first; nodes with several
+/// parents hang under the first one.
+pub fn tree(sizes: &[(f64, f64)], edges: &[(usize, usize)], direction: Direction) -> Vec<(f64, f64)> {
+    let n = sizes.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let (roots, children) = spanning_forest(n, edges);
+    let horizontal = direction.is_horizontal();
+    let extent = |node: usize| {
+        let (w, h) = sizes[node];
+        if horizontal {
+            (h, w)
+        } else {
+            (w, h)
+        }
+    };
+
+    let mut level = vec![0usize; n];
+    let mut stack: Vec<usize> = roots.clone();
+    while let Some(node) = stack.pop() {
+        for &child in &children[node] {
+            level[child] = level[node] + 1;
+            stack.push(child);
+        }
+    }
+    let level_count = level.iter().max().map_or(1, |l| l + 1);
+    let mut level_depth = vec![0.0f64; level_count];
+    for node in 0..n {
+        level_depth[level[node]] = level_depth[level[node]].max(extent(node).1);
+    }
+    let mut level_offset = Vec::with_capacity(level_count);
+    let mut offset = 0.0;
+    for depth in &level_depth {
+        level_offset.push(offset);
+        offset += depth + RANK_GAP;
+    }
+    let total_depth = offset - RANK_GAP;
+
+    // Breadth of every subtree, children before parents
+    let mut breadth = vec![0.0f64; n];
+    for &node in subtree_order(&roots, &children).iter().rev() {
+        let kids = &children[node];
+        let kids_breadth =
+            kids.iter().map(|&c| breadth[c]).sum::<f64>() + NODE_GAP * kids.len().saturating_sub(1) as f64;
+        breadth[node] = extent(node).0.max(kids_breadth);
+    }
+
+    let mut positions = vec![(0.0, 0.0); n];
+    let mut placements: Vec<(usize, f64)> = Vec::new();
+    let mut across = 0.0;
+    for &root in &roots {
+        placements.push((root, across));
+        across += breadth[root] + NODE_GAP;
+    }
+    while let Some((node, start)) = placements.pop() {
+        let (node_breadth, node_depth) = extent(node);
+        let along = level_offset[level[node]] + (level_depth[level[node]] - node_depth) / 2.0;
+        let along = if direction.is_reversed() {
+            total_depth - along - node_depth
+        } else {
+            along
+        };
+        let across = start + (breadth[node] - node_breadth) / 2.0;
+        positions[node] = if horizontal { (along, across) } else { (across, along) };
+
+        let kids = &children[node];
+        let kids_breadth =
+            kids.iter().map(|&c| breadth[c]).sum::<f64>() + NODE_GAP * kids.len().saturating_sub(1) as f64;
+        let mut child_start = start + (breadth[node] - kids_breadth) / 2.0;
+        for &child in kids {
+            placements.push((child, child_start));
+            child_start += breadth[child] + NODE_GAP;
+        }
+    }
+    positions
+}
+
+/// Mind-map layout: the root sits in the middle and each level of the tree
+/// is placed on a ring around it, every subtree getting a wedge proportional
+/// to its number of leaves. Several roots share an implicit centre.
+pub fn radial(sizes: &[(f64, f64)], edges: &[(usize, usize)]) -> Vec<(f64, f64)> {
+    let n = sizes.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let (roots, children) = spanning_forest(n, edges);
+    let order = subtree_order(&roots, &children);
+
+    let mut leaves = vec![0usize; n];
+    for &node in order.iter().rev() {
+        leaves[node] = children[node].iter().map(|&c| leaves[c]).sum::<usize>().max(1);
+    }
+    // With a single root it takes the centre; otherwise the roots form the first ring
+    let (centre, first_ring): (Option<usize>, Vec<usize>) = match roots.as_slice() {
+        [root] => (Some(*root), children[*root].clone()),
+        _ => (None, roots.clone()),
+    };
+
+    let mut ring = vec![0usize; n];
+    let mut stack: Vec<usize> = first_ring.clone();
+    for &node in &first_ring {
+        ring[node] = 1;
+    }
+    while let Some(node) = stack.pop() {
+        for &child in &children[node] {
+            ring[child] = ring[node] + 1;
+            stack.push(child);
+        }
+    }
+    let ring_count = ring.iter().max().map_or(1, |r| r + 1);
+
+    // Rings are far enough apart for the largest box, and long enough to hold their nodes
+    let diameter = |node: usize| sizes[node].0.hypot(sizes[node].1);
+    let mut radius = vec![0.0f64; ring_count];
+    let centre_size = centre.map_or(0.0, diameter);
+    for r in 1..ring_count {
+        let members: Vec<usize> = (0..n).filter(|&m| ring[m] == r && Some(m) != centre).collect();
+        let largest = members.iter().map(|&m| diameter(m)).fold(0.0, f64::max);
+        let previous = if r == 1 { centre_size / 2.0 } else { radius[r - 1] };
+        let circumference = members.iter().map(|&m| diameter(m) + NODE_GAP).sum::<f64>();
+        radius[r] = (previous + largest / 2.0 + RANK_GAP).max(circumference / TAU);
+    }
+
+    let mut centres = vec![(0.0, 0.0); n];
+    let total_leaves = first_ring.iter().map(|&m| leaves[m]).sum::<usize>().max(1) as f64;
+    let mut wedges: Vec<(usize, f64, f64)> = Vec::new();
+    let mut angle = -PI / 2.0;
+    for &node in &first_ring {
+        let span = TAU * leaves[node] as f64 / total_leaves;
+        wedges.push((node, angle, span));
+        angle += span;
+    }
+    while let Some((node, start, span)) = wedges.pop() {
+        let theta = start + span / 2.0;
+        centres[node] = (radius[ring[node]] * theta.cos(), radius[ring[node]] * theta.sin());
+        let mut child_start = start;
+        for &child in &children[node] {
+            let child_span = span * leaves[child] as f64 / leaves[node] as f64;
+            wedges.push((child, child_start, child_span));
+            child_start += child_span;
+        }
+    }
+
+    let positions: Vec<(f64, f64)> = centres
+        .iter()
+        .zip(sizes)
+        .map(|(&(cx, cy), &(w, h))| (cx - w / 2.0, cy - h / 2.0))
+        .collect();
+    remove_overlaps(&positions, sizes, NODE_GAP / 2.0)
+}
+
+/// Push overlapping boxes apart along the axis of least overlap until every
+/// pair is at least `gap` apart (or the iteration budget runs out).
+pub fn remove_overlaps(positions: &[(f64, f64)], sizes: &[(f64, f64)], gap: f64) -> Vec<(f64, f64)> {
+    let mut positions = positions.to_vec();
+    let n = positions.len().min(sizes.len());
+    for _ in 0..100 {
+        let mut moved = false;
+        for i in 0..n {
+            for j in i + 1..n {
+                let ((ax, ay), (aw, ah)) = (positions[i], sizes[i]);
+                let ((bx, by), (bw, bh)) = (positions[j], sizes[j]);
+                let overlap_x = (ax + aw).min(bx + bw) - ax.max(bx) + gap;
+                let overlap_y = (ay + ah).min(by + bh) - ay.max(by) + gap;
+                if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                    continue;
+                }
+                moved = true;
+                // Ties push the later box right/down, keeping reading order
+                let sign = |a: f64, b: f64| if b >= a { 1.0 } else { -1.0 };
+                if overlap_x < overlap_y {
+                    let push = overlap_x / 2.0 * sign(ax + aw / 2.0, bx + bw / 2.0);
+                    positions[i].0 -= push;
+                    positions[j].0 += push;
+                } else {
+                    let push = overlap_y / 2.0 * sign(ay + ah / 2.0, by + bh / 2.0);
+                    positions[i].1 -= push;
+                    positions[j].1 += push;
+                }
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    positions
+}
+
+/// Sugiyama-style layered layout: cycles are broken, nodes are ranked by
+/// longest path, ranks are ordered by barycenter sweeps to reduce crossings,
+/// and each rank is centred on the widest one.
+pub fn layered(sizes: &[(f64, f64)], edges: &[(usize, usize)], direction: Direction) -> Vec<(f64, f64)> {
+    let n = sizes.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let edges = acyclic_edges(n, edges);
+
+    // Longest-path ranking (edges now point forward in a topological order)
+    let order = topological_order(n, &edges);
+    let mut rank = vec![0usize; n];
+    for &node in &order {
+        for &(from, to) in edges.iter().filter(|(from, _)| *from == node) {
+            rank[to] = rank[to].max(rank[from] + 1);
+        }
+    }
+    let rank_count = rank.iter().max().map_or(1, |r| r + 1);
+    let mut ranks: Vec<Vec<usize>> = vec![Vec::new(); rank_count];
+    for node in 0..n {
+        ranks[rank[node]].push(node);
+    }
+
+    // Barycenter sweeps, alternating downwards and upwards
+    for sweep in 0..8 {
+        let downward = sweep % 2 == 0;
+        let indices: Vec<usize> = if downward {
+            (1..rank_count).collect()
+        } else {
+            (0..rank_count.saturating_sub(1)).rev().collect()
+        };
+        for r in indices {
+            let neighbour_rank = if downward { r - 1 } else { r + 1 };
+            let position = |node: usize| ranks[neighbour_rank].iter().position(|&m| m == node);
+            let mut keyed: Vec<(f64, usize)> = ranks[r]
+                .iter()
+                .enumerate()
+                .map(|(i, &node)| {
+                    let neighbours: Vec<usize> = edges
+                        .iter()
+                        .filter_map(|&(from, to)| match (from == node, to == node) {
+                            (true, _) => position(to),
+                            (_, true) => position(from),
+                            _ => None,
+                        })
+                        .collect();
+                    let key = if neighbours.is_empty() {
+                        i as f64
+                    } else {
+                        neighbours.iter().sum::<usize>() as f64 / neighbours.len() as f64
+                    };
+                    (key, node)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            ranks[r] = keyed.into_iter().map(|(_, node)| node).collect();
+        }
+    }
+
+    // Sizes along the flow ("depth") and across it ("breadth")
+    let horizontal = direction.is_horizontal();
+    let extent = |node: usize| {
+        let (w, h) = sizes[node];
+        if horizontal {
+            (h, w)
+        } else {
+            (w, h)
+        }
+    };
+    let rank_breadth = |nodes: &Vec<usize>| {
+        nodes.iter().map(|&m| extent(m).0).sum::<f64>() + NODE_GAP * nodes.len().saturating_sub(1) as f64
+    };
+    let widest = ranks.iter().map(rank_breadth).fold(0.0, f64::max);
+
+    let mut positions = vec![(0.0, 0.0); n];
+    let mut depth_offset = 0.0;
+    let ordered_ranks: Vec<&Vec<usize>> = if direction.is_reversed() {
+        ranks.iter().rev().collect()
+    } else {
+        ranks.iter().collect()
+    };
+    for nodes in ordered_ranks {
+        let depth = nodes.iter().map(|&m| extent(m).1).fold(0.0, f64::max);
+        let mut across = (widest - rank_breadth(nodes)) / 2.0;
+        for &node in nodes {
+            let (breadth, node_depth) = extent(node);
+            // Centre each node within its rank's depth
+            let along = depth_offset + (depth - node_depth) / 2.0;
+            positions[node] = if horizontal { (along, across) } else { (across, along) };
+            across += breadth + NODE_GAP;
+        }
+        depth_offset += depth + RANK_GAP;
+    }
+    positions
+}
+
+/// Drop self-loops and reverse edges that close a cycle (found by DFS)
+fn acyclic_edges(n: usize, edges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        Active,
+        Done,
+    }
+    fn visit(node: usize, edges: &[(usize, usize)], marks: &mut [Mark], back: &mut Vec<usize>) {
+        marks[node] = Mark::Active;
+        for (i, &(from, to)) in edges.iter().enumerate() {
+            if from != node {
+                continue;
+            }
+            match marks[to] {
+                Mark::New => visit(to, edges, marks, back),
+                Mark::Active => back.push(i),
+                Mark::Done => {}
+            }
+        }
+        marks[node] = Mark::Done;
+    }
+
+    let edges: Vec<(usize, usize)> = edges
+        .iter()
+        .copied()
+        .filter(|&(from, to)| from != to && from < n && to < n)
+        .collect();
+    let mut marks = vec![Mark::New; n];
+    let mut back = Vec::new();
+    for node in 0..n {
+        if marks[node] == Mark::New {
+            visit(node, &edges, &mut marks, &mut back);
+        }
+    }
+    edges
+        .iter()
+        .enumerate()
+        .map(|(i, &(from, to))| if back.contains(&i) { (to, from) } else { (from, to) })
+        .collect()
+}
+
+/// Kahn's algorithm; nodes keep their input order where there is a choice
+fn topological_order(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut incoming = vec![0usize; n];
+    for &(_, to) in edges {
+        incoming[to] += 1;
+    }
+    let mut ready: Vec<usize> = (0..n).filter(|&node| incoming[node] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while !ready.is_empty() {
+        let node = ready.remove(0);
+        order.push(node);
+        for &(from, to) in edges {
+            if from == node {
+                incoming[to] -= 1;
+                if incoming[to] == 0 {
+                    ready.push(to);
+                }
+            }
+        }
+    }
+    order
+}
+
+/// Roots (in input order) and children of a spanning forest of the graph.
+/// Every node is reached from exactly one root.
+fn spanning_forest(n: usize, edges: &[(usize, usize)]) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let edges = acyclic_edges(n, edges);
+    let mut children = vec![Vec::new(); n];
+    let mut reached = vec![false; n];
+    let mut roots = Vec::new();
+    let has_parent: Vec<bool> = (0..n).map(|node| edges.iter().any(|&(_, to)| to == node)).collect();
+    let starts = (0..n).filter(|&node| !has_parent[node]).chain(0..n);
+    for start in starts {
+        if reached[start] {
+            continue;
+        }
+        reached[start] = true;
+        roots.push(start);
+        let mut queue = vec![start];
+        while !queue.is_empty() {
+            let node = queue.remove(0);
+            for &(from, to) in &edges {
+                if from == node && !reached[to] {
+                    reached[to] = true;
+                    children[node].push(to);
+                    queue.push(to);
+                }
+            }
+        }
+    }
+    (roots, children)
+}
+
+/// Nodes of the forest with every parent before its children
+fn subtree_order(roots: &[usize], children: &[Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(children.len());
+    let mut stack: Vec<usize> = roots.to_vec();
+    while let Some(node) = stack.pop() {
+        order.push(node);
+        stack.extend(&children[node]);
+    }
+    order
+}
+
+/// Something [`arrange`] moves as a whole: a loose shape or a frame/group with its members
+struct Unit {
+    bounds: Bounds,
+    group: Option<Uuid>,
+    shapes: Vec<Uuid>,
+}
+
+/// Lay out shapes already on the board. `ids` limits the layout to those
+/// shapes (empty = every unlocked shape). Frames and groups move as one box
+/// with their members, and arrows bound between shapes become the edges of
+/// tree, radial and layered layouts. The result stays anchored at the current
+/// top-left of the shapes. Returns the actions that perform the move.
+pub fn arrange(board: &Board, ids: &[Uuid], kind: LayoutKind, direction: Direction) -> Vec<Action> {
+    let in_scope = |shape: &Shape| {
+        !shape.is_connector() && !board.is_locked(shape) && (ids.is_empty() || ids.contains(&shape.id))
+    };
+    let mut units: Vec<Unit> = Vec::new();
+    for shape in board.shapes.iter().filter(|s| in_scope(s)) {
+        // The outermost container: a frame if the shape is in one, else a plain group
+        let container = board
+            .groups
+            .iter()
+            .find(|g| g.is_frame() && g.members.contains(&shape.id))
+            .or_else(|| board.groups.iter().find(|g| g.members.contains(&shape.id)));
+        match container {
+            Some(group) => {
+                if let Some(unit) = units.iter_mut().find(|u| u.group == Some(group.id)) {
+                    unit.shapes.push(shape.id);
+                } else if let Some(bounds) = board.group_bounds(group.id) {
+                    units.push(Unit { bounds, group: Some(group.id), shapes: vec![shape.id] });
+                }
+            }
+            None => units.push(Unit { bounds: shape.bounds(), group: None, shapes: vec![shape.id] }),
+        }
+    }
+    if units.is_empty() {
+        return Vec::new();
+    }
+
+    let unit_of = |id: Uuid| units.iter().position(|u| u.shapes.contains(&id));
+    let edges: Vec<(usize, usize)> = board
+        .shapes
+        .iter()
+        .filter_map(|s| match s.shape_type {
+            ShapeType::Arrow { start: Some(start), end: Some(end), .. } => Some((unit_of(start)?, unit_of(end)?)),
+            _ => None,
+        })
+        .filter(|(from, to)| from != to)
+        .collect();
+    let sizes: Vec<(f64, f64)> = units.iter().map(|u| (u.bounds.width(), u.bounds.height())).collect();
+    let current: Vec<(f64, f64)> = units.iter().map(|u| (u.bounds.min_x, u.bounds.min_y)).collect();
+
+    let positions = match kind {
+        LayoutKind::Grid => grid(&sizes, None),
+        LayoutKind::Tree => tree(&sizes, &edges, direction),
+        LayoutKind::Radial => radial(&sizes, &edges),
+        LayoutKind::Layered => layered(&sizes, &edges, direction),
+        LayoutKind::RemoveOverlaps => remove_overlaps(&current, &sizes, NODE_GAP / 2.0),
+    };
+    let positions = match kind {
+        LayoutKind::RemoveOverlaps => positions,
+        _ => {
+            let anchor = top_left(&current);
+            let origin = top_left(&positions);
+            positions
+                .iter()
+                .map(|&(x, y)| (anchor.0 + x - origin.0, anchor.1 + y - origin.1))
+                .collect()
+        }
+    };
+
+    let mut moves = Vec::new();
+    let mut actions = Vec::new();
+    for (unit, (x, y)) in units.iter().zip(positions) {
+        let (dx, dy) = (x - unit.bounds.min_x, y - unit.bounds.min_y);
+        if dx.abs() < 0.5 && dy.abs() < 0.5 {
+            continue;
+        }
+        match unit.group {
+            Some(id) => actions.push(Action::TransformGroup {
+                id,
+                bounds: Bounds {
+                    min_x: x,
+                    min_y: y,
+                    max_x: x + unit.bounds.width(),
+                    max_y: y + unit.bounds.height(),
+                },
+            }),
+            None => moves.extend(unit.shapes.iter().map(|&id| (id, x, y))),
+        }
+    }
+    if !moves.is_empty() {
+        actions.insert(0, Action::MoveShapes(moves));
+    }
+    actions
+}
+
+fn top_left(positions: &[(f64, f64)]) -> (f64, f64) {
+    positions
+        .iter()
+        .fold((f64::INFINITY, f64::INFINITY), |(x, y), p| (x.min(p.0), y.min(p.1)))
+}
+
+/// Top-left corner for a new `size` box: the first free spot in reading
+/// order inside `area`, keeping [`NODE_GAP`] / 2 from existing shapes, or
+/// below everything when the area is full.
+#[cfg(feature = "server")]
+pub fn free_spot(board: &Board, area: &Bounds, size: (f64, f64)) -> (f64, f64) {
+    const MARGIN: f64 = 40.0;
+    const STEP: f64 = 20.0;
+    let occupied: Vec<Bounds> = board
+        .shapes
+        .iter()
+        .filter(|s| board.is_visible(s))
+        .map(|s| s.bounds().padded(NODE_GAP / 2.0))
+        .collect();
+    let fits = |x: f64, y: f64| {
+        occupied
+            .iter()
+            .all(|b| x >= b.max_x || x + size.0 <= b.min_x || y >= b.max_y || y + size.1 <= b.min_y)
+    };
+    let mut y = area.min_y + MARGIN;
+    while y + size.1 <= area.max_y - MARGIN {
+        let mut x = area.min_x + MARGIN;
+        while x + size.0 <= area.max_x - MARGIN {
+            if fits(x, y) {
+                return (x, y);
+            }
+            x += STEP;
+        }
+        y += STEP;
+    }
+    let below = occupied.iter().map(|b| b.max_y).fold(area.min_y + MARGIN, f64::max);
+    (area.min_x + MARGIN, below)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Group;
+
+    fn overlapping(positions: &[(f64, f64)], sizes: &[(f64, f64)]) -> Option<(usize, usize)> {
+        for i in 0..positions.len() {
+            for j in i + 1..positions.len() {
+                let ((ax, ay), (aw, ah)) = (positions[i], sizes[i]);
+                let ((bx, by), (bw, bh)) = (positions[j], sizes[j]);
+                if ax < bx + bw && bx < ax + aw && ay < by + bh && by < ay + ah {
+                    return Some((i, j));
+                }
+            }
+        }
+        None
+    }
+
+    fn centre(position: (f64, f64), size: (f64, f64)) -> (f64, f64) {
+        (position.0 + size.0 / 2.0, position.1 + size.1 / 2.0)
+    }
+
+    #[test]
+    fn grid_sizes_columns_and_rows_to_their_largest_box() {
+        let sizes = [(100.0, 50.0), (60.0, 80.0), (120.0, 40.0), (80.0, 40.0), (40.0, 40.0)];
+        let positions = grid(&sizes, None);
+        assert_eq!(
+            positions,
+            vec![
+                (0.0, 0.0),
+                (100.0 + NODE_GAP, 0.0),
+                (100.0 + 60.0 + 2.0 * NODE_GAP, 0.0),
+                (0.0, 80.0 + NODE_GAP),
+                (100.0 + NODE_GAP, 80.0 + NODE_GAP),
+            ]
+        );
+        assert_eq!(grid(&sizes, Some(1))[4], (0.0, 50.0 + 80.0 + 40.0 + 40.0 + 4.0 * NODE_GAP));
+        assert!(grid(&[], None).is_empty());
+    }
+
+    #[test]
+    fn tree_centres_parents_over_their_children() {
+        // 0 has children 1 and 2; 2 has children 3 and 4
+        let sizes = [(100.0, 40.0); 5];
+        let edges = [(0, 1), (0, 2), (2, 3), (2, 4)];
+        let positions = tree(&sizes, &edges, Direction::TopDown);
+        assert_eq!(overlapping(&positions, &sizes), None);
+        let c: Vec<_> = positions.iter().zip(&sizes).map(|(&p, &s)| centre(p, s)).collect();
+        assert_eq!(c[2].0, (c[3].0 + c[4].0) / 2.0);
+        // The root sits over the middle of everything below it
+        let left = positions[1..].iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let right = positions[1..].iter().map(|p| p.0 + 100.0).fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(c[0].0, (left + right) / 2.0);
+        assert!(c[0].1 < c[1].1 && c[1].1 == c[2].1 && c[2].1 < c[3].1);
+
+        let sideways = tree(&sizes, &edges, Direction::RightLeft);
+        assert!(sideways[0].0 > sideways[2].0 && sideways[2].0 > sideways[3].0);
+    }
+
+    #[test]
+    fn radial_puts_the_root_in_the_middle() {
+        let sizes = [(120.0, 60.0), (80.0, 40.0), (80.0, 40.0), (80.0, 40.0), (80.0, 40.0), (80.0, 40.0)];
+        let edges = [(0, 1), (0, 2), (0, 3), (1, 4), (1, 5)];
+        let positions = radial(&sizes, &edges);
+        assert_eq!(overlapping(&positions, &sizes), None);
+        let c: Vec<_> = positions.iter().zip(&sizes).map(|(&p, &s)| centre(p, s)).collect();
+        let distance = |i: usize| (c[i].0 - c[0].0).hypot(c[i].1 - c[0].1);
+        assert!(distance(1) > 0.0);
+        assert!((distance(1) - distance(2)).abs() < 1.0 && (distance(2) - distance(3)).abs() < 1.0);
+        assert!(distance(4) > distance(1) && distance(5) > distance(1));
+    }
+
+    #[test]
+    fn layered_ranks_follow_the_edges_and_survive_cycles() {
+        let sizes = [(100.0, 40.0); 4];
+        // 0 -> 1 -> 2 -> 0 is a cycle; 1 -> 3 branches off
+        let edges = [(0, 1), (1, 2), (2, 0), (1, 3)];
+        let positions = layered(&sizes, &edges, Direction::TopDown);
+        assert_eq!(overlapping(&positions, &sizes), None);
+        assert!(positions[0].1 < positions[1].1);
+        assert!(positions[1].1 < positions[2].1 && positions[1].1 < positions[3].1);
+
+        let left_right = layered(&sizes, &[(0, 1)], Direction::LeftRight);
+        assert!(left_right[0].0 < left_right[1].0);
+        let bottom_up = layered(&sizes, &[(0, 1)], Direction::BottomUp);
+        assert!(bottom_up[0].1 > bottom_up[1].1);
+    }
+
+    #[test]
+    fn remove_overlaps_separates_boxes_by_the_gap() {
+        let sizes = [(100.0, 100.0), (100.0, 100.0), (50.0, 50.0)];
+        let positions = remove_overlaps(&[(0.0, 0.0), (60.0, 10.0), (500.0, 500.0)], &sizes, 10.0);
+        assert_eq!(overlapping(&positions, &sizes), None);
+        assert!(positions[1].0 - (positions[0].0 + 100.0) >= 10.0 - 1e-9);
+        // Boxes already apart stay put
+        assert_eq!(positions[2], (500.0, 500.0));
+    }
+
+    #[test]
+    fn arrange_keeps_the_top_left_and_moves_groups_as_one() {
+        let mut board = Board::default();
+        let a = Shape::new(ShapeType::Rectangle, 200.0, 100.0, 100.0, 50.0, "#fff".to_string());
+        let b = Shape::new(ShapeType::Rectangle, 200.0, 120.0, 100.0, 50.0, "#fff".to_string());
+        let c = Shape::new(ShapeType::Rectangle, 600.0, 400.0, 100.0, 50.0, "#fff".to_string());
+        let d = Shape::new(ShapeType::Rectangle, 720.0, 400.0, 100.0, 50.0, "#fff".to_string());
+        let arrow = Shape::connector(&a, &b, "#000".to_string());
+        let group = Group::plain(vec![c.id, d.id]);
+        let group_id = group.id;
+        board.shapes.extend([a.clone(), b.clone(), c, d, arrow]);
+        board.groups.push(group);
+
+        let actions = arrange(&board, &[], LayoutKind::Layered, Direction::TopDown);
+        let mut arranged = board.clone();
+        for action in &actions {
+            arranged.apply(action);
+        }
+        let units = [
+            arranged.shape(a.id).unwrap().bounds(),
+            arranged.shape(b.id).unwrap().bounds(),
+            arranged.group_bounds(group_id).unwrap(),
+        ];
+        let sizes: Vec<_> = units.iter().map(|u| (u.width(), u.height())).collect();
+        let positions: Vec<_> = units.iter().map(|u| (u.min_x, u.min_y)).collect();
+        assert_eq!(overlapping(&positions, &sizes), None);
+        // a -> b is an edge, so b ends up below a
+        assert!(positions[0].1 + sizes[0].1 <= positions[1].1);
+        assert_eq!(top_left(&positions), (200.0, 100.0));
+        assert_eq!(sizes[2], (220.0, 50.0));
+        assert!(actions.iter().any(|a| matches!(a, Action::TransformGroup { id, .. } if *id == group_id)));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn free_spot_avoids_shapes_and_falls_below_a_full_area() {
+        let mut board = Board::default();
+        board.shapes.push(Shape::new(ShapeType::Rectangle, 40.0, 40.0, 200.0, 100.0, "#fff".to_string()));
+        let area = Bounds { min_x: 0.0, min_y: 0.0, max_x: 1000.0, max_y: 600.0 };
+        let (x, y) = free_spot(&board, &area, (100.0, 50.0));
+        assert_eq!((x, y), (260.0, 40.0));
+
+        let small = Bounds { min_x: 0.0, min_y: 0.0, max_x: 300.0, max_y: 200.0 };
+        assert_eq!(free_spot(&board, &small, (100.0, 50.0)), (40.0, 140.0 + NODE_GAP / 2.0));
+    }
+
+    #[test]
+    fn layout_names_parse() {
+        assert_eq!(LayoutKind::parse("Mind-Map"), Some(LayoutKind::Radial));
+        assert_eq!(LayoutKind::parse("remove overlaps"), Some(LayoutKind::RemoveOverlaps));
+        assert_eq!(LayoutKind::parse("spiral"), None);
+        assert_eq!(Direction::parse(" lr "), Some(Direction::LeftRight));
+    }
+}
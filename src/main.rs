@@ -1,10 +1,16 @@
 use dioxus::prelude::*;
 
 mod agent;
+mod clipboard;
 mod components;
+mod export;
+mod formats;
+mod layout;
 mod model;
+mod notes;
 mod state;
 mod tools;
+mod transcript;
 mod views;
 
 #[cfg(feature = "server")]
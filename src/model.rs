@@ -1,11 +1,220 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
+use crate::transcript::TranscriptSummary;
+
+/// A point relative to the owning shape's top-left corner (`x`, `y`)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum ShapeType {
     Rectangle,
     Circle,
     Line,
+    Ellipse,
+    /// Rhombus inscribed in the bounding box, used for flowchart decisions
+    Diamond,
+    /// Closed polygon through `points`
+    Polygon { points: Vec<Point> },
+    /// Open pen stroke through `points`
+    Freehand { points: Vec<Point> },
+    /// Filled note whose label is shown top-left aligned and wrapped
+    StickyNote,
+    /// Raster or SVG image loaded from `href` (URL or data URI)
+    Image { href: String },
+    /// Free-standing text; the label is the content and there is no outline
+    Text,
+    /// Connector from (x, y) to (x + width, y + height) with a head at the end.
+    /// Ends bound to a shape stay attached to its outline when it moves.
+    Arrow {
+        #[serde(default)]
+        start: Option<Uuid>,
+        #[serde(default)]
+        end: Option<Uuid>,
+        /// Plain connector without an arrowhead (undirected edge)
+        #[serde(default)]
+        headless: bool,
+    },
+    /// Bar, line or pie chart drawn from `data`; the label is the title
+    Chart { data: ChartData },
+}
+
+impl ShapeType {
+    /// Names accepted by the `draw_shape` tool
+    #[cfg(feature = "server")]
+    pub const NAMES: &'static [&'static str] = &[
+        "rectangle",
+        "circle",
+        "ellipse",
+        "line",
+        "diamond",
+        "polygon",
+        "freehand",
+        "sticky_note",
+        "image",
+        "text",
+        "arrow",
+    ];
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum ChartKind {
+    #[default]
+    Bar,
+    Line,
+    Pie,
+}
+
+impl ChartKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bar" | "column" => Some(Self::Bar),
+            "line" => Some(Self::Line),
+            "pie" | "donut" => Some(Self::Pie),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bar => "bar",
+            Self::Line => "line",
+            Self::Pie => "pie",
+        }
+    }
+}
+
+/// A named row of values, one per category
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub values: Vec<f64>,
+    /// Overrides the palette color
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// The data behind a chart shape, kept so the chart can be edited as data
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ChartData {
+    pub kind: ChartKind,
+    pub categories: Vec<String>,
+    pub series: Vec<Series>,
+}
+
+impl ChartData {
+    /// Check the series line up with the categories (pie charts use the first series)
+    pub fn validate(&self) -> Result<(), String> {
+        if self.categories.is_empty() {
+            return Err("A chart needs at least one category".to_string());
+        }
+        if self.series.is_empty() {
+            return Err("A chart needs at least one series".to_string());
+        }
+        for series in &self.series {
+            if series.values.len() != self.categories.len() {
+                return Err(format!(
+                    "Series '{}' has {} values for {} categories",
+                    series.name,
+                    series.values.len(),
+                    self.categories.len()
+                ));
+            }
+            if series.values.iter().any(|v| !v.is_finite()) {
+                return Err(format!("Series '{}' has a value that is not a number", series.name));
+            }
+        }
+        if self.kind == ChartKind::Pie && self.series[0].values.iter().all(|v| *v <= 0.0) {
+            return Err("A pie chart needs positive values".to_string());
+        }
+        Ok(())
+    }
+
+    /// Table form for editing: a header row of series names, then one row per category
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![std::iter::once("category")
+            .chain(self.series.iter().map(|s| s.name.as_str()))
+            .map(csv_field)
+            .collect::<Vec<_>>()
+            .join(",")];
+        for (i, category) in self.categories.iter().enumerate() {
+            let values = self.series.iter().map(|s| s.values.get(i).map_or(String::new(), f64::to_string));
+            rows.push(std::iter::once(csv_field(category)).chain(values).collect::<Vec<_>>().join(","));
+        }
+        rows.join("\n")
+    }
+
+    /// Parse the table form written by [`ChartData::to_csv`]. Series keep
+    /// their colors from `previous` when the names match.
+    pub fn from_csv(kind: ChartKind, text: &str, previous: Option<&ChartData>) -> Result<Self, String> {
+        let mut rows = text.lines().filter(|l| !l.trim().is_empty()).map(split_csv_row);
+        let header = rows.next().ok_or_else(|| "The table is empty".to_string())?;
+        let mut series: Vec<Series> = header
+            .iter()
+            .skip(1)
+            .map(|name| Series {
+                name: name.clone(),
+                values: Vec::new(),
+                color: previous
+                    .and_then(|p| p.series.iter().find(|s| &s.name == name))
+                    .and_then(|s| s.color.clone()),
+            })
+            .collect();
+        let mut categories = Vec::new();
+        for (line, row) in rows.enumerate() {
+            let Some((category, values)) = row.split_first() else { continue };
+            categories.push(category.clone());
+            for (i, series) in series.iter_mut().enumerate() {
+                let cell = values.get(i).map(|v| v.trim()).unwrap_or_default();
+                let value = if cell.is_empty() {
+                    0.0
+                } else {
+                    cell.parse()
+                        .map_err(|_| format!("Row {}: '{}' is not a number", line + 2, cell))?
+                };
+                series.values.push(value);
+            }
+        }
+        let data = ChartData {
+            kind,
+            categories,
+            series,
+        };
+        data.validate()?;
+        Ok(data)
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn split_csv_row(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -17,6 +226,36 @@ pub struct Shape {
     pub width: f64,
     pub height: f64,
     pub color: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Layer the shape lives on; `None` is the base layer below all named layers
+    #[serde(default)]
+    pub layer: Option<Uuid>,
+    /// References backing what the shape says
+    #[serde(default)]
+    pub sources: Vec<Source>,
+}
+
+/// A reference cited by a shape
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Source {
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Passage from the source that supports the shape
+    #[serde(default)]
+    pub quote: Option<String>,
+}
+
+impl Source {
+    /// Title to show for the source, falling back to the URL
+    pub fn display_title(&self) -> &str {
+        self.title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .unwrap_or(&self.url)
+    }
 }
 
 impl Shape {
@@ -36,6 +275,457 @@ impl Shape {
             width,
             height,
             color,
+            label: None,
+            layer: None,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Build a point-list shape from absolute world coordinates. The shape's
+    /// bounding box is fitted to the points and the points stored relative to it.
+    pub fn from_points(
+        absolute: &[(f64, f64)],
+        closed: bool,
+        color: String,
+    ) -> Self {
+        let min_x = absolute.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let min_y = absolute.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_x = absolute.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        let max_y = absolute.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        let points = absolute
+            .iter()
+            .map(|&(x, y)| Point {
+                x: x - min_x,
+                y: y - min_y,
+            })
+            .collect();
+        let shape_type = if closed {
+            ShapeType::Polygon { points }
+        } else {
+            ShapeType::Freehand { points }
+        };
+        Self::new(shape_type, min_x, min_y, max_x - min_x, max_y - min_y, color)
+    }
+
+    /// Text shape sized to fit `text` at the default label size
+    pub fn text(x: f64, y: f64, text: String) -> Self {
+        let longest = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let line_count = text.lines().count().max(1);
+        let width = (longest as f64 * 14.0 * 0.55 + 24.0).clamp(60.0, 600.0);
+        let height = line_count as f64 * 14.0 * 1.25 + 24.0;
+        let mut shape = Self::new(ShapeType::Text, x, y, width, height, "transparent".to_string());
+        shape.label = Some(text);
+        shape
+    }
+
+    /// Arrow between two points, optionally bound to shapes at either end
+    pub fn arrow(
+        from: (f64, f64),
+        to: (f64, f64),
+        start: Option<Uuid>,
+        end: Option<Uuid>,
+        color: String,
+    ) -> Self {
+        Self::new(
+            ShapeType::Arrow {
+                start,
+                end,
+                headless: false,
+            },
+            from.0,
+            from.1,
+            to.0 - from.0,
+            to.1 - from.1,
+            color,
+        )
+    }
+
+    /// Arrow connecting the outlines of two shapes
+    pub fn connector(from: &Shape, to: &Shape, color: String) -> Self {
+        let (start, end) = connector_points(from, to);
+        Self::arrow(start, end, Some(from.id), Some(to.id), color)
+    }
+
+    /// Start and end of a line or arrow
+    pub fn endpoints(&self) -> ((f64, f64), (f64, f64)) {
+        ((self.x, self.y), (self.x + self.width, self.y + self.height))
+    }
+
+    pub fn is_connector(&self) -> bool {
+        matches!(self.shape_type, ShapeType::Line | ShapeType::Arrow { .. })
+    }
+
+    /// Where the ray from the shape's center towards `toward` leaves its outline
+    pub fn boundary_point(&self, toward: (f64, f64)) -> (f64, f64) {
+        let (cx, cy) = self.center();
+        let (dx, dy) = (toward.0 - cx, toward.1 - cy);
+        if dx == 0.0 && dy == 0.0 {
+            return (cx, cy);
+        }
+        let (rx, ry) = (self.width.abs() / 2.0, self.height.abs() / 2.0);
+        let t = match self.shape_type {
+            ShapeType::Circle | ShapeType::Ellipse => {
+                1.0 / ((dx / rx.max(1e-9)).powi(2) + (dy / ry.max(1e-9)).powi(2)).sqrt()
+            }
+            ShapeType::Diamond => 1.0 / (dx.abs() / rx.max(1e-9) + dy.abs() / ry.max(1e-9)),
+            _ => {
+                let tx = if dx != 0.0 { rx / dx.abs() } else { f64::INFINITY };
+                let ty = if dy != 0.0 { ry / dy.abs() } else { f64::INFINITY };
+                tx.min(ty)
+            }
+        };
+        (cx + dx * t, cy + dy * t)
+    }
+
+    /// Copy of the shape with a fresh id, shifted by (`dx`, `dy`)
+    pub fn duplicated(&self, dx: f64, dy: f64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            x: self.x + dx,
+            y: self.y + dy,
+            ..self.clone()
+        }
+    }
+
+    /// Citation numbers of the shape's sources within a board's bibliography
+    pub fn citation_numbers(&self, bibliography: &[&Source]) -> Vec<usize> {
+        let mut numbers: Vec<usize> = self
+            .sources
+            .iter()
+            .filter_map(|s| bibliography.iter().position(|b| b.url == s.url))
+            .map(|i| i + 1)
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        numbers
+    }
+
+    /// Center of the bounding box in world coordinates
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Corner points of the diamond, clockwise from the top
+    pub fn diamond_points(&self) -> [Point; 4] {
+        let (cx, cy) = self.center();
+        [
+            Point { x: cx, y: self.y },
+            Point { x: self.x + self.width, y: cy },
+            Point { x: cx, y: self.y + self.height },
+            Point { x: self.x, y: cy },
+        ]
+    }
+
+    /// Point list of a polygon/freehand shape translated to world coordinates
+    pub fn absolute_points(&self) -> Vec<Point> {
+        match &self.shape_type {
+            ShapeType::Polygon { points } | ShapeType::Freehand { points } => points
+                .iter()
+                .map(|p| Point {
+                    x: self.x + p.x,
+                    y: self.y + p.y,
+                })
+                .collect(),
+            ShapeType::Diamond => self.diamond_points().to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Break the label into lines that roughly fit the shape's width at the
+    /// given font size (SVG has no native text wrapping).
+    pub fn label_lines(&self, font_size: f64) -> Vec<String> {
+        let Some(label) = self.label.as_deref().filter(|l| !l.trim().is_empty()) else {
+            return Vec::new();
+        };
+        // Average glyph width is a bit over half the font size
+        let max_chars = ((self.width.abs() - 16.0) / (font_size * 0.55)).max(6.0) as usize;
+        let mut lines = Vec::new();
+        for paragraph in label.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                    lines.push(std::mem::take(&mut line));
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(word);
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Resize the box so its label fits, wrapping lines wider than `max_width`
+    #[cfg(feature = "server")]
+    pub fn fit_to_label(&mut self, max_width: f64) {
+        let Some(label) = self.label.clone() else {
+            return;
+        };
+        let longest = label.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        self.width = (longest as f64 * 14.0 * 0.55 + 32.0).clamp(80.0, max_width);
+        let lines = self.label_lines(14.0).len().max(1);
+        self.height = (lines as f64 * 14.0 * 1.25 + 28.0).max(44.0);
+        // Round shapes only have room for text in the middle
+        if matches!(self.shape_type, ShapeType::Circle | ShapeType::Ellipse | ShapeType::Diamond) {
+            self.width *= 1.35;
+            self.height *= 1.4;
+        }
+    }
+
+    /// Map the shape from one reference rectangle to another, scaling its
+    /// position, size and any point list. Used to move/resize group members.
+    pub fn map_between(&mut self, from: &Bounds, to: &Bounds) {
+        let sx = if from.width() > 0.0 { to.width() / from.width() } else { 1.0 };
+        let sy = if from.height() > 0.0 { to.height() / from.height() } else { 1.0 };
+        self.x = to.min_x + (self.x - from.min_x) * sx;
+        self.y = to.min_y + (self.y - from.min_y) * sy;
+        self.width *= sx;
+        self.height *= sy;
+        if let ShapeType::Polygon { points } | ShapeType::Freehand { points } = &mut self.shape_type {
+            for p in points.iter_mut() {
+                p.x *= sx;
+                p.y *= sy;
+            }
+        }
+    }
+
+    /// Axis-aligned bounds in world coordinates. Lines may have negative
+    /// width/height, so the extents are normalised here.
+    pub fn bounds(&self) -> Bounds {
+        let (x1, x2) = (self.x, self.x + self.width);
+        let (y1, y2) = (self.y, self.y + self.height);
+        Bounds {
+            min_x: x1.min(x2),
+            min_y: y1.min(y2),
+            max_x: x1.max(x2),
+            max_y: y1.max(y2),
+        }
+    }
+}
+
+/// Endpoints of a connector between the outlines of two shapes
+pub fn connector_points(from: &Shape, to: &Shape) -> ((f64, f64), (f64, f64)) {
+    (from.boundary_point(to.center()), to.boundary_point(from.center()))
+}
+
+/// Size of the on-screen canvas in SVG units at zoom 1.0.
+pub const CANVAS_WIDTH: f64 = 800.0;
+pub const CANVAS_HEIGHT: f64 = 500.0;
+
+pub const MIN_ZOOM: f64 = 0.1;
+pub const MAX_ZOOM: f64 = 8.0;
+
+/// Axis-aligned rectangle in world coordinates
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Bounds {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Bounds {
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    pub fn padded(&self, padding: f64) -> Bounds {
+        Bounds {
+            min_x: self.min_x - padding,
+            min_y: self.min_y - padding,
+            max_x: self.max_x + padding,
+            max_y: self.max_y + padding,
+        }
+    }
+}
+
+/// The region of the (unbounded) world that the canvas is showing.
+/// `x`/`y` is the world coordinate of the top-left corner of the canvas.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Viewport {
+    /// Visible width in world units
+    pub fn width(&self) -> f64 {
+        CANVAS_WIDTH / self.zoom
+    }
+
+    /// Visible height in world units
+    pub fn height(&self) -> f64 {
+        CANVAS_HEIGHT / self.zoom
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            min_x: self.x,
+            min_y: self.y,
+            max_x: self.x + self.width(),
+            max_y: self.y + self.height(),
+        }
+    }
+
+    /// Value for the SVG `viewBox` attribute
+    pub fn view_box(&self) -> String {
+        format!("{} {} {} {}", self.x, self.y, self.width(), self.height())
+    }
+
+    /// Zoom by `factor`, keeping the world point (`anchor_x`, `anchor_y`) fixed on screen.
+    pub fn zoom_at(&mut self, factor: f64, anchor_x: f64, anchor_y: f64) {
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let ratio = self.zoom / zoom;
+        self.x = anchor_x - (anchor_x - self.x) * ratio;
+        self.y = anchor_y - (anchor_y - self.y) * ratio;
+        self.zoom = zoom;
+    }
+
+    /// Pan by a distance given in world units
+    pub fn pan_by(&mut self, dx: f64, dy: f64) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    /// Move the viewport so that its center is at the given world point.
+    pub fn center_on(&mut self, x: f64, y: f64) {
+        self.x = x - self.width() / 2.0;
+        self.y = y - self.height() / 2.0;
+    }
+
+    /// Smallest viewport that shows all of `bounds` with some padding around it.
+    pub fn fit(bounds: &Bounds, padding: f64) -> Viewport {
+        let bounds = bounds.padded(padding);
+        let zoom = (CANVAS_WIDTH / bounds.width().max(1.0))
+            .min(CANVAS_HEIGHT / bounds.height().max(1.0))
+            .clamp(MIN_ZOOM, MAX_ZOOM);
+        let mut viewport = Viewport { x: 0.0, y: 0.0, zoom };
+        viewport.center_on(
+            bounds.min_x + bounds.width() / 2.0,
+            bounds.min_y + bounds.height() / 2.0,
+        );
+        viewport
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum GroupKind {
+    /// Invisible grouping whose bounds follow its members
+    Group,
+    /// Titled, bordered section of the board with its own geometry
+    Frame,
+}
+
+/// A set of shapes that move and resize together
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Group {
+    pub id: Uuid,
+    pub kind: GroupKind,
+    pub title: String,
+    pub members: Vec<Uuid>,
+    /// Frame geometry; unused for plain groups
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Group {
+    pub fn plain(members: Vec<Uuid>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            kind: GroupKind::Group,
+            title: String::new(),
+            members,
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 0.0,
+        }
+    }
+
+    pub fn frame(title: String, members: Vec<Uuid>, bounds: Bounds) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            kind: GroupKind::Frame,
+            title,
+            members,
+            x: bounds.min_x,
+            y: bounds.min_y,
+            width: bounds.width(),
+            height: bounds.height(),
+        }
+    }
+
+    pub fn is_frame(&self) -> bool {
+        self.kind == GroupKind::Frame
+    }
+}
+
+/// A named layer. Layers stack in `Board::layers` order above the base layer.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Layer {
+    pub id: Uuid,
+    pub name: String,
+    pub visible: bool,
+    /// Shapes on a locked layer cannot be selected or edited
+    pub locked: bool,
+}
+
+impl Layer {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            visible: true,
+            locked: false,
+        }
+    }
+}
+
+/// Stacking-order operations on a set of shapes
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ZOrder {
+    BringToFront,
+    SendToBack,
+    BringForward,
+    SendBackward,
+}
+
+impl ZOrder {
+    #[cfg(feature = "server")]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace([' ', '-'], "_").as_str() {
+            "bring_to_front" | "front" => Some(ZOrder::BringToFront),
+            "send_to_back" | "back" => Some(ZOrder::SendToBack),
+            "bring_forward" | "forward" => Some(ZOrder::BringForward),
+            "send_backward" | "backward" => Some(ZOrder::SendBackward),
+            _ => None,
         }
     }
 }
@@ -43,6 +733,443 @@ impl Shape {
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
 pub struct Board {
     pub shapes: Vec<Shape>,
+    #[serde(default)]
+    pub viewport: Viewport,
+    #[serde(default)]
+    pub groups: Vec<Group>,
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+    /// Research notes in Markdown, written by the agent and editable by users
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl Board {
+    /// Bounds of all shapes, or `None` for an empty board
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.shapes
+            .iter()
+            .map(Shape::bounds)
+            .reduce(|acc, b| acc.union(&b))
+    }
+
+    pub fn shape(&self, id: Uuid) -> Option<&Shape> {
+        self.shapes.iter().find(|s| s.id == id)
+    }
+
+    pub fn group(&self, id: Uuid) -> Option<&Group> {
+        self.groups.iter().find(|g| g.id == id)
+    }
+
+    pub fn layer(&self, id: Uuid) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.id == id)
+    }
+
+    #[cfg(feature = "server")]
+    pub fn layer_by_name(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the shape's layer is shown (the base layer always is)
+    pub fn is_visible(&self, shape: &Shape) -> bool {
+        shape
+            .layer
+            .and_then(|id| self.layer(id))
+            .is_none_or(|l| l.visible)
+    }
+
+    pub fn is_locked(&self, shape: &Shape) -> bool {
+        shape
+            .layer
+            .and_then(|id| self.layer(id))
+            .is_some_and(|l| l.locked)
+    }
+
+    /// Visible shapes in paint order: the base layer first, then each named
+    /// layer in turn, keeping insertion order within a layer.
+    pub fn render_order(&self) -> Vec<&Shape> {
+        let layer_rank = |shape: &Shape| {
+            shape
+                .layer
+                .and_then(|id| self.layers.iter().position(|l| l.id == id))
+                .map_or(0, |i| i + 1)
+        };
+        let mut shapes: Vec<&Shape> = self.shapes.iter().filter(|s| self.is_visible(s)).collect();
+        shapes.sort_by_key(|s| layer_rank(s));
+        shapes
+    }
+
+    /// Distinct sources cited by visible shapes, in paint order. A source's
+    /// position in this list (plus one) is its citation number.
+    pub fn bibliography(&self) -> Vec<&Source> {
+        let mut sources: Vec<&Source> = Vec::new();
+        for source in self.render_order().into_iter().flat_map(|s| &s.sources) {
+            if !sources.iter().any(|s| s.url == source.url) {
+                sources.push(source);
+            }
+        }
+        sources
+    }
+
+    /// Restack the given shapes. Shapes only move relative to others on the
+    /// same layer, since layers already define the coarse order.
+    fn reorder(&mut self, ids: &[Uuid], op: ZOrder) {
+        let selected = |s: &Shape| ids.contains(&s.id);
+        match op {
+            ZOrder::BringToFront | ZOrder::SendToBack => {
+                let (moved, rest): (Vec<Shape>, Vec<Shape>) =
+                    self.shapes.drain(..).partition(|s| selected(s));
+                self.shapes = if op == ZOrder::BringToFront {
+                    rest.into_iter().chain(moved).collect()
+                } else {
+                    moved.into_iter().chain(rest).collect()
+                };
+            }
+            ZOrder::BringForward => {
+                for i in (0..self.shapes.len()).rev() {
+                    if !selected(&self.shapes[i]) {
+                        continue;
+                    }
+                    let layer = self.shapes[i].layer;
+                    let next = (i + 1..self.shapes.len()).find(|&j| self.shapes[j].layer == layer);
+                    if let Some(j) = next.filter(|&j| !selected(&self.shapes[j])) {
+                        self.shapes.swap(i, j);
+                    }
+                }
+            }
+            ZOrder::SendBackward => {
+                for i in 0..self.shapes.len() {
+                    if !selected(&self.shapes[i]) {
+                        continue;
+                    }
+                    let layer = self.shapes[i].layer;
+                    let prev = (0..i).rev().find(|&j| self.shapes[j].layer == layer);
+                    if let Some(j) = prev.filter(|&j| !selected(&self.shapes[j])) {
+                        self.shapes.swap(i, j);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Innermost plain group containing the shape, if any
+    pub fn group_of(&self, shape_id: Uuid) -> Option<&Group> {
+        self.groups
+            .iter()
+            .rev()
+            .find(|g| !g.is_frame() && g.members.contains(&shape_id))
+    }
+
+    /// Union of the bounds of the given shapes
+    pub fn members_bounds(&self, members: &[Uuid]) -> Option<Bounds> {
+        self.shapes
+            .iter()
+            .filter(|s| members.contains(&s.id))
+            .map(Shape::bounds)
+            .reduce(|acc, b| acc.union(&b))
+    }
+
+    /// Frames use their own geometry; plain groups span their members
+    pub fn group_bounds(&self, id: Uuid) -> Option<Bounds> {
+        let group = self.group(id)?;
+        match group.kind {
+            GroupKind::Frame => Some(Bounds {
+                min_x: group.x,
+                min_y: group.y,
+                max_x: group.x + group.width,
+                max_y: group.y + group.height,
+            }),
+            GroupKind::Group => self.members_bounds(&group.members),
+        }
+    }
+
+    /// Move/resize a group to `to`, carrying its members along proportionally
+    fn transform_group(&mut self, id: Uuid, to: &Bounds) {
+        let Some(from) = self.group_bounds(id) else {
+            return;
+        };
+        let Some(group) = self.groups.iter_mut().find(|g| g.id == id) else {
+            return;
+        };
+        group.x = to.min_x;
+        group.y = to.min_y;
+        group.width = to.width();
+        group.height = to.height();
+        let members = group.members.clone();
+        for shape in self.shapes.iter_mut().filter(|s| members.contains(&s.id)) {
+            shape.map_between(&from, to);
+        }
+    }
+
+    /// Give every shape and group a new id, keeping group membership and
+    /// arrow bindings intact. Used before merging imported content.
+    pub fn reassign_ids(&mut self) {
+        let ids: HashMap<Uuid, Uuid> = self
+            .shapes
+            .iter()
+            .map(|s| (s.id, Uuid::new_v4()))
+            .collect();
+        let remap = |id: &mut Uuid| {
+            if let Some(new_id) = ids.get(id) {
+                *id = *new_id;
+            }
+        };
+        for shape in self.shapes.iter_mut() {
+            remap(&mut shape.id);
+            if let ShapeType::Arrow { start, end, .. } = &mut shape.shape_type {
+                start.iter_mut().for_each(remap);
+                end.iter_mut().for_each(remap);
+            }
+        }
+        for group in self.groups.iter_mut() {
+            group.id = Uuid::new_v4();
+            group.members.iter_mut().for_each(remap);
+        }
+    }
+
+    /// Re-attach bound arrow ends to the outlines of the shapes they point at.
+    /// An end whose shape no longer exists stays where it is.
+    pub fn route_arrows(&mut self) {
+        let mut routes = Vec::new();
+        for (i, shape) in self.shapes.iter().enumerate() {
+            let ShapeType::Arrow { start, end, .. } = &shape.shape_type else {
+                continue;
+            };
+            let start_shape = start.and_then(|id| self.shape(id));
+            let end_shape = end.and_then(|id| self.shape(id));
+            if start_shape.is_none() && end_shape.is_none() {
+                continue;
+            }
+            let (mut from, mut to) = shape.endpoints();
+            // Aim each bound end at the other end's shape center (or free point)
+            let from_target = end_shape.map_or(to, Shape::center);
+            let to_target = start_shape.map_or(from, Shape::center);
+            if let Some(s) = start_shape {
+                from = s.boundary_point(from_target);
+            }
+            if let Some(s) = end_shape {
+                to = s.boundary_point(to_target);
+            }
+            routes.push((i, from, to));
+        }
+        for (i, from, to) in routes {
+            let shape = &mut self.shapes[i];
+            shape.x = from.0;
+            shape.y = from.1;
+            shape.width = to.0 - from.0;
+            shape.height = to.1 - from.1;
+        }
+    }
+
+    /// Apply a single action to the board. Shared by the client state and the
+    /// server-side mirror so both reduce history the same way.
+    pub fn apply(&mut self, action: &Action) {
+        match action {
+            Action::Draw(shape) => {
+                self.shapes.push(shape.clone());
+                self.route_arrows();
+            }
+            Action::Wipe => {
+                self.shapes.clear();
+                self.groups.clear();
+            }
+            Action::NewBoard => {
+                self.shapes.clear();
+                self.groups.clear();
+                self.layers.clear();
+                self.notes.clear();
+                self.viewport = Viewport::default();
+            }
+            Action::CreateGroup(group) => {
+                if self.group(group.id).is_none() {
+                    self.groups.push(group.clone());
+                }
+            }
+            Action::TransformGroup { id, bounds } => {
+                self.transform_group(*id, bounds);
+                self.route_arrows();
+            }
+            Action::RenameGroup { id, title } => {
+                if let Some(group) = self.groups.iter_mut().find(|g| g.id == *id) {
+                    group.title = title.clone();
+                }
+            }
+            Action::Ungroup(id) => {
+                self.groups.retain(|g| g.id != *id);
+            }
+            Action::Reorder { ids, op } => {
+                self.reorder(ids, *op);
+            }
+            Action::PutLayer(layer) => {
+                match self.layers.iter_mut().find(|l| l.id == layer.id) {
+                    Some(existing) => *existing = layer.clone(),
+                    None => self.layers.push(layer.clone()),
+                }
+            }
+            Action::RemoveLayer(id) => {
+                self.layers.retain(|l| l.id != *id);
+                for shape in self.shapes.iter_mut().filter(|s| s.layer == Some(*id)) {
+                    shape.layer = None;
+                }
+            }
+            Action::Delete(ids) => {
+                self.shapes.retain(|s| !ids.contains(&s.id));
+                for group in self.groups.iter_mut() {
+                    group.members.retain(|m| !ids.contains(m));
+                }
+                // Plain groups exist only through their members
+                self.groups.retain(|g| g.is_frame() || !g.members.is_empty());
+            }
+            Action::AssignLayer { ids, layer } => {
+                for shape in self.shapes.iter_mut().filter(|s| ids.contains(&s.id)) {
+                    shape.layer = *layer;
+                }
+            }
+            Action::MoveShapes(moves) => {
+                for (id, x, y) in moves {
+                    if let Some(shape) = self.shapes.iter_mut().find(|s| s.id == *id) {
+                        shape.x = *x;
+                        shape.y = *y;
+                    }
+                }
+                self.route_arrows();
+            }
+            Action::UpdateShape(updated) => {
+                if let Some(shape) = self.shapes.iter_mut().find(|s| s.id == updated.id) {
+                    *shape = updated.clone();
+                }
+                self.route_arrows();
+            }
+            Action::SetNotes(notes) => {
+                self.notes = notes.clone();
+            }
+            Action::SetOrder(ids) => {
+                let rank = |s: &Shape| ids.iter().position(|id| *id == s.id).unwrap_or(usize::MAX);
+                self.shapes.sort_by_key(rank);
+            }
+            Action::Batch(actions) => {
+                for action in actions {
+                    self.apply(action);
+                }
+            }
+        }
+    }
+
+    /// The action that undoes `action`. Must be computed against the board as
+    /// it is *before* `action` is applied.
+    pub fn inverse(&self, action: &Action) -> Action {
+        let nothing = Action::Batch(Vec::new());
+        match action {
+            Action::Draw(shape) => Action::Delete(vec![shape.id]),
+            Action::Wipe => Action::Batch(
+                self.shapes
+                    .iter()
+                    .map(|s| Action::Draw(s.clone()))
+                    .chain(self.groups.iter().map(|g| Action::CreateGroup(g.clone())))
+                    .collect(),
+            ),
+            Action::NewBoard => Action::Batch(
+                self.layers
+                    .iter()
+                    .map(|l| Action::PutLayer(l.clone()))
+                    .chain(self.shapes.iter().map(|s| Action::Draw(s.clone())))
+                    .chain(self.groups.iter().map(|g| Action::CreateGroup(g.clone())))
+                    .chain(std::iter::once(Action::SetNotes(self.notes.clone())))
+                    .collect(),
+            ),
+            Action::CreateGroup(group) if self.group(group.id).is_none() => Action::Ungroup(group.id),
+            Action::CreateGroup(_) => nothing,
+            Action::TransformGroup { id, .. } => match self.group_bounds(*id) {
+                Some(bounds) => Action::TransformGroup { id: *id, bounds },
+                None => nothing,
+            },
+            Action::RenameGroup { id, .. } => match self.group(*id) {
+                Some(group) => Action::RenameGroup {
+                    id: *id,
+                    title: group.title.clone(),
+                },
+                None => nothing,
+            },
+            Action::Ungroup(id) => match self.group(*id) {
+                Some(group) => Action::CreateGroup(group.clone()),
+                None => nothing,
+            },
+            Action::Reorder { .. } | Action::SetOrder(_) => {
+                Action::SetOrder(self.shapes.iter().map(|s| s.id).collect())
+            }
+            Action::PutLayer(layer) => match self.layer(layer.id) {
+                Some(existing) => Action::PutLayer(existing.clone()),
+                None => Action::RemoveLayer(layer.id),
+            },
+            Action::RemoveLayer(id) => match self.layer(*id) {
+                Some(layer) => Action::Batch(vec![
+                    Action::PutLayer(layer.clone()),
+                    Action::AssignLayer {
+                        ids: self
+                            .shapes
+                            .iter()
+                            .filter(|s| s.layer == Some(*id))
+                            .map(|s| s.id)
+                            .collect(),
+                        layer: Some(*id),
+                    },
+                ]),
+                None => nothing,
+            },
+            Action::Delete(ids) => {
+                let deleted: Vec<&Shape> = self.shapes.iter().filter(|s| ids.contains(&s.id)).collect();
+                let mut actions: Vec<Action> = deleted.iter().map(|s| Action::Draw((*s).clone())).collect();
+                // Put back the groups that lost members, then the stacking order
+                for group in self
+                    .groups
+                    .iter()
+                    .filter(|g| g.members.iter().any(|m| ids.contains(m)))
+                {
+                    actions.push(Action::Ungroup(group.id));
+                    actions.push(Action::CreateGroup(group.clone()));
+                }
+                actions.push(Action::SetOrder(self.shapes.iter().map(|s| s.id).collect()));
+                Action::Batch(actions)
+            }
+            Action::AssignLayer { ids, .. } => {
+                let mut previous: Vec<(Option<Uuid>, Vec<Uuid>)> = Vec::new();
+                for shape in self.shapes.iter().filter(|s| ids.contains(&s.id)) {
+                    match previous.iter_mut().find(|(layer, _)| *layer == shape.layer) {
+                        Some((_, ids)) => ids.push(shape.id),
+                        None => previous.push((shape.layer, vec![shape.id])),
+                    }
+                }
+                Action::Batch(
+                    previous
+                        .into_iter()
+                        .map(|(layer, ids)| Action::AssignLayer { ids, layer })
+                        .collect(),
+                )
+            }
+            Action::MoveShapes(moves) => Action::MoveShapes(
+                moves
+                    .iter()
+                    .filter_map(|(id, _, _)| self.shape(*id).map(|s| (s.id, s.x, s.y)))
+                    .collect(),
+            ),
+            Action::UpdateShape(updated) => match self.shape(updated.id) {
+                Some(shape) => Action::UpdateShape(shape.clone()),
+                None => nothing,
+            },
+            Action::SetNotes(_) => Action::SetNotes(self.notes.clone()),
+            Action::Batch(actions) => {
+                // Each step is undone against the board as it was just before that step
+                let mut board = self.clone();
+                let mut inverses = Vec::with_capacity(actions.len());
+                for action in actions {
+                    inverses.push(board.inverse(action));
+                    board.apply(action);
+                }
+                inverses.reverse();
+                Action::Batch(inverses)
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -50,6 +1177,53 @@ pub enum Action {
     Draw(Shape),
     Wipe,
     NewBoard,
+    CreateGroup(Group),
+    /// Move and/or resize a group or frame; members are mapped to the new bounds
+    TransformGroup { id: Uuid, bounds: Bounds },
+    RenameGroup { id: Uuid, title: String },
+    /// Dissolve a group or frame, keeping its member shapes
+    Ungroup(Uuid),
+    /// Change the stacking order of shapes within their layers
+    Reorder { ids: Vec<Uuid>, op: ZOrder },
+    /// Add a layer, or replace the one with the same id (rename, show/hide, lock)
+    PutLayer(Layer),
+    /// Delete a layer; its shapes fall back to the base layer
+    RemoveLayer(Uuid),
+    /// Remove shapes (and their group memberships)
+    Delete(Vec<Uuid>),
+    /// Move shapes onto a layer (`None` = base layer)
+    AssignLayer { ids: Vec<Uuid>, layer: Option<Uuid> },
+    /// Move shapes to new top-left corners (automatic layout); bound arrows follow
+    MoveShapes(Vec<(Uuid, f64, f64)>),
+    /// Replace a shape (matched by id) with an edited version, e.g. new chart data
+    UpdateShape(Shape),
+    /// Replace the board's research notes
+    SetNotes(String),
+    /// Restore a stacking order; shapes not listed keep their relative order on top
+    SetOrder(Vec<Uuid>),
+    /// Several actions applied, and undone, as one
+    Batch(Vec<Action>),
+}
+
+/// Result of polling the server history
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PollResponse {
+    /// History index to poll from next time
+    pub next_index: usize,
+    /// New actions, excluding ones the polling client submitted itself
+    pub actions: Vec<Action>,
+}
+
+/// One exchange with the agent about the current board
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ConversationTurn {
+    /// The topic or follow-up instruction the user sent
+    pub instruction: String,
+    /// The agent's final answer
+    pub answer: String,
+    /// How the run went and what it used
+    #[serde(default)]
+    pub run: Option<TranscriptSummary>,
 }
 
 /// Supported LLM providers (matches radkit::models::providers)
@@ -99,6 +1273,105 @@ impl AgentProvider {
     }
 }
 
+/// Web search backends behind the `web_search` tool
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub enum SearchBackend {
+    /// Scrapes html.duckduckgo.com; needs no key
+    #[default]
+    DuckDuckGo,
+    /// Self-hosted SearxNG instance with the JSON format enabled
+    SearxNG,
+    Brave,
+    Bing,
+    /// Canned results from a local JSON file, for offline runs
+    Fixture,
+}
+
+impl SearchBackend {
+    pub fn all() -> Vec<SearchBackend> {
+        vec![
+            SearchBackend::DuckDuckGo,
+            SearchBackend::SearxNG,
+            SearchBackend::Brave,
+            SearchBackend::Bing,
+            SearchBackend::Fixture,
+        ]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SearchBackend::DuckDuckGo => "DuckDuckGo",
+            SearchBackend::SearxNG => "SearxNG",
+            SearchBackend::Brave => "Brave Search API",
+            SearchBackend::Bing => "Bing Web Search API",
+            SearchBackend::Fixture => "Local fixture",
+        }
+    }
+
+    pub fn needs_api_key(&self) -> bool {
+        matches!(self, SearchBackend::Brave | SearchBackend::Bing)
+    }
+
+    /// What the endpoint setting means for this backend, if it uses one
+    pub fn endpoint_hint(&self) -> Option<&'static str> {
+        match self {
+            SearchBackend::SearxNG => Some("https://searx.example.org"),
+            SearchBackend::Bing => Some("https://api.bing.microsoft.com/v7.0/search (default)"),
+            SearchBackend::Fixture => Some("/path/to/search-fixture.json"),
+            SearchBackend::DuckDuckGo | SearchBackend::Brave => None,
+        }
+    }
+}
+
+/// Which search backend the agent uses and how to reach it
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct SearchConfig {
+    pub backend: SearchBackend,
+    /// Instance URL (SearxNG, Bing) or fixture file path
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub api_key: String,
+}
+
+/// Per-run budget for the agent, checked against the usage the model's
+/// provider reports. Once a run reaches any limit the model gets one last
+/// turn, without tool calls, to give its answer; zero means no limit.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RunLimits {
+    /// Most requests to the model, not counting the last turn to answer
+    pub max_iterations: usize,
+    pub max_tool_calls: usize,
+    /// Most input plus output tokens
+    pub max_tokens: u64,
+    /// Most spend in US dollars
+    pub max_cost: f64,
+    /// Model prices in US dollars per million tokens, used to price usage
+    pub input_price: f64,
+    pub output_price: f64,
+}
+
+impl Default for RunLimits {
+    fn default() -> Self {
+        Self {
+            max_iterations: 30,
+            max_tool_calls: 80,
+            max_tokens: 2_000_000,
+            max_cost: 0.0,
+            input_price: 0.0,
+            output_price: 0.0,
+        }
+    }
+}
+
+impl RunLimits {
+    /// Cost in US dollars of the given token counts
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_price + output_tokens as f64 * self.output_price) / 1_000_000.0
+    }
+}
+
 /// Configuration for the AI agent
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct AgentConfig {
@@ -107,6 +1380,17 @@ pub struct AgentConfig {
     pub api_key: String,
     pub system_prompt: String,
     pub research_topic: String,
+    #[serde(default)]
+    pub search: SearchConfig,
+    /// Local folder searched by the search_documents tool; empty disables it
+    #[serde(default)]
+    pub documents_dir: String,
+    /// Treat `research_topic` as a follow-up instruction that continues the
+    /// board's conversation instead of starting a fresh run
+    #[serde(default)]
+    pub follow_up: bool,
+    #[serde(default)]
+    pub limits: RunLimits,
 }
 
 impl Default for AgentConfig {
@@ -117,6 +1401,218 @@ impl Default for AgentConfig {
             api_key: String::new(),
             system_prompt: "You are a research agent. Use the tools available to research the topic and visualize the findings on the board.".to_string(),
             research_topic: String::new(),
+            search: SearchConfig::default(),
+            documents_dir: String::new(),
+            follow_up: false,
+            limits: RunLimits::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64) -> Shape {
+        Shape::new(ShapeType::Rectangle, x, y, 100.0, 60.0, "#fff".to_string())
+    }
+
+    /// Apply `action`, undo it and redo it the way the client state does,
+    /// checking the board after each step. Returns the board after redo.
+    fn undo_redo(board: &Board, action: Action) -> Board {
+        let undo = board.inverse(&action);
+        let mut after = board.clone();
+        after.apply(&action);
+
+        let redo = after.inverse(&undo);
+        let mut undone = after.clone();
+        undone.apply(&undo);
+        assert_eq!(&undone, board, "undo of {:?}", action);
+
+        let mut redone = undone;
+        redone.apply(&redo);
+        assert_eq!(redone, after, "redo of {:?}", action);
+        redone
+    }
+
+    /// Two boxes joined by a bound arrow, in a frame, with a layer
+    fn sample_board() -> (Board, [Uuid; 3]) {
+        let (a, b) = (rect(0.0, 0.0), rect(300.0, 0.0));
+        let arrow = Shape::connector(&a, &b, "#000".to_string());
+        let ids = [a.id, b.id, arrow.id];
+        let mut board = Board::default();
+        for shape in [a, b, arrow] {
+            board.apply(&Action::Draw(shape));
+        }
+        let bounds = board.members_bounds(&ids[..2]).unwrap().padded(20.0);
+        board.apply(&Action::CreateGroup(Group::frame("Flow".to_string(), ids[..2].to_vec(), bounds)));
+        board.apply(&Action::PutLayer(Layer::new("Notes".to_string())));
+        (board, ids)
+    }
+
+    #[test]
+    fn a_batch_is_undone_and_redone_as_one_step() {
+        let (board, [a, _, _]) = sample_board();
+        let c = rect(600.0, 200.0);
+        let mut moved_c = c.clone();
+        moved_c.label = Some("Storage".to_string());
+        let batch = Action::Batch(vec![
+            Action::Draw(c.clone()),
+            // Later steps depend on the earlier ones in the same batch
+            Action::UpdateShape(moved_c),
+            Action::MoveShapes(vec![(c.id, 700.0, 250.0), (a, -50.0, 40.0)]),
+            Action::CreateGroup(Group::plain(vec![a, c.id])),
+            Action::SetNotes("Findings".to_string()),
+        ]);
+        let after = undo_redo(&board, batch);
+        let c = after.shape(c.id).unwrap();
+        assert_eq!((c.x, c.y, c.label.as_deref()), (700.0, 250.0, Some("Storage")));
+        assert_eq!(after.groups.len(), 2);
+
+        // The inverse of a batch undoes its steps last to first
+        let Action::Batch(steps) = board.inverse(&Action::Batch(vec![
+            Action::Draw(rect(0.0, 0.0)),
+            Action::SetNotes("x".to_string()),
+        ])) else {
+            panic!("a batch inverts to a batch");
+        };
+        assert!(matches!(steps.as_slice(), [Action::SetNotes(_), Action::Delete(_)]));
+    }
+
+    #[test]
+    fn moving_shapes_is_undone_with_their_arrows() {
+        let (board, [a, b, arrow]) = sample_board();
+        let after = undo_redo(&board, Action::MoveShapes(vec![(b, 300.0, 400.0)]));
+        // The bound arrow followed the move and came back with the undo
+        assert_ne!(after.shape(arrow).unwrap(), board.shape(arrow).unwrap());
+        assert_eq!(after.shape(a).unwrap(), board.shape(a).unwrap());
+        // Unknown shapes are left out of the inverse
+        let inverse = board.inverse(&Action::MoveShapes(vec![(b, 1.0, 2.0), (Uuid::new_v4(), 3.0, 4.0)]));
+        assert!(matches!(inverse, Action::MoveShapes(moves) if moves == vec![(b, 300.0, 0.0)]));
+    }
+
+    #[test]
+    fn stacking_changes_restore_the_previous_order() {
+        let (board, [a, b, arrow]) = sample_board();
+        let after = undo_redo(&board, Action::Reorder { ids: vec![a], op: ZOrder::BringToFront });
+        assert_eq!(after.shapes.iter().map(|s| s.id).collect::<Vec<_>>(), vec![b, arrow, a]);
+        let after = undo_redo(&board, Action::SetOrder(vec![arrow, b]));
+        assert_eq!(after.shapes.iter().map(|s| s.id).collect::<Vec<_>>(), vec![arrow, b, a]);
+    }
+
+    #[test]
+    fn deleting_restores_shapes_groups_and_order() {
+        let (board, [a, _, _]) = sample_board();
+        let after = undo_redo(&board, Action::Delete(vec![a]));
+        assert!(after.shape(a).is_none());
+        assert_eq!(after.groups[0].members.len(), 1);
+    }
+
+    #[test]
+    fn other_actions_round_trip() {
+        let (board, [a, b, _]) = sample_board();
+        let frame = board.groups[0].id;
+        let layer = board.layers[0].clone();
+        let mut hidden = layer.clone();
+        hidden.visible = false;
+        let mut relabeled = board.shape(a).unwrap().clone();
+        relabeled.label = Some("Panels".to_string());
+        for action in [
+            Action::TransformGroup {
+                id: frame,
+                bounds: Bounds { min_x: 100.0, min_y: 100.0, max_x: 540.0, max_y: 200.0 },
+            },
+            Action::RenameGroup { id: frame, title: "Supply".to_string() },
+            Action::Ungroup(frame),
+            Action::PutLayer(hidden),
+            Action::PutLayer(Layer::new("New".to_string())),
+            Action::AssignLayer { ids: vec![a, b], layer: Some(layer.id) },
+            Action::UpdateShape(relabeled),
+            Action::Wipe,
+        ] {
+            undo_redo(&board, action);
+        }
+
+        // Resizing scales the members there and back, so only up to rounding
+        let resize = Action::TransformGroup {
+            id: frame,
+            bounds: Bounds { min_x: 100.0, min_y: 100.0, max_x: 600.0, max_y: 300.0 },
+        };
+        let mut resized = board.clone();
+        let undo = resized.inverse(&resize);
+        resized.apply(&resize);
+        assert_eq!(resized.shape(b).unwrap().width, 500.0 / 440.0 * 100.0);
+        resized.apply(&undo);
+        for (shape, original) in resized.shapes.iter().zip(&board.shapes) {
+            assert!((shape.x - original.x).abs() < 1e-9 && (shape.width - original.width).abs() < 1e-9);
+        }
+
+        let mut on_layer = board.clone();
+        on_layer.apply(&Action::AssignLayer { ids: vec![b], layer: Some(layer.id) });
+        undo_redo(&on_layer, Action::RemoveLayer(layer.id));
+    }
+
+    fn chart(kind: ChartKind, categories: &[&str], series: &[(&str, &[f64])]) -> ChartData {
+        ChartData {
+            kind,
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            series: series
+                .iter()
+                .map(|(name, values)| Series {
+                    name: name.to_string(),
+                    values: values.to_vec(),
+                    color: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn chart_csv_round_trips_quoted_fields() {
+        let data = chart(
+            ChartKind::Bar,
+            &["Paris, France", "The \"Big\" Apple", "Tokyo"],
+            &[("Population, m", &[2.1, 8.3, 14.0]), ("Area", &[105.4, -1.5, 2194.0])],
+        );
+        let csv = data.to_csv();
+        assert_eq!(
+            csv,
+            "category,\"Population, m\",Area\n\"Paris, France\",2.1,105.4\n\"The \"\"Big\"\" Apple\",8.3,-1.5\nTokyo,14,2194"
+        );
+        assert_eq!(ChartData::from_csv(ChartKind::Bar, &csv, None).unwrap(), data);
+    }
+
+    #[test]
+    fn chart_csv_keeps_colors_and_fills_blank_cells() {
+        let mut previous = chart(ChartKind::Line, &["a"], &[("Sales", &[1.0])]);
+        previous.series[0].color = Some("#123456".to_string());
+        let data = ChartData::from_csv(ChartKind::Line, "category,Sales,Costs\n\nQ1,5\nQ2, 7 ,2", Some(&previous)).unwrap();
+        assert_eq!(data.categories, vec!["Q1", "Q2"]);
+        assert_eq!(data.series[0].color.as_deref(), Some("#123456"));
+        assert_eq!(data.series[0].values, vec![5.0, 7.0]);
+        assert_eq!(data.series[1].values, vec![0.0, 2.0]);
+        assert_eq!(data.series[1].color, None);
+    }
+
+    #[test]
+    fn chart_csv_reports_the_row_of_a_bad_cell() {
+        let err = ChartData::from_csv(ChartKind::Bar, "category,Sales\nQ1,5\nQ2,lots", None).unwrap_err();
+        assert_eq!(err, "Row 3: 'lots' is not a number");
+        assert_eq!(ChartData::from_csv(ChartKind::Bar, " \n", None).unwrap_err(), "The table is empty");
+        assert!(ChartData::from_csv(ChartKind::Bar, "category,Sales", None).is_err());
+    }
+
+    #[test]
+    fn chart_validation_rejects_non_finite_values_and_empty_pies() {
+        for cell in ["NaN", "inf", "-inf"] {
+            let err = ChartData::from_csv(ChartKind::Bar, &format!("category,Sales\nQ1,{}", cell), None).unwrap_err();
+            assert_eq!(err, "Series 'Sales' has a value that is not a number");
         }
+        let pie = chart(ChartKind::Pie, &["a", "b"], &[("Share", &[0.0, 0.0])]);
+        assert_eq!(pie.validate().unwrap_err(), "A pie chart needs positive values");
+        assert!(chart(ChartKind::Pie, &["a", "b"], &[("Share", &[0.0, 3.0])]).validate().is_ok());
+        // Only the first series of a pie chart counts
+        assert!(chart(ChartKind::Bar, &["a", "b"], &[("Share", &[0.0, 0.0])]).validate().is_ok());
+        assert!(chart(ChartKind::Bar, &["a", "b"], &[("Short", &[1.0])]).validate().is_err());
     }
 }
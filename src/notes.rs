@@ -0,0 +1,191 @@
+//! Research notes: the Markdown write-up the agent keeps beside each board.
+//!
+//! Every research run appends a section with the agent's answer, the findings
+//! it recorded along the way and the sources it read. Users edit the result
+//! freely; it is stored on the board and exported with it.
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+#[cfg(feature = "server")]
+use crate::model::Source;
+#[cfg(feature = "server")]
+use serde::{Deserialize, Serialize};
+
+/// What the agent gathered during one research run
+#[cfg(feature = "server")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResearchRun {
+    pub topic: String,
+    /// The agent's final answer
+    pub answer: String,
+    /// Short summaries the agent wrote while working, in order
+    pub findings: Vec<String>,
+    /// Pages read or cited during the run, without repeats
+    pub sources: Vec<Source>,
+}
+
+#[cfg(feature = "server")]
+impl ResearchRun {
+    pub fn new(topic: &str) -> Self {
+        Self {
+            topic: topic.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Add a source unless one with the same URL is already listed; a later
+    /// copy fills in a missing title
+    pub fn add_source(&mut self, source: Source) {
+        match self.sources.iter_mut().find(|s| s.url == source.url) {
+            Some(existing) if existing.title.is_none() => existing.title = source.title,
+            Some(_) => {}
+            None => self.sources.push(source),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answer.trim().is_empty() && self.findings.is_empty() && self.sources.is_empty()
+    }
+
+    /// The run as a Markdown section headed by its topic
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## {}\n\n", single_line(&self.topic));
+        if !self.answer.trim().is_empty() {
+            out.push_str(self.answer.trim());
+            out.push_str("\n\n");
+        }
+        if !self.findings.is_empty() {
+            out.push_str("### Findings\n\n");
+            for finding in &self.findings {
+                out.push_str(&format!("- {}\n", single_line(finding)));
+            }
+            out.push('\n');
+        }
+        if !self.sources.is_empty() {
+            out.push_str("### Sources\n\n");
+            for (i, source) in self.sources.iter().enumerate() {
+                out.push_str(&format!(
+                    "{}. [{}]({})\n",
+                    i + 1,
+                    single_line(source.display_title()).replace(['[', ']'], ""),
+                    source.url.replace(' ', "%20").replace(')', "%29"),
+                ));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Append a run's section to existing notes
+#[cfg(feature = "server")]
+pub fn append_run(notes: &str, run: &ResearchRun) -> String {
+    let notes = notes.trim_end();
+    if notes.is_empty() {
+        run.to_markdown()
+    } else {
+        format!("{}\n\n{}", notes, run.to_markdown())
+    }
+}
+
+#[cfg(feature = "server")]
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether a link target is safe to follow from the rendered notes
+fn is_safe_link(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    ["http://", "https://", "mailto:", "#"].iter().any(|p| lower.starts_with(p))
+}
+
+/// Render notes Markdown to HTML. Raw HTML in the notes is shown as text and
+/// links other than web and mail links are disabled, so pasted or generated
+/// markup can't run in the page.
+pub fn render_markdown(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) if !is_safe_link(&dest_url) => {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url: CowStr::Borrowed("#"),
+                title,
+                id,
+            })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) if !is_safe_link(&dest_url) => {
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url: CowStr::Borrowed(""),
+                title,
+                id,
+            })
+        }
+        event => event,
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, events);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "server")]
+    fn source(url: &str, title: Option<&str>) -> Source {
+        Source {
+            url: url.to_string(),
+            title: title.map(str::to_string),
+            quote: None,
+        }
+    }
+
+    #[test]
+    fn raw_html_is_rendered_as_text() {
+        let html = render_markdown("<script>alert(1)</script>\n\nHello <img src=x onerror=alert(1)> world");
+        assert!(!html.contains("<script"), "{}", html);
+        assert!(!html.contains("<img"), "{}", html);
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"), "{}", html);
+        assert!(html.contains("Hello &lt;img src=x onerror=alert(1)&gt; world"), "{}", html);
+    }
+
+    #[test]
+    fn unsafe_link_and_image_targets_are_neutralised() {
+        let html = render_markdown("[click](javascript:alert(1)) ![pic](JavaScript:alert(2)) [data](data:text/html,x)");
+        assert!(!html.to_lowercase().contains("javascript:"), "{}", html);
+        assert!(!html.contains("data:"), "{}", html);
+        assert!(html.contains(r##"<a href="#">click</a>"##), "{}", html);
+        assert!(html.contains(r#"<img src="" alt="pic" />"#), "{}", html);
+
+        let html = render_markdown("[site](https://example.com/a) [mail](mailto:a@example.com) [top](#notes)");
+        assert!(html.contains(r#"href="https://example.com/a""#), "{}", html);
+        assert!(html.contains(r#"href="mailto:a@example.com""#), "{}", html);
+        assert!(html.contains(r##"href="#notes""##), "{}", html);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn add_source_skips_repeats_and_fills_missing_titles() {
+        let mut run = ResearchRun::new("Solar");
+        run.add_source(source("https://a.example", None));
+        run.add_source(source("https://b.example", Some("B")));
+        run.add_source(source("https://a.example", Some("A")));
+        run.add_source(source("https://b.example", Some("Other B")));
+        assert_eq!(run.sources, vec![source("https://a.example", Some("A")), source("https://b.example", Some("B"))]);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn append_run_adds_a_section() {
+        let mut run = ResearchRun::new("Solar\npanels");
+        assert!(run.is_empty());
+        run.answer = "They work.".to_string();
+        run.findings.push("Cheap\nnow".to_string());
+        run.add_source(source("https://a.example/x y", Some("[A] site")));
+
+        let section = "## Solar panels\n\nThey work.\n\n### Findings\n\n- Cheap now\n\n### Sources\n\n1. [A site](https://a.example/x%20y)\n\n";
+        assert_eq!(append_run("", &run), section);
+        assert_eq!(append_run("# My notes\n\n\n", &run), format!("# My notes\n\n{}", section));
+    }
+}
@@ -1,7 +1,18 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 use tokio::sync::broadcast;
 use once_cell::sync::Lazy;
-use crate::model::{Action, AgentConfig};
+use uuid::Uuid;
+use crate::model::{Action, AgentConfig, Board, ConversationTurn};
+use crate::notes::ResearchRun;
+use crate::transcript::Transcript;
+
+/// An action in the shared history. `origin` is the client that submitted it
+/// (`None` for the agent) so clients can skip their own echoes when polling.
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub origin: Option<Uuid>,
+    pub action: Action,
+}
 
 // Global broadcast channel for agent actions
 pub static AGENT_CHANNEL: Lazy<broadcast::Sender<Action>> = Lazy::new(|| {
@@ -10,11 +21,58 @@ pub static AGENT_CHANNEL: Lazy<broadcast::Sender<Action>> = Lazy::new(|| {
 });
 
 // Global history for reliable polling
-pub static AGENT_HISTORY: Lazy<Mutex<Vec<Action>>> = Lazy::new(|| {
+pub static AGENT_HISTORY: Lazy<Mutex<Vec<HistoryEntry>>> = Lazy::new(|| {
     Mutex::new(Vec::new())
 });
 
+// Server-side mirror of the board, reduced from AGENT_HISTORY.
+// The viewport is pushed here by the client so the agent knows what is visible.
+pub static AGENT_BOARD: Lazy<Mutex<Board>> = Lazy::new(|| {
+    Mutex::new(Board::default())
+});
+
 // Global agent configuration
 pub static AGENT_CONFIG: Lazy<Mutex<AgentConfig>> = Lazy::new(|| {
     Mutex::new(AgentConfig::default())
 });
+
+// What the current research run has gathered for the notes; reset at the start of each run
+pub static RESEARCH_RUN: Lazy<Mutex<ResearchRun>> = Lazy::new(|| {
+    Mutex::new(ResearchRun::default())
+});
+
+// Transcript of the research run in progress, saved to disk when the run ends
+pub static CURRENT_TRANSCRIPT: Lazy<Mutex<Option<Transcript>>> = Lazy::new(|| {
+    Mutex::new(None)
+});
+
+// Instructions and answers exchanged with the agent about the current board;
+// follow-up runs continue from them, a fresh run or a new board starts over
+pub static CONVERSATION: Lazy<Mutex<Vec<ConversationTurn>>> = Lazy::new(|| {
+    Mutex::new(Vec::new())
+});
+
+/// Record an agent action: append to history, apply to the server board and broadcast.
+pub fn record_action(action: Action) {
+    record(None, action);
+}
+
+/// Record an action a user performed in the given client
+pub fn record_client_action(client_id: Uuid, action: Action) {
+    record(Some(client_id), action);
+}
+
+fn record(origin: Option<Uuid>, action: Action) {
+    if let Ok(mut history) = AGENT_HISTORY.lock() {
+        history.push(HistoryEntry { origin, action: action.clone() });
+    }
+    if let Ok(mut board) = AGENT_BOARD.lock() {
+        board.apply(&action);
+    }
+    if matches!(action, Action::NewBoard) {
+        if let Ok(mut conversation) = CONVERSATION.lock() {
+            conversation.clear();
+        }
+    }
+    let _ = AGENT_CHANNEL.send(action);
+}
@@ -1,10 +1,31 @@
-use crate::model::{Action, Board};
+use crate::agent::submit_action;
+use crate::clipboard::{self, PASTE_OFFSET};
+use crate::layout::{self, Direction, LayoutKind};
+use crate::model::{Action, Board, Shape};
 use dioxus::prelude::*;
+use uuid::Uuid;
+
+/// How many steps can be undone
+const UNDO_LIMIT: usize = 100;
 
 #[derive(Clone, Copy)]
 pub struct BoardState {
     pub board: Signal<Board>,
     pub history: Signal<Vec<Action>>,
+    /// Identifies this client so the server can filter out our own actions when polling
+    pub client_id: Uuid,
+    /// Server history index we have applied up to
+    pub last_index: Signal<usize>,
+    /// Currently selected shapes
+    pub selection: Signal<Vec<Uuid>>,
+    /// Currently selected group or frame
+    pub selected_group: Signal<Option<Uuid>>,
+    /// Last copied payload, used when the system clipboard is unavailable
+    pub clipboard: Signal<Option<String>>,
+    /// Inverses of applied actions (user's and agent's), most recent last
+    pub undo_stack: Signal<Vec<Action>>,
+    /// Inverses of undone actions, for redo
+    pub redo_stack: Signal<Vec<Action>>,
 }
 
 impl BoardState {
@@ -12,22 +33,170 @@ impl BoardState {
         Self {
             board: Signal::new(Board::default()),
             history: Signal::new(Vec::new()),
+            client_id: Uuid::new_v4(),
+            last_index: Signal::new(0),
+            selection: Signal::new(Vec::new()),
+            selected_group: Signal::new(None),
+            clipboard: Signal::new(None),
+            undo_stack: Signal::new(Vec::new()),
+            redo_stack: Signal::new(Vec::new()),
         }
     }
 
+    /// Apply an action (from this user or the agent), making it undoable
     pub fn apply_action(&mut self, action: Action) {
+        let inverse = self.board.read().inverse(&action);
+        push_bounded(&mut self.undo_stack.write(), inverse);
+        self.redo_stack.write().clear();
+        self.history.write().push(action.clone());
+        self.board.write().apply(&action);
+    }
+
+    /// Apply a user action locally and record it on the server so the agent sees it too
+    pub fn dispatch(&mut self, action: Action) {
+        self.apply_action(action.clone());
+        self.submit(action);
+    }
+
+    fn submit(&self, action: Action) {
+        let client_id = self.client_id;
+        spawn(async move {
+            let _ = submit_action(client_id, action).await;
+        });
+    }
+
+    /// Revert the most recent action; a batch (e.g. a whole mind map) is one step
+    pub fn undo(&mut self) {
+        let Some(action) = self.undo_stack.write().pop() else { return };
+        let inverse = self.board.read().inverse(&action);
+        push_bounded(&mut self.redo_stack.write(), inverse);
+        self.history.write().push(action.clone());
+        self.board.write().apply(&action);
+        self.submit(action);
+        self.clear_selection();
+    }
+
+    pub fn redo(&mut self) {
+        let Some(action) = self.redo_stack.write().pop() else { return };
+        let inverse = self.board.read().inverse(&action);
+        push_bounded(&mut self.undo_stack.write(), inverse);
         self.history.write().push(action.clone());
-        match action {
-            Action::Draw(shape) => {
-                self.board.write().shapes.push(shape);
-            }
-            Action::Wipe => {
-                self.board.write().shapes.clear();
-            }
-            Action::NewBoard => {
-                self.board.write().shapes.clear();
-                // Logic for new board ID could go here if we tracked multiple boards
-            }
+        self.board.write().apply(&action);
+        self.submit(action);
+        self.clear_selection();
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.write().clear();
+        self.selected_group.set(None);
+    }
+
+    /// Selected shapes in stacking order
+    fn selected_shapes(&self) -> Vec<Shape> {
+        let selection = self.selection.read();
+        self.board
+            .read()
+            .shapes
+            .iter()
+            .filter(|s| selection.contains(&s.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Copy the selection to the system clipboard (and the in-app fallback)
+    pub fn copy_selection(&mut self) {
+        let shapes = self.selected_shapes();
+        if shapes.is_empty() {
+            return;
         }
+        let text = clipboard::to_clipboard_text(&shapes);
+        self.clipboard.set(Some(text.clone()));
+        spawn(async move {
+            clipboard::write_system_clipboard(text).await;
+        });
+    }
+
+    pub fn cut_selection(&mut self) {
+        self.copy_selection();
+        self.delete_selection();
+    }
+
+    pub fn delete_selection(&mut self) {
+        let ids = self.selection.read().clone();
+        if ids.is_empty() {
+            return;
+        }
+        self.dispatch(Action::Delete(ids));
+        self.clear_selection();
+    }
+
+    /// Paste from the system clipboard, falling back to the last in-app copy
+    pub fn paste(&mut self) {
+        let mut state = *self;
+        spawn(async move {
+            let text = match clipboard::read_system_clipboard().await {
+                Some(text) if !text.trim().is_empty() => text,
+                _ => match state.clipboard.read().clone() {
+                    Some(text) => text,
+                    None => return,
+                },
+            };
+            state.paste_text(&text);
+        });
+    }
+
+    /// Insert clipboard text (shapes or plain text) and select the result
+    pub fn paste_text(&mut self, text: &str) {
+        let view = self.board.read().viewport.bounds();
+        let shapes = clipboard::parse_clipboard_text(text, view.min_x + 40.0, view.min_y + 40.0);
+        let shapes = clipboard::prepare_paste(&self.board.read(), &shapes, PASTE_OFFSET);
+        self.insert_shapes(shapes);
+    }
+
+    pub fn duplicate_selection(&mut self) {
+        let shapes = self.selected_shapes();
+        let shapes = clipboard::prepare_paste(&self.board.read(), &shapes, PASTE_OFFSET);
+        self.insert_shapes(shapes);
+    }
+
+    /// Lay out the selection, or the whole board when nothing is selected
+    pub fn arrange_selection(&mut self, kind: LayoutKind) {
+        let ids = self.selection.read().clone();
+        let actions = layout::arrange(&self.board.read(), &ids, kind, Direction::TopDown);
+        if !actions.is_empty() {
+            self.dispatch(Action::Batch(actions));
+        }
+    }
+
+    fn insert_shapes(&mut self, shapes: Vec<Shape>) {
+        if shapes.is_empty() {
+            return;
+        }
+        let ids = shapes.iter().map(|s| s.id).collect();
+        self.dispatch(Action::Batch(shapes.into_iter().map(Action::Draw).collect()));
+        self.selection.set(ids);
+        self.selected_group.set(None);
+    }
+
+    /// Add everything from another board (an import) to this one and select it
+    pub fn import_board(&mut self, mut imported: Board) {
+        imported.reassign_ids();
+        let ids = imported.shapes.iter().map(|s| s.id).collect();
+        let actions = imported
+            .shapes
+            .into_iter()
+            .map(Action::Draw)
+            .chain(imported.groups.into_iter().map(Action::CreateGroup))
+            .collect();
+        self.dispatch(Action::Batch(actions));
+        self.selection.set(ids);
+        self.selected_group.set(None);
+    }
+}
+
+fn push_bounded(stack: &mut Vec<Action>, action: Action) {
+    stack.push(action);
+    if stack.len() > UNDO_LIMIT {
+        stack.remove(0);
     }
 }
@@ -1,16 +1,25 @@
 use radkit::tools::{FunctionTool, ToolResult};
 use serde::Deserialize;
-use crate::model::{Action, Shape, ShapeType};
-use crate::server_state::{AGENT_CHANNEL, AGENT_HISTORY};
+use crate::formats::diagram::{self, DiagramFormat};
+use crate::model::{
+    Action, Board, Bounds, ChartData, ChartKind, Group, Layer, Series, Shape, ShapeType, Source, Viewport,
+    ZOrder,
+};
+use crate::layout::{self, Direction, LayoutKind};
+use crate::server_state::{record_action, AGENT_BOARD};
+use crate::tools::trace::traced_tool;
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct DrawShapeArgs {
-    /// Type of shape: "rectangle", "circle", or "line"
+    /// Type of shape: "rectangle", "circle", "ellipse", "line", "diamond",
+    /// "polygon", "freehand", "sticky_note", "image", "text" or "arrow"
     shape_type: String,
-    /// X position (0-800)
+    /// X position in world coordinates (unbounded, may be negative)
     x: Option<f64>,
-    /// Y position (0-500)
+    /// Y position in world coordinates (unbounded, may be negative)
     y: Option<f64>,
     /// Width of shape
     width: Option<f64>,
@@ -20,13 +29,25 @@ pub struct DrawShapeArgs {
     color: String,
     /// Label for this shape (what concept it represents)
     label: Option<String>,
+    /// World-coordinate points for "polygon" (3+) and "freehand" (2+), as [[x, y], ...]
+    points: Option<Vec<[f64; 2]>>,
+    /// Image URL or data URI for "image"
+    image_url: Option<String>,
+    /// For "arrow": ids of the shapes to connect; the arrow stays attached when they move
+    from_shape_id: Option<String>,
+    to_shape_id: Option<String>,
+    /// References backing the shape: [{"url": ..., "title": ..., "quote": ...}]
+    sources: Option<Vec<Source>>,
 }
 
+/// Most sources one shape can cite
+const MAX_SOURCES: usize = 8;
+
 pub fn make_draw_tool() -> FunctionTool {
-    FunctionTool::new(
+    traced_tool(
         "draw_shape",
-        "Draw a labeled shape on the presentation canvas. Use this to create visual diagrams. Each shape can represent a concept, fact, or category from your research.",
-        |args, _ctx| Box::pin(async move {
+        "Draw a labeled shape on the presentation canvas. Supported shape_type values: rectangle, circle, ellipse, line, diamond (flowchart decisions), polygon and freehand (pass points as [[x, y], ...]), sticky_note (label is shown as note text), text (free-standing label with no outline), image (pass image_url) and arrow (pass from_shape_id and to_shape_id to connect two drawn shapes, or x/y/width/height for a free arrow). The canvas is an infinite world; x/y are world coordinates and can be any value. Prefer placing shapes inside the currently visible region given in your instructions. Each shape can represent a concept, fact, or category from your research; pass sources as [{\"url\", \"title\", \"quote\"}] to cite the pages that back it, and they get a numbered citation marker and a bibliography entry in exports.",
+        |args| Box::pin(async move {
             let args_value = serde_json::Value::Object(args.into_iter().collect());
 
             let args: DrawShapeArgs = match serde_json::from_value(args_value) {
@@ -34,60 +55,1686 @@ pub fn make_draw_tool() -> FunctionTool {
                 Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
             };
 
-            let shape_type = match args.shape_type.to_lowercase().as_str() {
-                "circle" => ShapeType::Circle,
-                "line" => ShapeType::Line,
-                _ => ShapeType::Rectangle,
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
             };
+            match build_shape(&args, &board) {
+                Ok((shape, kind)) => draw(shape, &kind),
+                Err(e) => ToolResult::error(e),
+            }
+        })
+    )
+}
 
-            // Auto-position based on history length if not specified
-            let history_len = AGENT_HISTORY.lock().map(|h| h.len()).unwrap_or(0);
-            let row = history_len / 4;
-            let col = history_len % 4;
-            
-            let x = args.x.unwrap_or(80.0 + (col as f64) * 180.0);
-            let y = args.y.unwrap_or(60.0 + (row as f64) * 130.0);
-            let width = args.width.unwrap_or(150.0);
-            let height = args.height.unwrap_or(100.0);
-
-            let shape = Shape::new(shape_type, x, y, width, height, args.color.clone());
-            let label = args.label.clone().unwrap_or_default();
-            let action = Action::Draw(shape);
-            
-            println!("Tool: Drawing {} '{}' at ({}, {})", args.shape_type, label, x, y);
+/// Build the shape described by draw_shape arguments; arrows connect to
+/// shapes on `board` and unplaced shapes go in its first free spot
+fn build_shape(args: &DrawShapeArgs, board: &Board) -> Result<(Shape, String), String> {
+    let sources = clean_sources(args.sources.as_deref().unwrap_or_default())?;
+    let (mut shape, kind) = shape_from_args(args, board)?;
+    shape.sources = sources;
+    Ok((shape, kind))
+}
 
-            // Send to channel AND history
-            if let Ok(mut history) = AGENT_HISTORY.lock() {
-                history.push(action.clone());
+/// Trim cited sources and check they are web links, dropping repeats
+fn clean_sources(sources: &[Source]) -> Result<Vec<Source>, String> {
+    if sources.len() > MAX_SOURCES {
+        return Err(format!("A shape can cite at most {} sources", MAX_SOURCES));
+    }
+    let mut cleaned: Vec<Source> = Vec::new();
+    for source in sources {
+        let url = source.url.trim();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(format!("Source url '{}' must be an http(s) link", url));
+        }
+        if cleaned.iter().any(|s| s.url == url) {
+            continue;
+        }
+        let text = |value: &Option<String>| {
+            value.as_deref().map(str::trim).filter(|t| !t.is_empty()).map(str::to_string)
+        };
+        cleaned.push(Source {
+            url: url.to_string(),
+            title: text(&source.title),
+            quote: text(&source.quote),
+        });
+    }
+    Ok(cleaned)
+}
+
+fn shape_from_args(args: &DrawShapeArgs, board: &Board) -> Result<(Shape, String), String> {
+    let kind = args.shape_type.to_lowercase().replace([' ', '-'], "_");
+    let shape_type = match kind.as_str() {
+        "rectangle" | "rect" => ShapeType::Rectangle,
+        "circle" => ShapeType::Circle,
+        "ellipse" | "oval" => ShapeType::Ellipse,
+        "line" => ShapeType::Line,
+        "diamond" => ShapeType::Diamond,
+        "sticky_note" | "sticky" | "note" => ShapeType::StickyNote,
+        "text" => match &args.label {
+            Some(label) if !label.trim().is_empty() => ShapeType::Text,
+            _ => return Err("shape_type 'text' requires a label".to_string()),
+        },
+        "image" => match &args.image_url {
+            Some(href) if !href.trim().is_empty() => ShapeType::Image { href: href.clone() },
+            _ => return Err("shape_type 'image' requires an image_url".to_string()),
+        },
+        "arrow" | "connector" if args.from_shape_id.is_some() || args.to_shape_id.is_some() => {
+            let (Some(from), Some(to)) = (&args.from_shape_id, &args.to_shape_id) else {
+                return Err("Connecting arrows need both from_shape_id and to_shape_id".to_string());
+            };
+            let find = |raw: &String| raw.parse::<Uuid>().ok().and_then(|id| board.shape(id));
+            let (Some(from_shape), Some(to_shape)) = (find(from), find(to)) else {
+                let unknown: Vec<&str> = [from, to]
+                    .into_iter()
+                    .filter(|raw| find(raw).is_none())
+                    .map(String::as_str)
+                    .collect();
+                return Err(format!("Unknown shape ids: {}", unknown.join(", ")));
+            };
+            let mut shape = Shape::connector(from_shape, to_shape, args.color.clone());
+            shape.label = args.label.clone();
+            return Ok((shape, "arrow".to_string()));
+        }
+        "arrow" | "connector" => ShapeType::Arrow {
+            start: None,
+            end: None,
+            headless: false,
+        },
+        // Point-based shapes take their geometry from `points` instead of x/y/width/height
+        "polygon" | "freehand" => {
+            let closed = kind == "polygon";
+            let points: Vec<(f64, f64)> = args.points.iter().flatten().map(|p| (p[0], p[1])).collect();
+            let min_points = if closed { 3 } else { 2 };
+            if points.len() < min_points {
+                return Err(format!(
+                    "shape_type '{}' requires at least {} points as [[x, y], ...]",
+                    kind, min_points
+                ));
             }
-            let _ = AGENT_CHANNEL.send(action);
-            
-            ToolResult::success(json!({ 
-                "status": "drawn", 
-                "label": label,
-                "position": { "x": x, "y": y },
-                "shape_id": history_len
+            let mut shape = Shape::from_points(&points, closed, args.color.clone());
+            shape.label = args.label.clone();
+            return Ok((shape, kind));
+        }
+        other => {
+            return Err(format!(
+                "Unknown shape_type '{}'. Expected one of: {}",
+                other,
+                ShapeType::NAMES.join(", ")
+            ))
+        }
+    };
+
+    let (default_width, default_height) = match &shape_type {
+        ShapeType::StickyNote => (160.0, 160.0),
+        ShapeType::Circle => (120.0, 120.0),
+        _ => (150.0, 100.0),
+    };
+    let width = args.width.unwrap_or(default_width);
+    let height = args.height.unwrap_or(default_height);
+
+    // Without a position, take the first free spot in the region the user is looking at
+    let (x, y) = match (args.x, args.y) {
+        (Some(x), Some(y)) => (x, y),
+        (x, y) => {
+            let spot = layout::free_spot(board, &board.viewport.bounds(), (width, height));
+            (x.unwrap_or(spot.0), y.unwrap_or(spot.1))
+        }
+    };
+
+    let mut shape = match shape_type {
+        // Text sizes itself to its content unless told otherwise
+        ShapeType::Text => Shape::text(x, y, args.label.clone().unwrap_or_default()),
+        shape_type => Shape::new(shape_type, x, y, width, height, args.color.clone()),
+    };
+    if let (Some(width), Some(height)) = (args.width, args.height) {
+        shape.width = width;
+        shape.height = height;
+    }
+    shape.label = args.label.clone();
+    Ok((shape, kind))
+}
+
+/// Record a finished shape and build the tool response
+fn draw(shape: Shape, kind: &str) -> ToolResult {
+    let label = shape.label.clone().unwrap_or_default();
+    let (x, y) = (shape.x, shape.y);
+    let shape_id = shape.id;
+
+    println!("Tool: Drawing {} '{}' at ({}, {})", kind, label, x, y);
+
+    record_action(Action::Draw(shape));
+
+    ToolResult::success(json!({
+        "status": "drawn",
+        "label": label,
+        "position": { "x": x, "y": y },
+        "shape_id": shape_id.to_string()
+    }))
+}
+
+/// Upper bound on shapes per draw_shapes call
+const MAX_BATCH_SHAPES: usize = 100;
+
+#[derive(Deserialize)]
+pub struct BatchShapeArgs {
+    /// Name other entries of the same call can use as from_shape_id/to_shape_id
+    key: Option<String>,
+    #[serde(flatten)]
+    shape: DrawShapeArgs,
+}
+
+#[derive(Deserialize)]
+pub struct DrawShapesArgs {
+    /// Shapes in draw_shape format
+    shapes: Vec<BatchShapeArgs>,
+}
+
+pub fn make_draw_shapes_tool() -> FunctionTool {
+    traced_tool(
+        "draw_shapes",
+        "Draw many shapes in one call, e.g. {\"shapes\": [{\"key\": \"a\", \"shape_type\": \"rectangle\", \"color\": \"#dbeafe\", \"label\": \"Input\"}, {\"key\": \"b\", \"shape_type\": \"rectangle\", \"color\": \"#dcfce7\", \"label\": \"Output\"}, {\"shape_type\": \"arrow\", \"color\": \"#475569\", \"from_shape_id\": \"a\", \"to_shape_id\": \"b\"}]}. Each entry takes the same fields as draw_shape plus an optional key; arrows may connect shapes by key from the same call or by shape_id from earlier calls. Either every shape is drawn or, if any entry is invalid, none are. The shapes appear together and undo as a single step, so prefer this over repeated draw_shape calls.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: DrawShapesArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            if args.shapes.is_empty() {
+                return ToolResult::error("No shapes given".to_string());
+            }
+            if args.shapes.len() > MAX_BATCH_SHAPES {
+                return ToolResult::error(format!(
+                    "{} shapes given; the limit is {} per call",
+                    args.shapes.len(),
+                    MAX_BATCH_SHAPES
+                ));
+            }
+            // Shapes are built against a working copy so auto-placed ones don't
+            // pile up and arrows can bind to shapes from the same call
+            let mut board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+
+            let mut keys: HashMap<&str, Uuid> = HashMap::new();
+            let mut built: Vec<Option<Shape>> = args.shapes.iter().map(|_| None).collect();
+            // Connecting arrows go second so they can reference any shape in the list
+            let connects = |entry: &BatchShapeArgs| entry.shape.from_shape_id.is_some() || entry.shape.to_shape_id.is_some();
+            let order = (0..args.shapes.len())
+                .filter(|&i| !connects(&args.shapes[i]))
+                .chain((0..args.shapes.len()).filter(|&i| connects(&args.shapes[i])));
+            for i in order {
+                let entry = &args.shapes[i];
+                let mut shape_args = entry.shape.clone();
+                for end in [&mut shape_args.from_shape_id, &mut shape_args.to_shape_id].into_iter().flatten() {
+                    if let Some(id) = keys.get(end.as_str()) {
+                        *end = id.to_string();
+                    }
+                }
+                let shape = match build_shape(&shape_args, &board) {
+                    Ok((shape, _)) => shape,
+                    Err(e) => {
+                        let name = entry.key.as_deref().map(|k| format!(" ('{}')", k)).unwrap_or_default();
+                        return ToolResult::error(format!("Shape {}{}: {}. Nothing was drawn", i + 1, name, e));
+                    }
+                };
+                if let Some(key) = entry.key.as_deref() {
+                    if keys.insert(key, shape.id).is_some() {
+                        return ToolResult::error(format!("Duplicate key '{}'. Nothing was drawn", key));
+                    }
+                }
+                board.apply(&Action::Draw(shape.clone()));
+                built[i] = Some(shape);
+            }
+            let shapes: Vec<Shape> = built.into_iter().flatten().collect();
+
+            println!("Tool: Drawing {} shapes in one batch", shapes.len());
+            // Connectors first so they are drawn beneath the shapes they join
+            let (connectors, others): (Vec<&Shape>, Vec<&Shape>) = shapes.iter().partition(|s| s.is_connector());
+            let actions = connectors
+                .into_iter()
+                .chain(others)
+                .cloned()
+                .map(Action::Draw)
+                .collect();
+            record_action(Action::Batch(actions));
+
+            ToolResult::success(json!({
+                "status": "drawn",
+                "count": shapes.len(),
+                "shapes": shapes
+                    .iter()
+                    .zip(&args.shapes)
+                    .map(|(shape, entry)| json!({
+                        "key": entry.key,
+                        "label": shape.label,
+                        "shape_id": shape.id.to_string(),
+                        "position": { "x": shape.x, "y": shape.y }
+                    }))
+                    .collect::<Vec<_>>()
+            }))
+        })
+    )
+}
+
+/// Resolve shape id strings against the board, reporting any that don't exist
+fn parse_shape_ids(board: &Board, raw_ids: &[String]) -> Result<Vec<Uuid>, ToolResult> {
+    let mut ids = Vec::new();
+    let mut unknown = Vec::new();
+    for raw in raw_ids {
+        match raw.parse::<Uuid>() {
+            Ok(id) if board.shape(id).is_some() => ids.push(id),
+            _ => unknown.push(raw.clone()),
+        }
+    }
+    if unknown.is_empty() {
+        Ok(ids)
+    } else {
+        Err(ToolResult::error(format!("Unknown shape ids: {}", unknown.join(", "))))
+    }
+}
+
+fn current_board() -> Result<Board, ToolResult> {
+    AGENT_BOARD
+        .lock()
+        .map(|board| board.clone())
+        .map_err(|_| ToolResult::error("Board is unavailable".to_string()))
+}
+
+#[derive(Deserialize)]
+pub struct CreateFrameArgs {
+    /// Section title shown above the frame
+    title: String,
+    /// Ids (from draw_shape) of shapes that belong to this section
+    shape_ids: Option<Vec<String>>,
+    /// Frame geometry; defaults to the members' bounds plus padding
+    x: Option<f64>,
+    y: Option<f64>,
+    width: Option<f64>,
+    height: Option<f64>,
+}
+
+pub fn make_frame_tool() -> FunctionTool {
+    traced_tool(
+        "create_frame",
+        "Create a titled frame that groups shapes into a section of the board (e.g. 'Pros', 'Cons', 'Timeline'). Pass the shape_id values returned by draw_shape; the frame is sized to fit them unless x/y/width/height are given. Members move and resize together with the frame.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: CreateFrameArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+            let members = match parse_shape_ids(&board, args.shape_ids.as_deref().unwrap_or_default()) {
+                Ok(ids) => ids,
+                Err(e) => return e,
+            };
+
+            let fitted = board.members_bounds(&members).map(|b| b.padded(30.0));
+            let bounds = match (args.x, args.y, args.width, args.height, fitted) {
+                (Some(x), Some(y), Some(w), Some(h), _) => Bounds { min_x: x, min_y: y, max_x: x + w, max_y: y + h },
+                (_, _, _, _, Some(fitted)) => fitted,
+                _ => return ToolResult::error(
+                    "Provide shape_ids or a full x/y/width/height for the frame".to_string(),
+                ),
+            };
+
+            let frame = Group::frame(args.title.clone(), members.clone(), bounds);
+            let frame_id = frame.id;
+            println!("Tool: Creating frame '{}' with {} shapes", args.title, members.len());
+            record_action(Action::CreateGroup(frame));
+
+            ToolResult::success(json!({
+                "status": "created",
+                "frame_id": frame_id.to_string(),
+                "title": args.title,
+                "members": members.len(),
+                "bounds": { "x": bounds.min_x, "y": bounds.min_y, "width": bounds.width(), "height": bounds.height() }
+            }))
+        })
+    )
+}
+
+#[derive(Deserialize)]
+pub struct DrawDiagramArgs {
+    /// Mermaid flowchart or Graphviz DOT source
+    source: String,
+    /// "mermaid" or "dot"; detected from the source when omitted
+    format: Option<String>,
+    /// Top-left corner of the diagram; defaults to the top-left of the visible region
+    x: Option<f64>,
+    y: Option<f64>,
+}
+
+pub fn make_diagram_tool() -> FunctionTool {
+    traced_tool(
+        "draw_diagram",
+        "Draw a whole graph from Mermaid flowchart (`flowchart LR; A[Idea] --> B{Choice}`) or Graphviz DOT (`digraph { a -> b [label=\"why\"] }`) source. Nodes are laid out automatically in layers following the edges and connected with arrows that stay attached; Mermaid subgraphs and DOT `subgraph cluster_*` become titled frames. Supports node shapes (rectangle, rounded, circle, diamond, note) and edge labels. Returns the shape_id of every node so you can connect or group them further. Prefer this over many draw_shape calls for flowcharts, dependency graphs and hierarchies.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: DrawDiagramArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let format = match args.format.as_deref().map(DiagramFormat::parse) {
+                Some(None) => return ToolResult::error(format!(
+                    "Unknown format '{}'. Expected 'mermaid' or 'dot'",
+                    args.format.unwrap_or_default()
+                )),
+                Some(format) => format,
+                None => None,
+            };
+            let parsed = match diagram::parse(&args.source, format) {
+                Ok(parsed) => parsed,
+                Err(e) => return ToolResult::error(format!("Could not parse diagram: {}", e)),
+            };
+
+            let viewport = AGENT_BOARD.lock().map(|b| b.viewport).unwrap_or_default();
+            let origin = (args.x.unwrap_or(viewport.x + 40.0), args.y.unwrap_or(viewport.y + 40.0));
+            let (board, nodes) = parsed.to_board(origin);
+
+            println!(
+                "Tool: Drawing diagram with {} nodes and {} edges at ({}, {})",
+                parsed.nodes.len(),
+                parsed.edges.len(),
+                origin.0,
+                origin.1
+            );
+            let actions = board
+                .shapes
+                .iter()
+                .cloned()
+                .map(Action::Draw)
+                .chain(board.groups.iter().cloned().map(Action::CreateGroup))
+                .collect();
+            record_action(Action::Batch(actions));
+
+            let shape_ids: serde_json::Map<String, serde_json::Value> = nodes
+                .iter()
+                .map(|(id, shape)| (id.clone(), json!(shape.id.to_string())))
+                .collect();
+            let bounds = board.members_bounds(&board.shapes.iter().map(|s| s.id).collect::<Vec<_>>());
+            ToolResult::success(json!({
+                "status": "drawn",
+                "nodes": parsed.nodes.len(),
+                "edges": parsed.edges.len(),
+                "frames": board.groups.len(),
+                "shape_ids": shape_ids,
+                "bounds": bounds.map(|b| json!({ "x": b.min_x, "y": b.min_y, "width": b.width(), "height": b.height() }))
+            }))
+        })
+    )
+}
+
+#[derive(Deserialize)]
+pub struct AutoLayoutArgs {
+    /// "grid", "tree", "radial", "layered" or "remove_overlaps"
+    layout: String,
+    /// Shapes to arrange; defaults to every unlocked shape on the board
+    shape_ids: Option<Vec<String>>,
+    /// Flow direction for tree and layered layouts: "TD", "BT", "LR" or "RL"
+    direction: Option<String>,
+}
+
+pub fn make_layout_tool() -> FunctionTool {
+    traced_tool(
+        "auto_layout",
+        "Rearrange shapes already on the board so nothing overlaps. Layouts: grid (rows and columns), tree (hierarchy following the arrows), radial (mind map around a central shape), layered (flowchart following the arrows, fewest crossings) and remove_overlaps (keep positions, just push overlapping shapes apart). Frames and groups move as one block with their contents; bound arrows follow. Pass shape_ids to arrange only some shapes. Use after drawing several shapes without explicit positions, or when the board looks cluttered.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: AutoLayoutArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let Some(kind) = LayoutKind::parse(&args.layout) else {
+                return ToolResult::error(format!(
+                    "Unknown layout '{}'. Expected one of: {}",
+                    args.layout,
+                    LayoutKind::NAMES.join(", ")
+                ));
+            };
+            let direction = match args.direction.as_deref().map(Direction::parse) {
+                Some(None) => return ToolResult::error(format!(
+                    "Unknown direction '{}'. Expected TD, BT, LR or RL",
+                    args.direction.unwrap_or_default()
+                )),
+                Some(Some(direction)) => direction,
+                None => Direction::default(),
+            };
+
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+            let ids = match parse_shape_ids(&board, args.shape_ids.as_deref().unwrap_or_default()) {
+                Ok(ids) => ids,
+                Err(e) => return e,
+            };
+
+            let actions = layout::arrange(&board, &ids, kind, direction);
+            println!("Tool: Auto layout '{}' ({} moves)", args.layout, actions.len());
+            if !actions.is_empty() {
+                record_action(Action::Batch(actions));
+            }
+
+            let bounds = current_board().ok().and_then(|board| board.bounds());
+            ToolResult::success(json!({
+                "status": "arranged",
+                "layout": args.layout,
+                "bounds": bounds.map(|b| json!({ "x": b.min_x, "y": b.min_y, "width": b.width(), "height": b.height() }))
+            }))
+        })
+    )
+}
+
+/// Fill of first-level branches, fill of deeper nodes and connector color, per branch
+const BRANCH_COLORS: &[(&str, &str, &str)] = &[
+    ("#bfdbfe", "#eff6ff", "#3b82f6"),
+    ("#bbf7d0", "#f0fdf4", "#22c55e"),
+    ("#fde68a", "#fffbeb", "#f59e0b"),
+    ("#fbcfe8", "#fdf2f8", "#ec4899"),
+    ("#ddd6fe", "#f5f3ff", "#8b5cf6"),
+    ("#fecaca", "#fef2f2", "#ef4444"),
+    ("#a5f3fc", "#ecfeff", "#06b6d4"),
+    ("#fed7aa", "#fff7ed", "#f97316"),
+];
+const MIND_MAP_ROOT_FILL: &str = "#e0e7ff";
+/// Upper bound on mind map size, so one call can't flood the board
+const MAX_MIND_MAP_NODES: usize = 150;
+
+/// A mind map node: a plain string for a leaf, or a label with children
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum MindMapNode {
+    Leaf(String),
+    Branch {
+        label: String,
+        #[serde(default)]
+        children: Vec<MindMapNode>,
+    },
+}
+
+impl MindMapNode {
+    fn label(&self) -> &str {
+        match self {
+            MindMapNode::Leaf(label) | MindMapNode::Branch { label, .. } => label,
+        }
+    }
+
+    fn children(&self) -> &[MindMapNode] {
+        match self {
+            MindMapNode::Leaf(_) => &[],
+            MindMapNode::Branch { children, .. } => children,
+        }
+    }
+}
+
+/// A mind map node in depth-first order
+struct MindMapEntry<'a> {
+    node: &'a MindMapNode,
+    depth: usize,
+    /// Index of the first-level branch the node belongs to, which picks its colors
+    branch: usize,
+    /// Index of the parent entry; `None` for the root
+    parent: Option<usize>,
+}
+
+/// Flatten the tree depth-first, refusing maps over `MAX_MIND_MAP_NODES`
+fn flatten_mind_map(root: &MindMapNode) -> Result<Vec<MindMapEntry<'_>>, String> {
+    let mut nodes: Vec<MindMapEntry> = Vec::new();
+    let mut stack = vec![(root, 0, 0, None)];
+    while let Some((node, depth, branch, parent)) = stack.pop() {
+        let index = nodes.len();
+        nodes.push(MindMapEntry { node, depth, branch, parent });
+        for (i, child) in node.children().iter().enumerate().rev() {
+            let child_branch = if depth == 0 { i } else { branch };
+            stack.push((child, depth + 1, child_branch, Some(index)));
+        }
+    }
+    if nodes.len() > MAX_MIND_MAP_NODES {
+        return Err(format!(
+            "Mind map has {} nodes; the limit is {}. Summarize or split it",
+            nodes.len(),
+            MAX_MIND_MAP_NODES
+        ));
+    }
+    Ok(nodes)
+}
+
+/// Boxes for the flattened nodes, laid out radially around the origin, and
+/// the connectors from each parent to its children
+fn mind_map_shapes(nodes: &[MindMapEntry]) -> (Vec<Shape>, Vec<Shape>) {
+    let mut shapes: Vec<Shape> = nodes
+        .iter()
+        .map(|entry| {
+            let (strong, light, _) = BRANCH_COLORS[entry.branch % BRANCH_COLORS.len()];
+            let (shape_type, fill, max_width) = match entry.depth {
+                0 => (ShapeType::Ellipse, MIND_MAP_ROOT_FILL, 240.0),
+                1 => (ShapeType::Rectangle, strong, 200.0),
+                _ => (ShapeType::Rectangle, light, 180.0),
+            };
+            let mut shape = Shape::new(shape_type, 0.0, 0.0, 0.0, 0.0, fill.to_string());
+            shape.label = Some(entry.node.label().to_string());
+            shape.fit_to_label(max_width);
+            shape
+        })
+        .collect();
+    let sizes: Vec<(f64, f64)> = shapes.iter().map(|s| (s.width, s.height)).collect();
+    let edges: Vec<(usize, usize)> = nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| Some((entry.parent?, i)))
+        .collect();
+    for (shape, (x, y)) in shapes.iter_mut().zip(layout::radial(&sizes, &edges)) {
+        shape.x = x;
+        shape.y = y;
+    }
+    let connectors = edges
+        .iter()
+        .map(|&(parent, child)| {
+            let (_, _, stroke) = BRANCH_COLORS[nodes[child].branch % BRANCH_COLORS.len()];
+            let mut line = Shape::connector(&shapes[parent], &shapes[child], stroke.to_string());
+            if let ShapeType::Arrow { headless, .. } = &mut line.shape_type {
+                *headless = true;
+            }
+            line
+        })
+        .collect();
+    (shapes, connectors)
+}
+
+#[derive(Deserialize)]
+pub struct DrawMindMapArgs {
+    /// Central topic: {"label": "...", "children": [...]}; children nest the same way
+    /// and leaves may be plain strings
+    root: MindMapNode,
+    /// Centre of the map; defaults to the centre of the visible region
+    x: Option<f64>,
+    y: Option<f64>,
+}
+
+pub fn make_mind_map_tool() -> FunctionTool {
+    traced_tool(
+        "draw_mind_map",
+        "Draw a radial mind map from a nested tree of labels, e.g. {\"root\": {\"label\": \"Solar power\", \"children\": [{\"label\": \"Costs\", \"children\": [\"Panels -80% since 2010\", \"Storage\"]}, \"Policy\"]}}. The root sits in the centre, each subtopic branch gets its own color, boxes are sized to fit their text and connected with lines that stay attached. Drawn as a single step the user can undo at once. Use it to summarize hierarchical research (topic, subtopics, facts) instead of placing many shapes by hand.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: DrawMindMapArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let nodes = match flatten_mind_map(&args.root) {
+                Ok(nodes) => nodes,
+                Err(e) => return ToolResult::error(e),
+            };
+            let (mut shapes, mut connectors) = mind_map_shapes(&nodes);
+
+            let view = AGENT_BOARD
+                .lock()
+                .map(|b| b.viewport.bounds())
+                .unwrap_or_else(|_| Viewport::default().bounds());
+            let centre = (
+                args.x.unwrap_or(view.min_x + view.width() / 2.0),
+                args.y.unwrap_or(view.min_y + view.height() / 2.0),
+            );
+            for shape in shapes.iter_mut().chain(&mut connectors) {
+                shape.x += centre.0;
+                shape.y += centre.1;
+            }
+
+            // Connectors go first so they are drawn beneath the boxes
+            let mut actions: Vec<Action> = connectors.into_iter().map(Action::Draw).collect();
+            actions.extend(shapes.iter().cloned().map(Action::Draw));
+
+            println!(
+                "Tool: Drawing mind map '{}' with {} nodes at ({}, {})",
+                args.root.label(),
+                nodes.len(),
+                centre.0,
+                centre.1
+            );
+            record_action(Action::Batch(actions));
+
+            let ids: Vec<_> = shapes.iter().map(|s| s.id).collect();
+            let bounds = current_board().ok().and_then(|board| board.members_bounds(&ids));
+            ToolResult::success(json!({
+                "status": "drawn",
+                "nodes": shapes.len(),
+                "root_id": shapes[0].id.to_string(),
+                "shapes": shapes
+                    .iter()
+                    .zip(&nodes)
+                    .map(|(shape, node)| json!({
+                        "label": shape.label,
+                        "shape_id": shape.id.to_string(),
+                        "depth": node.depth
+                    }))
+                    .collect::<Vec<_>>(),
+                "bounds": bounds.map(|b| json!({ "x": b.min_x, "y": b.min_y, "width": b.width(), "height": b.height() }))
+            }))
+        })
+    )
+}
+
+#[derive(Deserialize)]
+pub struct DrawChartArgs {
+    /// "bar", "line" or "pie"
+    chart_type: String,
+    /// Title shown above the chart
+    title: Option<String>,
+    /// Labels along the x axis (pie: the slices)
+    categories: Vec<String>,
+    /// One or more named series with one value per category (pie uses the first)
+    series: Vec<Series>,
+    x: Option<f64>,
+    y: Option<f64>,
+    width: Option<f64>,
+    height: Option<f64>,
+    /// shape_id of an existing chart to update instead of drawing a new one
+    shape_id: Option<String>,
+}
+
+pub fn make_chart_tool() -> FunctionTool {
+    traced_tool(
+        "draw_chart",
+        "Draw a bar, line or pie chart from data, e.g. {\"chart_type\": \"bar\", \"title\": \"Market share 2024 (%)\", \"categories\": [\"Apple\", \"Samsung\", \"Xiaomi\"], \"series\": [{\"name\": \"Share\", \"values\": [28, 23, 13]}]}. Use several series for grouped bars or multiple lines; a pie chart uses the first series. The chart keeps its data: pass shape_id of a chart drawn earlier to change its type, title or data in place. Use it for quantitative findings instead of approximating charts with rectangles.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: DrawChartArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let Some(kind) = ChartKind::parse(&args.chart_type) else {
+                return ToolResult::error(format!(
+                    "Unknown chart_type '{}'. Expected bar, line or pie",
+                    args.chart_type
+                ));
+            };
+            let data = ChartData {
+                kind,
+                categories: args.categories,
+                series: args.series,
+            };
+            if let Err(e) = data.validate() {
+                return ToolResult::error(e);
+            }
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+
+            // Update an existing chart in place
+            if let Some(raw_id) = &args.shape_id {
+                let ids = match parse_shape_ids(&board, std::slice::from_ref(raw_id)) {
+                    Ok(ids) => ids,
+                    Err(e) => return e,
+                };
+                let Some(mut shape) = board.shape(ids[0]).cloned().filter(|s| matches!(s.shape_type, ShapeType::Chart { .. })) else {
+                    return ToolResult::error(format!("Shape {} is not a chart", raw_id));
+                };
+                shape.shape_type = ShapeType::Chart { data };
+                shape.label = args.title.or(shape.label);
+                shape.x = args.x.unwrap_or(shape.x);
+                shape.y = args.y.unwrap_or(shape.y);
+                shape.width = args.width.unwrap_or(shape.width);
+                shape.height = args.height.unwrap_or(shape.height);
+                println!("Tool: Updating {} chart '{}'", kind.name(), shape.label.clone().unwrap_or_default());
+                let shape_id = shape.id;
+                record_action(Action::UpdateShape(shape));
+                return ToolResult::success(json!({
+                    "status": "updated",
+                    "shape_id": shape_id.to_string()
+                }));
+            }
+
+            let (default_width, default_height) = match kind {
+                ChartKind::Pie => (340.0, 320.0),
+                _ => (440.0, 280.0),
+            };
+            let width = args.width.unwrap_or(default_width);
+            let height = args.height.unwrap_or(default_height);
+            let (x, y) = placement(&board, args.x, args.y, (width, height));
+            let mut shape = Shape::new(ShapeType::Chart { data }, x, y, width, height, "#ffffff".to_string());
+            shape.label = args.title;
+            draw(shape, &format!("{} chart", kind.name()))
+        })
+    )
+}
+
+const TIMELINE_AXIS_COLOR: &str = "#334155";
+const TIMELINE_MARKER_COLOR: &str = "#3b82f6";
+const TIMELINE_STEM_COLOR: &str = "#94a3b8";
+const TIMELINE_CARD_FILL: &str = "#eff6ff";
+const TIMELINE_CARD_WIDTH: f64 = 180.0;
+/// Axis length per event when events are spaced evenly
+const TIMELINE_EVENT_SPACING: f64 = 200.0;
+/// Closest two markers may get; cards alternate sides, so this is half a card plus a margin
+const TIMELINE_MIN_GAP: f64 = 110.0;
+const TIMELINE_STEM: f64 = 36.0;
+const TIMELINE_MARKER: f64 = 14.0;
+const MAX_TIMELINE_EVENTS: usize = 60;
+
+#[derive(Deserialize)]
+pub struct TimelineEvent {
+    /// e.g. "1969", "2020-03" or "March 2020"
+    date: String,
+    /// What happened
+    label: String,
+    /// Card fill; defaults to the timeline style
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DrawTimelineArgs {
+    /// Frame title; without one the timeline is a plain group
+    title: Option<String>,
+    events: Vec<TimelineEvent>,
+    /// Top-left corner; defaults to a free spot in the visible region
+    x: Option<f64>,
+    y: Option<f64>,
+}
+
+/// Position in years of dates containing a 3-4 digit year, optionally
+/// followed by `-MM` and `-DD` ("1969", "2020-03-11", "c. 1850")
+fn timeline_position(date: &str) -> Option<f64> {
+    let bytes = date.as_bytes();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_digit() {
+            start += 1;
+            continue;
+        }
+        let end = (start..bytes.len()).find(|&i| !bytes[i].is_ascii_digit()).unwrap_or(bytes.len());
+        if (3..=4).contains(&(end - start)) {
+            let year: f64 = date[start..end].parse().ok()?;
+            let mut parts = date[end..].split('-').skip(1).map_while(|p| p.get(..2)?.parse::<f64>().ok());
+            let month = parts.next().filter(|m| (1.0..=12.0).contains(m)).unwrap_or(1.0);
+            let day = parts.next().filter(|d| (1.0..=31.0).contains(d)).unwrap_or(1.0);
+            return Some(year + (month - 1.0) / 12.0 + (day - 1.0) / 365.0);
+        }
+        start = end;
+    }
+    None
+}
+
+/// Marker offsets along the axis, in event order. Events are sorted and spaced
+/// by date when every date has a year, otherwise spaced evenly as given.
+fn timeline_offsets(events: &[TimelineEvent]) -> (Vec<usize>, Vec<f64>) {
+    let positions: Option<Vec<f64>> = events.iter().map(|e| timeline_position(&e.date)).collect();
+    let Some(positions) = positions else {
+        let order = (0..events.len()).collect();
+        let offsets = (0..events.len()).map(|i| i as f64 * TIMELINE_EVENT_SPACING).collect();
+        return (order, offsets);
+    };
+    let mut order: Vec<usize> = (0..events.len()).collect();
+    order.sort_by(|&a, &b| positions[a].total_cmp(&positions[b]));
+    let first = positions[order[0]];
+    let span = positions[order[order.len() - 1]] - first;
+    let length = (events.len() - 1) as f64 * TIMELINE_EVENT_SPACING;
+    let mut offsets: Vec<f64> = Vec::with_capacity(order.len());
+    for &i in &order {
+        let proportional = if span > 0.0 { (positions[i] - first) / span * length } else { 0.0 };
+        let offset = match offsets.last() {
+            Some(previous) => proportional.max(previous + TIMELINE_MIN_GAP),
+            None => proportional,
+        };
+        offsets.push(offset);
+    }
+    (order, offsets)
+}
+
+/// Size a box to a fixed width and the height its wrapped label needs
+fn fit_height(shape: &mut Shape, min_height: f64) {
+    let lines = shape.label_lines(14.0).len().max(1);
+    shape.height = (lines as f64 * 14.0 * 1.25 + 24.0).max(min_height);
+}
+
+/// Wrap freshly drawn shapes in a frame when titled, otherwise a plain group
+fn group_shapes(title: Option<String>, members: Vec<Uuid>, bounds: Bounds) -> Group {
+    match title {
+        Some(title) => Group::frame(title, members, bounds.padded(30.0)),
+        None => Group::plain(members),
+    }
+}
+
+/// Top-left corner for a new block of `size`, from the arguments or a free spot
+fn placement(board: &Board, x: Option<f64>, y: Option<f64>, size: (f64, f64)) -> (f64, f64) {
+    match (x, y) {
+        (Some(x), Some(y)) => (x, y),
+        (x, y) => {
+            let spot = layout::free_spot(board, &board.viewport.bounds(), size);
+            (x.unwrap_or(spot.0), y.unwrap_or(spot.1))
+        }
+    }
+}
+
+pub fn make_timeline_tool() -> FunctionTool {
+    traced_tool(
+        "draw_timeline",
+        "Draw a horizontal timeline of dated events, e.g. {\"title\": \"Space race\", \"events\": [{\"date\": \"1957-10\", \"label\": \"Sputnik 1\"}, {\"date\": \"1969-07-20\", \"label\": \"Apollo 11 lands\"}]}. When every date contains a year the events are sorted and spaced by date, otherwise they keep the given order. Each event gets a marker on the axis and a card with its date and label, alternating above and below. Everything is grouped (framed when titled) and drawn as a single undoable step.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: DrawTimelineArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            if args.events.is_empty() {
+                return ToolResult::error("A timeline needs at least one event".to_string());
+            }
+            if args.events.len() > MAX_TIMELINE_EVENTS {
+                return ToolResult::error(format!(
+                    "Timeline has {} events; the limit is {}. Keep the key milestones",
+                    args.events.len(),
+                    MAX_TIMELINE_EVENTS
+                ));
+            }
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+
+            // Build around an axis at y = 0 starting at x = 0, then move into place
+            let (order, offsets) = timeline_offsets(&args.events);
+            let mut cards = Vec::with_capacity(order.len());
+            let mut details = Vec::with_capacity(order.len());
+            for (side, (&i, &offset)) in order.iter().zip(&offsets).enumerate() {
+                let event = &args.events[i];
+                let fill = event.color.clone().unwrap_or_else(|| TIMELINE_CARD_FILL.to_string());
+                let mut card = Shape::new(ShapeType::Rectangle, 0.0, 0.0, TIMELINE_CARD_WIDTH, 0.0, fill);
+                card.label = Some(format!("{}\n{}", event.date.trim(), event.label.trim()));
+                fit_height(&mut card, 44.0);
+                card.x = offset - card.width / 2.0;
+                let above = side % 2 == 0;
+                card.y = if above { -TIMELINE_STEM - card.height } else { TIMELINE_STEM };
+
+                let r = TIMELINE_MARKER / 2.0;
+                let marker = Shape::new(
+                    ShapeType::Circle,
+                    offset - r,
+                    -r,
+                    TIMELINE_MARKER,
+                    TIMELINE_MARKER,
+                    TIMELINE_MARKER_COLOR.to_string(),
+                );
+                let (stem_y, stem_height) = if above {
+                    (-TIMELINE_STEM, TIMELINE_STEM - r)
+                } else {
+                    (r, TIMELINE_STEM - r)
+                };
+                let stem = Shape::new(ShapeType::Line, offset, stem_y, 0.0, stem_height, TIMELINE_STEM_COLOR.to_string());
+                details.push((event, card.id, marker.id));
+                cards.push((stem, marker, card));
+            }
+            let end = offsets.last().copied().unwrap_or_default() + TIMELINE_CARD_WIDTH / 2.0 + 40.0;
+            let axis = Shape::arrow(
+                (-TIMELINE_CARD_WIDTH / 2.0 - 40.0, 0.0),
+                (end, 0.0),
+                None,
+                None,
+                TIMELINE_AXIS_COLOR.to_string(),
+            );
+
+            // Axis and stems first so markers and cards sit on top
+            let mut shapes = vec![axis];
+            shapes.extend(cards.iter().map(|(stem, _, _)| stem.clone()));
+            shapes.extend(cards.iter().map(|(_, marker, _)| marker.clone()));
+            shapes.extend(cards.into_iter().map(|(_, _, card)| card));
+
+            let local = shapes
+                .iter()
+                .skip(1)
+                .fold(shapes[0].bounds(), |acc, s| acc.union(&s.bounds()));
+            let (x, y) = placement(&board, args.x, args.y, (local.width(), local.height()));
+            for shape in &mut shapes {
+                shape.x += x - local.min_x;
+                shape.y += y - local.min_y;
+            }
+            let bounds = Bounds {
+                min_x: x,
+                min_y: y,
+                max_x: x + local.width(),
+                max_y: y + local.height(),
+            };
+            let ids: Vec<Uuid> = shapes.iter().map(|s| s.id).collect();
+            let group = group_shapes(args.title.clone(), ids, bounds);
+            let group_id = group.id;
+
+            println!(
+                "Tool: Drawing timeline '{}' with {} events at ({}, {})",
+                args.title.as_deref().unwrap_or_default(),
+                args.events.len(),
+                x,
+                y
+            );
+            let mut actions: Vec<Action> = shapes.into_iter().map(Action::Draw).collect();
+            actions.push(Action::CreateGroup(group));
+            record_action(Action::Batch(actions));
+
+            ToolResult::success(json!({
+                "status": "drawn",
+                "group_id": group_id.to_string(),
+                "events": details
+                    .iter()
+                    .map(|(event, card, marker)| json!({
+                        "date": event.date,
+                        "label": event.label,
+                        "card_id": card.to_string(),
+                        "marker_id": marker.to_string()
+                    }))
+                    .collect::<Vec<_>>(),
+                "bounds": { "x": bounds.min_x, "y": bounds.min_y, "width": bounds.width(), "height": bounds.height() }
             }))
         })
     )
 }
 
+const TABLE_HEADER_FILL: &str = "#dbeafe";
+/// Body rows alternate between these fills
+const TABLE_ROW_FILLS: [&str; 2] = ["#ffffff", "#f8fafc"];
+const TABLE_MIN_COLUMN_WIDTH: f64 = 80.0;
+const TABLE_MAX_COLUMN_WIDTH: f64 = 260.0;
+const TABLE_ROW_HEIGHT: f64 = 40.0;
+const MAX_TABLE_CELLS: usize = 400;
+
 #[derive(Deserialize)]
-pub struct WipeBoardArgs {}
+pub struct DrawTableArgs {
+    /// Frame title; without one the table is a plain group
+    title: Option<String>,
+    /// Header cells
+    columns: Vec<String>,
+    /// Body rows, one string per column; short rows are padded with empty cells
+    rows: Vec<Vec<String>>,
+    /// Top-left corner; defaults to a free spot in the visible region
+    x: Option<f64>,
+    y: Option<f64>,
+}
+
+/// Rows of cells, header row first, and the table's width and height
+type TableLayout = (Vec<Vec<Shape>>, (f64, f64));
+
+/// Table cells laid out from the origin. Rows shorter than the header are
+/// padded with empty cells.
+fn table_layout(header: &[String], body: &[Vec<String>]) -> Result<TableLayout, String> {
+    let columns = header.len();
+    if columns == 0 {
+        return Err("A table needs at least one column".to_string());
+    }
+    if let Some(i) = body.iter().position(|row| row.len() > columns) {
+        return Err(format!(
+            "Row {} has {} cells but there are only {} columns",
+            i + 1,
+            body[i].len(),
+            columns
+        ));
+    }
+    let cells = columns * (body.len() + 1);
+    if cells > MAX_TABLE_CELLS {
+        return Err(format!(
+            "Table has {} cells; the limit is {}. Split it or summarize",
+            cells, MAX_TABLE_CELLS
+        ));
+    }
+    let mut grid: Vec<Vec<String>> = vec![header.to_vec()];
+    grid.extend(body.iter().map(|row| {
+        let mut row = row.clone();
+        row.resize(columns, String::new());
+        row
+    }));
+    let widths: Vec<f64> = (0..columns)
+        .map(|c| {
+            let longest = grid
+                .iter()
+                .flat_map(|row| row[c].lines())
+                .map(|l| l.chars().count())
+                .max()
+                .unwrap_or(0);
+            (longest as f64 * 14.0 * 0.55 + 32.0).clamp(TABLE_MIN_COLUMN_WIDTH, TABLE_MAX_COLUMN_WIDTH)
+        })
+        .collect();
+
+    // Lay rows out from the top, each as tall as its tallest cell
+    let mut rows: Vec<Vec<Shape>> = Vec::with_capacity(grid.len());
+    let mut top = 0.0;
+    for (r, texts) in grid.iter().enumerate() {
+        let fill = if r == 0 { TABLE_HEADER_FILL } else { TABLE_ROW_FILLS[(r - 1) % 2] };
+        let mut left = 0.0;
+        let mut row: Vec<Shape> = texts
+            .iter()
+            .zip(&widths)
+            .map(|(text, &width)| {
+                let mut cell = Shape::new(ShapeType::Rectangle, left, top, width, 0.0, fill.to_string());
+                cell.label = Some(text.trim().to_string()).filter(|t| !t.is_empty());
+                fit_height(&mut cell, TABLE_ROW_HEIGHT);
+                left += width;
+                cell
+            })
+            .collect();
+        let height = row.iter().map(|c| c.height).fold(TABLE_ROW_HEIGHT, f64::max);
+        for cell in &mut row {
+            cell.height = height;
+        }
+        top += height;
+        rows.push(row);
+    }
+
+    let size = (widths.iter().sum::<f64>(), top);
+    Ok((rows, size))
+}
+
+pub fn make_table_tool() -> FunctionTool {
+    traced_tool(
+        "draw_table",
+        "Draw a table of text cells, e.g. {\"title\": \"Battery chemistries\", \"columns\": [\"Type\", \"Energy density\", \"Cost\"], \"rows\": [[\"Li-ion\", \"250 Wh/kg\", \"$139/kWh\"], [\"LFP\", \"160 Wh/kg\", \"$95/kWh\"]]}. Columns are sized to their content, long text wraps and rows grow to fit; the header row is highlighted. Everything is grouped (framed when titled) and drawn as a single undoable step. Use it for comparisons instead of placing rectangles by hand.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: DrawTableArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let (mut rows, size) = match table_layout(&args.columns, &args.rows) {
+                Ok(table) => table,
+                Err(e) => return ToolResult::error(e),
+            };
+            let columns = args.columns.len();
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+
+            let (x, y) = placement(&board, args.x, args.y, size);
+            for cell in rows.iter_mut().flatten() {
+                cell.x += x;
+                cell.y += y;
+            }
+            let bounds = Bounds { min_x: x, min_y: y, max_x: x + size.0, max_y: y + size.1 };
+            let ids: Vec<Uuid> = rows.iter().flatten().map(|c| c.id).collect();
+            let group = group_shapes(args.title.clone(), ids, bounds);
+            let group_id = group.id;
+            let cell_ids: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| row.iter().map(|c| c.id.to_string()).collect())
+                .collect();
+
+            println!(
+                "Tool: Drawing table '{}' with {} columns and {} rows at ({}, {})",
+                args.title.as_deref().unwrap_or_default(),
+                columns,
+                args.rows.len(),
+                x,
+                y
+            );
+            let mut actions: Vec<Action> = rows.into_iter().flatten().map(Action::Draw).collect();
+            actions.push(Action::CreateGroup(group));
+            record_action(Action::Batch(actions));
+
+            ToolResult::success(json!({
+                "status": "drawn",
+                "group_id": group_id.to_string(),
+                "columns": columns,
+                "rows": args.rows.len(),
+                // Header row first, then body rows
+                "cell_ids": cell_ids,
+                "bounds": { "x": bounds.min_x, "y": bounds.min_y, "width": bounds.width(), "height": bounds.height() }
+            }))
+        })
+    )
+}
 
 pub fn make_wipe_tool() -> FunctionTool {
-    FunctionTool::new(
+    traced_tool(
         "wipe_board",
         "Clear all shapes from the canvas. Use before creating a new diagram or when the board is cluttered.",
-        |_args, _ctx| Box::pin(async move {
+        |_args| Box::pin(async move {
             println!("Tool: Wiping board");
             
-            if let Ok(mut history) = AGENT_HISTORY.lock() {
-                history.push(Action::Wipe);
-            }
-            let _ = AGENT_CHANNEL.send(Action::Wipe);
+            record_action(Action::Wipe);
             
             ToolResult::success(json!({ "status": "cleared" }))
         })
     )
 }
+
+#[derive(Deserialize)]
+pub struct ZOrderArgs {
+    /// Ids (from draw_shape) of the shapes to restack
+    shape_ids: Vec<String>,
+    /// "bring_to_front", "send_to_back", "bring_forward" or "send_backward"
+    operation: String,
+}
+
+pub fn make_z_order_tool() -> FunctionTool {
+    traced_tool(
+        "set_z_order",
+        "Change the stacking order of shapes so labels or highlights are not hidden behind later shapes. operation is one of bring_to_front, send_to_back, bring_forward, send_backward. Shapes only move relative to others on the same layer.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: ZOrderArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let Some(op) = ZOrder::parse(&args.operation) else {
+                return ToolResult::error(format!(
+                    "Unknown operation '{}'. Expected bring_to_front, send_to_back, bring_forward or send_backward",
+                    args.operation
+                ));
+            };
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+            let ids = match parse_shape_ids(&board, &args.shape_ids) {
+                Ok(ids) => ids,
+                Err(e) => return e,
+            };
+
+            println!("Tool: Reordering {} shapes ({:?})", ids.len(), op);
+            record_action(Action::Reorder { ids: ids.clone(), op });
+
+            ToolResult::success(json!({ "status": "reordered", "count": ids.len() }))
+        })
+    )
+}
+
+#[derive(Deserialize)]
+pub struct AssignLayerArgs {
+    /// Ids (from draw_shape) of the shapes to move
+    shape_ids: Vec<String>,
+    /// Layer name; created on top of the existing layers if it doesn't exist
+    layer: String,
+    /// Optionally show/hide or lock/unlock the layer at the same time
+    visible: Option<bool>,
+    locked: Option<bool>,
+}
+
+pub fn make_layer_tool() -> FunctionTool {
+    traced_tool(
+        "assign_layer",
+        "Move shapes onto a named layer (created if missing). Later layers are drawn above earlier ones, so use e.g. a 'background' layer for frames/areas and an 'annotations' layer for callouts. Optional visible/locked flags update the layer.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: AssignLayerArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+            let ids = match parse_shape_ids(&board, &args.shape_ids) {
+                Ok(ids) => ids,
+                Err(e) => return e,
+            };
+
+            let mut layer = board
+                .layer_by_name(&args.layer)
+                .cloned()
+                .unwrap_or_else(|| Layer::new(args.layer.clone()));
+            let changed = board.layer(layer.id).is_none()
+                || args.visible.is_some_and(|v| v != layer.visible)
+                || args.locked.is_some_and(|l| l != layer.locked);
+            layer.visible = args.visible.unwrap_or(layer.visible);
+            layer.locked = args.locked.unwrap_or(layer.locked);
+            if changed {
+                record_action(Action::PutLayer(layer.clone()));
+            }
+
+            println!("Tool: Moving {} shapes to layer '{}'", ids.len(), layer.name);
+            record_action(Action::AssignLayer { ids: ids.clone(), layer: Some(layer.id) });
+
+            ToolResult::success(json!({
+                "status": "assigned",
+                "layer": layer.name,
+                "count": ids.len()
+            }))
+        })
+    )
+}
+
+#[derive(Deserialize)]
+pub struct ShapeEdit {
+    /// Id of the shape to change (from draw_shape or the board summary)
+    shape_id: String,
+    /// New label; an empty string removes it
+    label: Option<String>,
+    color: Option<String>,
+    /// New top-left corner in world coordinates (the start of a line or arrow)
+    x: Option<f64>,
+    y: Option<f64>,
+    /// New size; for lines and arrows the signed offset from start to end
+    width: Option<f64>,
+    height: Option<f64>,
+    /// Remove the shape instead of changing it
+    delete: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct EditShapesArgs {
+    edits: Vec<ShapeEdit>,
+}
+
+/// Apply one edit to a copy of the shape; `None` means delete it
+fn edited_shape(shape: &Shape, edit: &ShapeEdit) -> Option<Shape> {
+    if edit.delete == Some(true) {
+        return None;
+    }
+    let mut shape = shape.clone();
+    if let Some(label) = &edit.label {
+        shape.label = Some(label.clone()).filter(|l| !l.trim().is_empty());
+        // Text sizes itself to its content unless told otherwise
+        if shape.shape_type == ShapeType::Text {
+            let resized = Shape::text(shape.x, shape.y, label.clone());
+            shape.width = resized.width;
+            shape.height = resized.height;
+        }
+    }
+    if let Some(color) = &edit.color {
+        shape.color = color.clone();
+    }
+    shape.x = edit.x.unwrap_or(shape.x);
+    shape.y = edit.y.unwrap_or(shape.y);
+    if shape.is_connector() {
+        // Lines and arrows run from (x, y) by a signed width and height
+        shape.width = edit.width.unwrap_or(shape.width);
+        shape.height = edit.height.unwrap_or(shape.height);
+        return Some(shape);
+    }
+    let width = edit.width.map_or(shape.width, |w| w.max(1.0));
+    let height = edit.height.map_or(shape.height, |h| h.max(1.0));
+    // Point lists are relative to the corner, so they scale with the box
+    if let ShapeType::Polygon { points } | ShapeType::Freehand { points } = &mut shape.shape_type {
+        let sx = if shape.width > 0.0 { width / shape.width } else { 1.0 };
+        let sy = if shape.height > 0.0 { height / shape.height } else { 1.0 };
+        for p in points.iter_mut() {
+            p.x *= sx;
+            p.y *= sy;
+        }
+    }
+    shape.width = width;
+    shape.height = height;
+    Some(shape)
+}
+
+/// An id that more than one edit refers to, if any
+fn repeated_id(ids: &[Uuid]) -> Option<Uuid> {
+    let mut seen = HashSet::new();
+    ids.iter().copied().find(|id| !seen.insert(*id))
+}
+
+pub fn make_edit_shapes_tool() -> FunctionTool {
+    traced_tool(
+        "edit_shapes",
+        "Change shapes already on the board instead of redrawing them: relabel, recolor, move (x, y) or resize (width, height) each shape by id, or remove it with delete: true. For lines and arrows, x and y are the start and width and height the signed offset to the end. Only the fields you pass change. Give each shape at most one edit. All edits are applied together as one undoable step, or none are if any id is unknown or repeated.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: EditShapesArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+            if args.edits.is_empty() {
+                return ToolResult::error("No edits given".to_string());
+            }
+            if args.edits.len() > MAX_BATCH_SHAPES {
+                return ToolResult::error(format!(
+                    "Too many edits ({}); edit at most {} shapes per call",
+                    args.edits.len(),
+                    MAX_BATCH_SHAPES
+                ));
+            }
+
+            let board = match current_board() {
+                Ok(board) => board,
+                Err(e) => return e,
+            };
+            let raw_ids: Vec<String> = args.edits.iter().map(|e| e.shape_id.clone()).collect();
+            let ids = match parse_shape_ids(&board, &raw_ids) {
+                Ok(ids) => ids,
+                Err(e) => return e,
+            };
+            if let Some(id) = repeated_id(&ids) {
+                return ToolResult::error(format!(
+                    "Shape {} has more than one edit; combine its changes into a single edit",
+                    id
+                ));
+            }
+
+            let mut actions = Vec::new();
+            let mut deleted = Vec::new();
+            for (id, edit) in ids.iter().zip(&args.edits) {
+                let Some(shape) = board.shape(*id) else { continue };
+                match edited_shape(shape, edit) {
+                    Some(updated) if updated != *shape => actions.push(Action::UpdateShape(updated)),
+                    Some(_) => {}
+                    None => deleted.push(*id),
+                }
+            }
+            let updated = actions.len();
+            if !deleted.is_empty() {
+                actions.push(Action::Delete(deleted.clone()));
+            }
+
+            println!("Tool: Editing {} shapes, deleting {}", updated, deleted.len());
+            if !actions.is_empty() {
+                record_action(Action::Batch(actions));
+            }
+
+            ToolResult::success(json!({
+                "status": "edited",
+                "updated": updated,
+                "deleted": deleted.len()
+            }))
+        })
+    )
+}
+
+fn type_name(shape_type: &ShapeType) -> &'static str {
+    match shape_type {
+        ShapeType::Rectangle => "rectangle",
+        ShapeType::Circle => "circle",
+        ShapeType::Line => "line",
+        ShapeType::Ellipse => "ellipse",
+        ShapeType::Diamond => "diamond",
+        ShapeType::Polygon { .. } => "polygon",
+        ShapeType::Freehand { .. } => "freehand",
+        ShapeType::StickyNote => "sticky_note",
+        ShapeType::Image { .. } => "image",
+        ShapeType::Text => "text",
+        ShapeType::Arrow { .. } => "arrow",
+        ShapeType::Chart { .. } => "chart",
+    }
+}
+
+/// Plain-text listing of the board's frames and shapes (ids, types, labels,
+/// geometry and arrow endpoints) so a follow-up run can refer to what is
+/// already drawn. Lists at most `max_shapes` shapes.
+pub fn describe_board(board: &Board, max_shapes: usize) -> String {
+    if board.shapes.is_empty() {
+        return "The board is empty.".to_string();
+    }
+    let mut out = String::new();
+    let frames: Vec<&Group> = board.groups.iter().filter(|g| g.is_frame()).collect();
+    if !frames.is_empty() {
+        out.push_str("Frames:\n");
+        for frame in frames {
+            out.push_str(&format!(
+                "- '{}' at ({:.0}, {:.0}) size {:.0}x{:.0}, {} shapes\n",
+                frame.title,
+                frame.x,
+                frame.y,
+                frame.width,
+                frame.height,
+                frame.members.len()
+            ));
+        }
+    }
+    out.push_str(&format!("Shapes ({}):\n", board.shapes.len()));
+    for shape in board.shapes.iter().take(max_shapes) {
+        let label = shape
+            .label
+            .as_deref()
+            .map(|l| format!(" '{}'", l.split_whitespace().collect::<Vec<_>>().join(" ")))
+            .unwrap_or_default();
+        let ends = match &shape.shape_type {
+            ShapeType::Arrow { start, end, .. } if start.is_some() || end.is_some() => {
+                let end_name = |id: &Option<Uuid>| id.map(|id| id.to_string()).unwrap_or_else(|| "-".to_string());
+                format!(" from {} to {}", end_name(start), end_name(end))
+            }
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "- {} {}{} at ({:.0}, {:.0}) size {:.0}x{:.0} color {}{}\n",
+            shape.id,
+            type_name(&shape.shape_type),
+            label,
+            shape.x,
+            shape.y,
+            shape.width,
+            shape.height,
+            shape.color,
+            ends
+        ));
+    }
+    if board.shapes.len() > max_shapes {
+        out.push_str(&format!("- ... and {} more\n", board.shapes.len() - max_shapes));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(fields: serde_json::Value) -> ShapeEdit {
+        let mut value = json!({ "shape_id": Uuid::nil().to_string() });
+        value.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn moving_an_arrow_keeps_its_direction() {
+        let arrow = Shape::arrow((300.0, 200.0), (100.0, 250.0), None, None, "#fff".to_string());
+        let moved = edited_shape(&arrow, &edit(json!({ "x": 320.0, "label": "cause" }))).unwrap();
+        assert_eq!((moved.x, moved.y, moved.width, moved.height), (320.0, 200.0, -200.0, 50.0));
+
+        let horizontal = Shape::new(ShapeType::Line, 0.0, 40.0, 120.0, 0.0, "#fff".to_string());
+        let recolored = edited_shape(&horizontal, &edit(json!({ "color": "#f00" }))).unwrap();
+        assert_eq!((recolored.width, recolored.height), (120.0, 0.0));
+    }
+
+    #[test]
+    fn resizing_an_arrow_sets_its_signed_offset() {
+        let arrow = Shape::arrow((0.0, 0.0), (100.0, 100.0), None, None, "#fff".to_string());
+        let turned = edited_shape(&arrow, &edit(json!({ "width": -80.0 }))).unwrap();
+        assert_eq!((turned.width, turned.height), (-80.0, 100.0));
+    }
+
+    #[test]
+    fn boxes_keep_their_size_unless_it_is_set() {
+        let rect = Shape::new(ShapeType::Rectangle, 0.0, 0.0, 160.0, 80.0, "#fff".to_string());
+        let moved = edited_shape(&rect, &edit(json!({ "x": 50.0, "y": 60.0 }))).unwrap();
+        assert_eq!((moved.width, moved.height), (160.0, 80.0));
+
+        let squashed = edited_shape(&rect, &edit(json!({ "height": -5.0 }))).unwrap();
+        assert_eq!((squashed.width, squashed.height), (160.0, 1.0));
+    }
+
+    #[test]
+    fn resizing_a_polygon_scales_its_points() {
+        let triangle = Shape::from_points(&[(0.0, 0.0), (100.0, 0.0), (50.0, 50.0)], true, "#fff".to_string());
+        let wider = edited_shape(&triangle, &edit(json!({ "width": 200.0 }))).unwrap();
+        assert_eq!(wider.width, 200.0);
+        let points: Vec<(f64, f64)> = wider.absolute_points().iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(points, vec![(0.0, 0.0), (200.0, 0.0), (100.0, 50.0)]);
+    }
+
+    #[test]
+    fn repeated_ids_are_found() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(repeated_id(&[a, b]), None);
+        assert_eq!(repeated_id(&[a, b, a]), Some(a));
+    }
+
+    fn event(date: &str) -> TimelineEvent {
+        TimelineEvent {
+            date: date.to_string(),
+            label: format!("Event {}", date),
+            color: None,
+        }
+    }
+
+    fn strings(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn timeline_dates_are_read_as_years() {
+        assert_eq!(timeline_position("1969"), Some(1969.0));
+        assert_eq!(timeline_position("2020-03-11"), Some(2020.0 + 2.0 / 12.0 + 10.0 / 365.0));
+        assert_eq!(timeline_position("2020-03"), Some(2020.0 + 2.0 / 12.0));
+        assert_eq!(timeline_position("c. 1850"), Some(1850.0));
+        // Day numbers are too short to be years
+        assert_eq!(timeline_position("March 20, 2020"), Some(2020.0));
+        assert_eq!(timeline_position("2020-13-40"), Some(2020.0));
+        assert_eq!(timeline_position("Spring"), None);
+        assert_eq!(timeline_position("the 20s"), None);
+    }
+
+    #[test]
+    fn timeline_events_are_sorted_and_spaced_by_date() {
+        let events = [event("2000"), event("1950"), event("1900")];
+        let (order, offsets) = timeline_offsets(&events);
+        assert_eq!(order, vec![2, 1, 0]);
+        assert_eq!(offsets, vec![0.0, TIMELINE_EVENT_SPACING, 2.0 * TIMELINE_EVENT_SPACING]);
+
+        // Close dates are pushed apart to the minimum gap
+        let events = [event("1900"), event("1901"), event("2000")];
+        let (_, offsets) = timeline_offsets(&events);
+        assert_eq!(offsets[1], TIMELINE_MIN_GAP);
+        assert_eq!(offsets[2], 2.0 * TIMELINE_EVENT_SPACING);
+    }
+
+    #[test]
+    fn equal_timeline_dates_keep_the_minimum_gap() {
+        let events = [event("1969"), event("1969")];
+        assert_eq!(timeline_offsets(&events), (vec![0, 1], vec![0.0, TIMELINE_MIN_GAP]));
+    }
+
+    #[test]
+    fn timelines_without_a_year_everywhere_keep_the_given_order() {
+        let events = [event("2000"), event("Later"), event("1900")];
+        let (order, offsets) = timeline_offsets(&events);
+        assert_eq!(order, vec![0, 1, 2]);
+        assert_eq!(offsets, vec![0.0, TIMELINE_EVENT_SPACING, 2.0 * TIMELINE_EVENT_SPACING]);
+    }
+
+    #[test]
+    fn tables_pad_short_rows_and_share_row_heights() {
+        let long = "a long cell that has to wrap over several lines because it is far wider than any column";
+        let (rows, size) = table_layout(&strings(&["Type", "Notes"]), &[strings(&["Li-ion", long]), strings(&["LFP"])]).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row.len() == 2));
+        assert_eq!(rows[2][1].label, None);
+        assert_eq!(rows[0][0].color, TABLE_HEADER_FILL);
+        assert_eq!(rows[1][1].width, TABLE_MAX_COLUMN_WIDTH);
+        assert_eq!(rows[1][0].height, rows[1][1].height);
+        assert!(rows[1][0].height > TABLE_ROW_HEIGHT);
+        assert_eq!(rows[2][0].y, rows[1][0].y + rows[1][0].height);
+        assert_eq!(size, (rows[0][0].width + rows[0][1].width, rows[2][0].y + rows[2][0].height));
+    }
+
+    #[test]
+    fn tables_reject_rows_wider_than_the_columns() {
+        let err = table_layout(&strings(&["A", "B"]), &[strings(&["1", "2"]), strings(&["1", "2", "3"])]).unwrap_err();
+        assert_eq!(err, "Row 2 has 3 cells but there are only 2 columns");
+        assert!(table_layout(&[], &[]).is_err());
+        let too_many = vec![strings(&["x"]); MAX_TABLE_CELLS];
+        assert!(table_layout(&strings(&["A"]), &too_many).unwrap_err().contains("the limit is"));
+    }
+
+    fn mind_map(value: serde_json::Value) -> MindMapNode {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn mind_map_nodes_are_leaves_or_branches() {
+        assert!(matches!(mind_map(json!("Storage")), MindMapNode::Leaf(label) if label == "Storage"));
+        let branch = mind_map(json!({ "label": "Costs" }));
+        assert_eq!(branch.label(), "Costs");
+        assert!(branch.children().is_empty());
+        let nested = mind_map(json!({ "label": "Costs", "children": ["Panels", { "label": "Storage", "children": ["Li-ion"] }] }));
+        assert_eq!(nested.children().len(), 2);
+        assert_eq!(nested.children()[1].children()[0].label(), "Li-ion");
+        assert!(serde_json::from_value::<MindMapNode>(json!(42)).is_err());
+    }
+
+    #[test]
+    fn mind_map_flattening_tracks_branches_and_parents() {
+        let root = mind_map(json!({
+            "label": "Solar",
+            "children": [
+                { "label": "Costs", "children": ["Panels", { "label": "Storage", "children": ["Li-ion"] }] },
+                "Policy"
+            ]
+        }));
+        let nodes = flatten_mind_map(&root).unwrap();
+        let rows: Vec<(&str, usize, usize, Option<usize>)> = nodes
+            .iter()
+            .map(|n| (n.node.label(), n.depth, n.branch, n.parent))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Solar", 0, 0, None),
+                ("Costs", 1, 0, Some(0)),
+                ("Panels", 2, 0, Some(1)),
+                ("Storage", 2, 0, Some(1)),
+                ("Li-ion", 3, 0, Some(3)),
+                ("Policy", 1, 1, Some(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn mind_map_connectors_follow_the_tree() {
+        let root = mind_map(json!({ "label": "Solar", "children": [{ "label": "Costs", "children": ["Panels"] }, "Policy"] }));
+        let nodes = flatten_mind_map(&root).unwrap();
+        let (shapes, connectors) = mind_map_shapes(&nodes);
+        assert_eq!(shapes.len(), 4);
+        assert!(matches!(shapes[0].shape_type, ShapeType::Ellipse));
+        assert_eq!(shapes[0].color, MIND_MAP_ROOT_FILL);
+        assert_eq!(shapes[1].color, BRANCH_COLORS[0].0);
+        assert_eq!(shapes[2].color, BRANCH_COLORS[0].1);
+        assert_eq!(shapes[3].color, BRANCH_COLORS[1].0);
+
+        let edges: Vec<(Option<Uuid>, Option<Uuid>, &str, bool)> = connectors
+            .iter()
+            .map(|line| match &line.shape_type {
+                ShapeType::Arrow { start, end, headless } => (*start, *end, line.color.as_str(), *headless),
+                other => panic!("connector is a {:?}", other),
+            })
+            .collect();
+        let id = |i: usize| Some(shapes[i].id);
+        assert_eq!(
+            edges,
+            vec![
+                (id(0), id(1), BRANCH_COLORS[0].2, true),
+                (id(1), id(2), BRANCH_COLORS[0].2, true),
+                (id(0), id(3), BRANCH_COLORS[1].2, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn mind_maps_over_the_node_limit_are_refused() {
+        let leaves = |count: usize| (0..count).map(|i| json!(format!("Leaf {}", i))).collect::<Vec<_>>();
+        let full = mind_map(json!({ "label": "Root", "children": leaves(MAX_MIND_MAP_NODES - 1) }));
+        assert_eq!(flatten_mind_map(&full).unwrap().len(), MAX_MIND_MAP_NODES);
+        let over = mind_map(json!({ "label": "Root", "children": leaves(MAX_MIND_MAP_NODES) }));
+        let err = flatten_mind_map(&over).err().unwrap();
+        assert_eq!(err, format!("Mind map has {} nodes; the limit is {}. Summarize or split it", MAX_MIND_MAP_NODES + 1, MAX_MIND_MAP_NODES));
+    }
+}
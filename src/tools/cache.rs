@@ -0,0 +1,137 @@
+//! On-disk cache for search and fetch results, so repeated research topics
+//! don't hit the same sites again. Entries are JSON files named after a
+//! hash of their key and expire after a per-namespace TTL.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Overrides where cache files are kept
+const CACHE_DIR_VAR: &str = "RESEARCH_CACHE_DIR";
+const DEFAULT_CACHE_DIR: &str = ".research-cache";
+
+pub const SEARCH_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+pub const FETCH_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    /// Full key, checked on read in case two keys share a file name
+    key: String,
+    /// Seconds since the Unix epoch
+    stored_at: u64,
+    value: T,
+}
+
+/// A cache hit and how old it is
+pub struct Cached<T> {
+    pub value: T,
+    pub age: Duration,
+}
+
+/// Lowercase with runs of whitespace collapsed, so trivially different
+/// spellings of a query share an entry
+pub fn normalize_key(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn cache_dir() -> PathBuf {
+    std::env::var_os(CACHE_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_DIR))
+}
+
+fn entry_path(namespace: &str, key: &str) -> PathBuf {
+    cache_dir().join(format!("{}-{:016x}.json", namespace, fnv1a(key)))
+}
+
+/// FNV-1a: stable across runs and Rust versions, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// A stored value younger than `ttl`
+pub async fn get<T: DeserializeOwned>(namespace: &str, key: &str, ttl: Duration) -> Option<Cached<T>> {
+    let text = tokio::fs::read_to_string(entry_path(namespace, key)).await.ok()?;
+    let entry: Entry<T> = serde_json::from_str(&text).ok()?;
+    let age = Duration::from_secs(now_secs().saturating_sub(entry.stored_at));
+    (entry.key == key && age < ttl).then_some(Cached { value: entry.value, age })
+}
+
+/// Store a value; failures only cost a future cache miss, so they are logged and ignored
+pub async fn put<T: Serialize>(namespace: &str, key: &str, value: &T) {
+    let entry = Entry {
+        key: key.to_string(),
+        stored_at: now_secs(),
+        value,
+    };
+    let result = async {
+        tokio::fs::create_dir_all(cache_dir()).await?;
+        let json = serde_json::to_string(&entry)?;
+        tokio::fs::write(entry_path(namespace, key), json).await
+    }
+    .await;
+    if let Err(e) = result {
+        println!("Cache: Failed to store {} entry: {}", namespace, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Once;
+
+    /// Point every test at one fresh temporary cache folder
+    fn use_temp_dir() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("research-cache-{}", uuid::Uuid::new_v4()));
+            std::env::set_var(CACHE_DIR_VAR, dir);
+        });
+    }
+
+    #[test]
+    fn keys_ignore_case_and_spacing() {
+        assert_eq!(normalize_key("  Solar\tPanel   Efficiency \n"), "solar panel efficiency");
+        assert_eq!(normalize_key("Solar panel"), normalize_key("solar  PANEL"));
+    }
+
+    #[tokio::test]
+    async fn stored_values_are_read_back_until_they_expire() {
+        use_temp_dir();
+        put("test", "fresh", &vec![1, 2, 3]).await;
+        let hit = get::<Vec<i32>>("test", "fresh", SEARCH_TTL).await.expect("cache miss");
+        assert_eq!(hit.value, vec![1, 2, 3]);
+        assert!(hit.age < Duration::from_secs(5));
+        assert!(get::<Vec<i32>>("test", "missing", SEARCH_TTL).await.is_none());
+        assert!(get::<Vec<i32>>("other", "fresh", SEARCH_TTL).await.is_none());
+
+        let old = Entry {
+            key: "old".to_string(),
+            stored_at: now_secs() - 7 * 60 * 60,
+            value: 5,
+        };
+        std::fs::write(entry_path("test", "old"), serde_json::to_string(&old).unwrap()).unwrap();
+        assert!(get::<i32>("test", "old", SEARCH_TTL).await.is_none());
+        assert_eq!(get::<i32>("test", "old", FETCH_TTL).await.map(|hit| hit.value), Some(5));
+    }
+
+    #[tokio::test]
+    async fn an_entry_stored_under_another_key_is_a_miss() {
+        use_temp_dir();
+        put("test", "first key", &"first").await;
+        // Simulate a hash collision: the second key's file holds the first key's entry
+        std::fs::copy(entry_path("test", "first key"), entry_path("test", "second key")).unwrap();
+        assert!(get::<String>("test", "second key", SEARCH_TTL).await.is_none());
+        assert!(get::<String>("test", "first key", SEARCH_TTL).await.is_some());
+    }
+}
@@ -0,0 +1,457 @@
+//! Full-text search over a local folder of notes and papers. Files are split
+//! into passages and indexed in an in-memory inverted index ranked with BM25;
+//! the index is rebuilt whenever a file in the folder is added, changed or
+//! removed.
+
+use crate::server_state::AGENT_CONFIG;
+use crate::tools::fetch::extract_page;
+use crate::tools::trace::traced_tool;
+use once_cell::sync::Lazy;
+use radkit::tools::{FunctionTool, ToolResult};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::SystemTime;
+
+const DEFAULT_MAX_RESULTS: usize = 5;
+const MAX_MAX_RESULTS: usize = 20;
+/// Indexed file types by extension
+const EXTENSIONS: &[&str] = &["md", "markdown", "txt", "text", "rst", "html", "htm", "pdf"];
+/// Files beyond this count are ignored, to keep indexing time bounded
+const MAX_FILES: usize = 5000;
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+/// Passages are built from whole paragraphs up to about this many words
+const PASSAGE_WORDS: usize = 150;
+/// Longest passage text returned to the model
+const MAX_PASSAGE_CHARS: usize = 1200;
+/// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "has", "have", "in", "is",
+    "it", "its", "of", "on", "or", "that", "the", "this", "to", "was", "were", "will", "with",
+];
+
+#[derive(Deserialize)]
+pub struct SearchDocumentsArgs {
+    /// Words to look for; ranked by relevance, not an exact phrase match
+    query: String,
+    /// Number of passages to return (default 5, at most 20)
+    max_results: Option<usize>,
+}
+
+struct Passage {
+    /// Path relative to the indexed folder
+    path: String,
+    /// Nearest preceding Markdown heading
+    heading: Option<String>,
+    text: String,
+    length: usize,
+}
+
+#[derive(Default)]
+struct DocumentIndex {
+    root: PathBuf,
+    /// Modification time of every indexed file, to detect changes
+    stamps: HashMap<PathBuf, SystemTime>,
+    passages: Vec<Passage>,
+    /// Term -> (passage, term frequency)
+    postings: HashMap<String, Vec<(usize, usize)>>,
+    average_length: f64,
+    /// Files that could not be read, with the reason
+    skipped: Vec<(String, String)>,
+}
+
+static INDEX: Lazy<Mutex<Option<DocumentIndex>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn make_documents_tool() -> FunctionTool {
+    traced_tool(
+        "search_documents",
+        "Search the team's local document folder (Markdown, text, HTML and PDF files) for passages about a topic. Returns the best matching passages ranked by relevance, each with its file path and section heading. Use it alongside web_search; cite the file path when you use a passage.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: SearchDocumentsArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+            let max_results = args.max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, MAX_MAX_RESULTS);
+
+            let folder = AGENT_CONFIG.lock().map(|c| c.documents_dir.clone()).unwrap_or_default();
+            if folder.trim().is_empty() {
+                return ToolResult::error("No document folder is configured; set one in the settings".to_string());
+            }
+
+            println!("Tool: Searching documents in '{}' for '{}'", folder, args.query);
+
+            // Walking the folder and extracting PDFs is blocking work
+            let query = args.query.clone();
+            let searched = tokio::task::spawn_blocking(move || search_folder(Path::new(folder.trim()), &query, max_results)).await;
+            let (results, files, passages, skipped) = match searched {
+                Ok(Ok(found)) => found,
+                Ok(Err(e)) => return ToolResult::error(e),
+                Err(e) => return ToolResult::error(format!("Document search failed: {}", e)),
+            };
+
+            println!("Tool: Document search found {} passages", results.len());
+
+            ToolResult::success(json!({
+                "query": args.query,
+                "files_indexed": files,
+                "passages_indexed": passages,
+                "result_count": results.len(),
+                "results": results,
+                "unreadable_files": skipped
+            }))
+        })
+    )
+}
+
+type SearchOutcome = (Vec<serde_json::Value>, usize, usize, Vec<serde_json::Value>);
+
+/// Bring the index for `folder` up to date and run the query against it
+fn search_folder(folder: &Path, query: &str, max_results: usize) -> Result<SearchOutcome, String> {
+    if !folder.is_dir() {
+        return Err(format!("Document folder '{}' does not exist", folder.display()));
+    }
+    let files = list_files(folder);
+    let stale = lock_index().as_ref().is_none_or(|index| {
+        index.root != folder
+            || index.stamps.len() != files.len()
+            || files.iter().any(|(path, stamp)| index.stamps.get(path) != Some(stamp))
+    });
+    if stale {
+        // Built without holding the lock, so a slow or failing build cannot
+        // block or poison searches
+        println!("Tool: Indexing {} documents in '{}'", files.len(), folder.display());
+        let index = DocumentIndex::build(folder, files);
+        *lock_index() = Some(index);
+    }
+    let guard = lock_index();
+    let Some(index) = guard.as_ref() else {
+        return Err("Document index is unavailable".to_string());
+    };
+
+    let results = index
+        .search(query, max_results)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (passage, score))| {
+            let passage = &index.passages[passage];
+            json!({
+                "rank": i + 1,
+                "path": passage.path,
+                "heading": passage.heading,
+                "text": truncate_chars(&passage.text, MAX_PASSAGE_CHARS),
+                "score": (score * 100.0).round() / 100.0
+            })
+        })
+        .collect();
+    let skipped = index
+        .skipped
+        .iter()
+        .map(|(path, reason)| json!({ "path": path, "reason": reason }))
+        .collect();
+    Ok((results, index.stamps.len(), index.passages.len(), skipped))
+}
+
+fn lock_index() -> MutexGuard<'static, Option<DocumentIndex>> {
+    INDEX.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Indexable files under `root` with their modification times, skipping hidden entries
+fn list_files(root: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                pending.push(path);
+            } else if meta.len() <= MAX_FILE_BYTES && extension(&path).is_some_and(|e| EXTENSIONS.contains(&e.as_str())) {
+                files.push((path, meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)));
+                if files.len() >= MAX_FILES {
+                    break;
+                }
+            }
+        }
+        if files.len() >= MAX_FILES {
+            break;
+        }
+    }
+    files.sort();
+    files
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension().map(|e| e.to_string_lossy().to_lowercase())
+}
+
+/// Plain text of a document
+fn read_text(path: &Path) -> Result<String, String> {
+    match extension(path).as_deref() {
+        // The PDF parser panics on some malformed files
+        Some("pdf") => std::panic::catch_unwind(|| pdf_extract::extract_text(path))
+            .map_err(|_| "PDF text extraction failed: malformed file".to_string())?
+            .map_err(|e| format!("PDF text extraction failed: {}", e)),
+        Some("html" | "htm") => {
+            let html = std::fs::read(path).map_err(|e| e.to_string())?;
+            let url = Url::from_file_path(path).map_err(|_| "Invalid file path".to_string())?;
+            let page = extract_page(&String::from_utf8_lossy(&html), &url);
+            if page.title.is_empty() {
+                Ok(page.text)
+            } else {
+                Ok(format!("# {}\n\n{}", page.title, page.text))
+            }
+        }
+        _ => std::fs::read(path)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+impl DocumentIndex {
+    fn build(root: &Path, files: Vec<(PathBuf, SystemTime)>) -> Self {
+        let mut index = DocumentIndex {
+            root: root.to_path_buf(),
+            ..Default::default()
+        };
+        for (path, stamp) in files {
+            let relative = path.strip_prefix(root).unwrap_or(&path).display().to_string();
+            match read_text(&path) {
+                Ok(text) => {
+                    for (heading, passage) in split_passages(&text) {
+                        index.add(relative.clone(), heading, passage);
+                    }
+                }
+                Err(e) => index.skipped.push((relative, e)),
+            }
+            index.stamps.insert(path, stamp);
+        }
+        let total: usize = index.passages.iter().map(|p| p.length).sum();
+        index.average_length = total as f64 / index.passages.len().max(1) as f64;
+        index
+    }
+
+    fn add(&mut self, path: String, heading: Option<String>, text: String) {
+        let terms = tokenize(&text);
+        if terms.is_empty() {
+            return;
+        }
+        let id = self.passages.len();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for term in &terms {
+            *counts.entry(term.clone()).or_default() += 1;
+        }
+        for (term, count) in counts {
+            self.postings.entry(term).or_default().push((id, count));
+        }
+        self.passages.push(Passage {
+            path,
+            heading,
+            text,
+            length: terms.len(),
+        });
+    }
+
+    /// Best `limit` passages for `query` by BM25 score
+    fn search(&self, query: &str, limit: usize) -> Vec<(usize, f64)> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        let total = self.passages.len() as f64;
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for term in &terms {
+            let Some(postings) = self.postings.get(term) else { continue };
+            let frequency = postings.len() as f64;
+            let idf = ((total - frequency + 0.5) / (frequency + 0.5) + 1.0).ln();
+            for &(passage, count) in postings {
+                let count = count as f64;
+                let length = self.passages[passage].length as f64 / self.average_length;
+                *scores.entry(passage).or_default() += idf * count * (K1 + 1.0) / (count + K1 * (1.0 - B + B * length));
+            }
+        }
+        let mut ranked: Vec<(usize, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+/// Lowercase words without stop words. A trailing `s` is dropped as a
+/// crude plural stem, so "panels" matches "panel".
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .map(|w| match w.strip_suffix('s') {
+            Some(stem) if stem.len() > 2 && !stem.ends_with('s') => stem.to_string(),
+            _ => w,
+        })
+        .collect()
+}
+
+/// Group paragraphs into passages of about `PASSAGE_WORDS` words, each with
+/// the Markdown heading it falls under. Longer paragraphs are split.
+fn split_passages(text: &str) -> Vec<(Option<String>, String)> {
+    let mut builder = PassageBuilder::default();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        let title = trimmed
+            .strip_prefix('#')
+            .map(|t| t.trim_start_matches('#'))
+            .filter(|t| t.starts_with(' '));
+        if trimmed.is_empty() || title.is_some() {
+            builder.paragraph(&paragraph.join(" "));
+            paragraph.clear();
+        }
+        match title {
+            Some(title) => builder.heading(title.trim()),
+            None if !trimmed.is_empty() => paragraph.push(trimmed),
+            None => {}
+        }
+    }
+    builder.paragraph(&paragraph.join(" "));
+    builder.flush();
+    builder.passages
+}
+
+#[derive(Default)]
+struct PassageBuilder {
+    passages: Vec<(Option<String>, String)>,
+    heading: Option<String>,
+    words: Vec<String>,
+}
+
+impl PassageBuilder {
+    fn heading(&mut self, title: &str) {
+        self.flush();
+        self.heading = Some(title.to_string()).filter(|t| !t.is_empty());
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.is_empty() {
+            return;
+        }
+        if !self.words.is_empty() && self.words.len() + words.len() > PASSAGE_WORDS {
+            self.flush();
+        }
+        for chunk in words.chunks(PASSAGE_WORDS) {
+            if !self.words.is_empty() {
+                // Paragraph breaks inside a passage are kept as blank lines
+                self.words.push("\n\n".to_string());
+            }
+            self.words.extend(chunk.iter().map(|w| w.to_string()));
+            if chunk.len() == PASSAGE_WORDS {
+                self.flush();
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.words.is_empty() {
+            let text = self.words.join(" ").replace(" \n\n ", "\n\n");
+            self.passages.push((self.heading.clone(), text));
+            self.words.clear();
+        }
+    }
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temporary folder holding the given files, removed on drop
+    struct Folder(PathBuf);
+
+    impl Folder {
+        fn new(files: &[(&str, &str)]) -> Self {
+            let root = std::env::temp_dir().join(format!("documents-{}", uuid::Uuid::new_v4()));
+            for (name, text) in files {
+                let path = root.join(name);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, text).unwrap();
+            }
+            Folder(root)
+        }
+    }
+
+    impl Drop for Folder {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn tokenize_drops_stop_words_and_plurals() {
+        assert_eq!(
+            tokenize("The Solar panels of 2024, and a glass"),
+            vec!["solar", "panel", "2024", "glass"]
+        );
+        assert_eq!(tokenize("is gas"), vec!["gas"]);
+    }
+
+    #[test]
+    fn split_passages_keeps_headings_and_breaks_long_text() {
+        let passages = split_passages("Intro line.\n\n# Methods\nFirst paragraph\ncontinues here.\n\nSecond one.\n\n## Results\nDone.");
+        assert_eq!(
+            passages,
+            vec![
+                (None, "Intro line.".to_string()),
+                (Some("Methods".to_string()), "First paragraph continues here.\n\nSecond one.".to_string()),
+                (Some("Results".to_string()), "Done.".to_string()),
+            ]
+        );
+
+        let long = vec!["word"; PASSAGE_WORDS * 2 + 10].join(" ");
+        let passages = split_passages(&long);
+        assert_eq!(passages.len(), 3);
+        assert_eq!(passages[0].1.split_whitespace().count(), PASSAGE_WORDS);
+        assert_eq!(passages[2].1.split_whitespace().count(), 10);
+        assert!(split_passages("#hashtag is text").iter().all(|(heading, _)| heading.is_none()));
+    }
+
+    #[test]
+    fn search_ranks_the_most_relevant_passage_first() {
+        let folder = Folder::new(&[
+            ("solar.md", "# Solar\nSolar panels convert sunlight. Panel efficiency for solar cells keeps rising."),
+            ("notes/wind.txt", "Wind turbines make power on windy hills. Solar is mentioned once."),
+            ("other.txt", "Nothing relevant about cooking pasta."),
+            (".hidden.md", "Solar solar solar"),
+            ("image.png", "Solar"),
+        ]);
+        let files = list_files(&folder.0);
+        assert_eq!(files.len(), 3);
+        let index = DocumentIndex::build(&folder.0, files);
+
+        let results = index.search("solar panels", 5);
+        assert_eq!(results.len(), 2);
+        let top = &index.passages[results[0].0];
+        assert_eq!(top.path, "solar.md");
+        assert_eq!(top.heading.as_deref(), Some("Solar"));
+        assert_eq!(index.passages[results[1].0].path, Path::new("notes").join("wind.txt").display().to_string());
+        assert!(results[0].1 > results[1].1);
+
+        assert_eq!(index.search("solar", 1).len(), 1);
+        assert!(index.search("the and of", 5).is_empty());
+    }
+}
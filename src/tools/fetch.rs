@@ -0,0 +1,471 @@
+use crate::model::Source;
+use crate::server_state::RESEARCH_RUN;
+use crate::tools::trace::traced_tool;
+use crate::tools::{cache, http};
+use crate::transcript::CHARS_PER_TOKEN;
+use radkit::tools::{FunctionTool, ToolResult};
+use reqwest::Url;
+use scraper::node::Node;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const DEFAULT_MAX_TOKENS: usize = 3000;
+const MAX_MAX_TOKENS: usize = 12000;
+/// Pages declaring a larger size are refused; others are cut at this size
+/// while downloading
+const MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+/// Cache namespace of extracted pages
+const CACHE_NAMESPACE: &str = "page";
+
+/// Elements that never hold article text
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form", "iframe",
+    "svg", "canvas", "button", "select", "input", "textarea", "dialog", "menu", "figure",
+];
+
+/// Class/id words marking navigation, ads and other page chrome
+const BOILERPLATE_WORDS: &[&str] = &[
+    "nav", "navbar", "navigation", "menu", "sidebar", "breadcrumb", "breadcrumbs", "ad", "ads",
+    "advert", "advertisement", "sponsored", "promo", "banner", "cookie", "cookies", "consent",
+    "share", "sharing", "social", "comments", "comment", "footer", "related", "newsletter",
+    "subscribe", "popup", "modal", "masthead", "toolbar", "skip",
+];
+
+/// Elements that start a new paragraph of text
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "main", "h1", "h2", "h3", "h4", "h5", "h6", "li", "ul",
+    "ol", "dl", "dt", "dd", "blockquote", "pre", "table", "tr", "br", "hr",
+];
+
+/// Text length at which a main content candidate is accepted
+const MIN_MAIN_TEXT: usize = 200;
+
+/// Candidates for the main content, most specific first
+const MAIN_SELECTORS: &[&str] = &[
+    "article",
+    "main",
+    "[role=main]",
+    "#content",
+    "#main-content",
+    ".post-content",
+    ".entry-content",
+    ".article-body",
+    "body",
+];
+
+#[derive(Deserialize)]
+pub struct FetchPageArgs {
+    /// Address of the page to read, usually a url from web_search
+    url: String,
+    /// Approximate size limit of the returned text in tokens
+    max_tokens: Option<usize>,
+}
+
+/// Readable content of a web page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page {
+    pub title: String,
+    /// `<link rel="canonical">` when present, otherwise the fetched URL
+    pub canonical_url: String,
+    /// Main text as paragraphs separated by blank lines
+    pub text: String,
+}
+
+/// A page as stored in the cache, before truncation
+#[derive(Serialize, Deserialize)]
+struct FetchedPage {
+    /// Address after redirects
+    url: String,
+    page: Page,
+}
+
+pub fn make_fetch_tool() -> FunctionTool {
+    traced_tool(
+        "fetch_page",
+        "Download a web page (usually a url from web_search) and read its main text, without navigation, ads or scripts. Returns the title, canonical url and text, cut to about max_tokens tokens (default 3000). Use it to read the sources behind the most relevant search results before drawing conclusions.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: FetchPageArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let url = match Url::parse(args.url.trim()) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+                Ok(url) => return ToolResult::error(format!("Unsupported url scheme '{}'", url.scheme())),
+                Err(e) => return ToolResult::error(format!("Invalid url '{}': {}", args.url, e)),
+            };
+            let max_tokens = args.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS).clamp(100, MAX_MAX_TOKENS);
+
+            let mut cache_key = url.clone();
+            cache_key.set_fragment(None);
+            let cache_key = cache_key.to_string();
+            let (fetched, cache_age) = match cache::get::<FetchedPage>(CACHE_NAMESPACE, &cache_key, cache::FETCH_TTL).await {
+                Some(hit) => {
+                    println!("Tool: Page cache hit for '{}'", url);
+                    (hit.value, Some(hit.age))
+                }
+                None => {
+                    println!("Tool: Fetching page '{}'", url);
+                    let fetched = match download(url).await {
+                        Ok(fetched) => fetched,
+                        Err(e) => return ToolResult::error(e),
+                    };
+                    cache::put(CACHE_NAMESPACE, &cache_key, &fetched).await;
+                    (fetched, None)
+                }
+            };
+            let page = fetched.page;
+            let (text, truncated) = truncate_to_tokens(&page.text, max_tokens);
+
+            println!(
+                "Tool: Read '{}' ({} chars{})",
+                page.title,
+                text.len(),
+                if truncated { ", truncated" } else { "" }
+            );
+
+            // Pages the agent reads are listed under the run's sources in the notes
+            if let Ok(mut run) = RESEARCH_RUN.lock() {
+                run.add_source(Source {
+                    url: page.canonical_url.clone(),
+                    title: Some(page.title.clone()).filter(|t| !t.trim().is_empty()),
+                    quote: None,
+                });
+            }
+
+            ToolResult::success(json!({
+                "url": fetched.url,
+                "canonical_url": page.canonical_url,
+                "title": page.title,
+                "text": text,
+                "truncated": truncated,
+                "cached": cache_age.is_some(),
+                "cache_age_secs": cache_age.map(|age| age.as_secs())
+            }))
+        })
+    )
+}
+
+/// Download a page and extract its readable content
+async fn download(url: Url) -> Result<FetchedPage, String> {
+    http::check_public_url(&url).await?;
+    let request = http::client()
+        .get(url)
+        .header("Accept", "text/html,application/xhtml+xml,text/plain;q=0.9");
+    let mut resp = http::send(request).await?;
+    if !resp.status().is_success() {
+        return Err(format!("Request failed with status: {}", resp.status()));
+    }
+    let final_url = resp.url().clone();
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("text/html")
+        .to_lowercase();
+    let is_html = content_type.contains("html");
+    if !is_html && !content_type.starts_with("text/") {
+        return Err(format!(
+            "Cannot read '{}' content; only HTML and plain text pages are supported",
+            content_type
+        ));
+    }
+    if let Some(length) = resp.content_length().filter(|&l| l > MAX_BODY_BYTES as u64) {
+        return Err(format!(
+            "Page is too large to read ({} bytes; the limit is {})",
+            length, MAX_BODY_BYTES
+        ));
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| format!("Failed to read body: {}", e))? {
+        let room = MAX_BODY_BYTES - bytes.len();
+        bytes.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        if bytes.len() >= MAX_BODY_BYTES {
+            break;
+        }
+    }
+    let body = String::from_utf8_lossy(&bytes);
+
+    let page = if is_html {
+        extract_page(&body, &final_url)
+    } else {
+        Page {
+            title: String::new(),
+            canonical_url: final_url.to_string(),
+            text: normalize_paragraphs(&body),
+        }
+    };
+    Ok(FetchedPage {
+        url: final_url.to_string(),
+        page,
+    })
+}
+
+/// Pull the title, canonical URL and main text out of an HTML document
+pub fn extract_page(html: &str, url: &Url) -> Page {
+    let document = Html::parse_document(html);
+
+    let first_attr = |selector: &str, attr: &str| {
+        Selector::parse(selector).ok().and_then(|s| {
+            document
+                .select(&s)
+                .filter_map(|e| e.value().attr(attr))
+                .map(str::trim)
+                .find(|v| !v.is_empty())
+                .map(str::to_string)
+        })
+    };
+    let first_text = |selector: &str| {
+        Selector::parse(selector).ok().and_then(|s| {
+            document
+                .select(&s)
+                .map(|e| collapse_whitespace(&e.text().collect::<String>()))
+                .find(|t| !t.is_empty())
+        })
+    };
+
+    let title = first_attr("meta[property='og:title']", "content")
+        .or_else(|| first_text("title"))
+        .or_else(|| first_text("h1"))
+        .unwrap_or_default();
+    let canonical_url = first_attr("link[rel=canonical]", "href")
+        .and_then(|href| url.join(&href).ok())
+        .unwrap_or_else(|| url.clone())
+        .to_string();
+
+    // The first candidate holding a reasonable amount of text is the content;
+    // failing that, the longest one
+    let mut text = String::new();
+    for name in MAIN_SELECTORS {
+        let Ok(selector) = Selector::parse(name) else { continue };
+        let candidate = document
+            .select(&selector)
+            .map(|root| {
+                let mut builder = TextBuilder::default();
+                builder.walk(root);
+                builder.finish()
+            })
+            .max_by_key(|t| t.len())
+            .unwrap_or_default();
+        if candidate.len() >= MIN_MAIN_TEXT {
+            text = candidate;
+            break;
+        }
+        if candidate.len() > text.len() {
+            text = candidate;
+        }
+    }
+
+    Page { title, canonical_url, text }
+}
+
+/// Collects text from a subtree as paragraphs, skipping page chrome
+#[derive(Default)]
+struct TextBuilder {
+    paragraphs: Vec<String>,
+    current: String,
+}
+
+impl TextBuilder {
+    fn walk(&mut self, element: ElementRef) {
+        let tag = element.value().name();
+        if is_boilerplate(element) {
+            return;
+        }
+        if tag == "pre" {
+            self.flush();
+            let code = element.text().collect::<String>();
+            let code = code.trim_matches('\n').trim_end();
+            if !code.is_empty() {
+                self.paragraphs.push(code.to_string());
+            }
+            return;
+        }
+        let block = BLOCK_TAGS.contains(&tag);
+        if block {
+            self.flush();
+        }
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.current.push_str("## "),
+            "li" => self.current.push_str("- "),
+            _ => {}
+        }
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.walk(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if block {
+            self.flush();
+        } else if matches!(tag, "td" | "th") {
+            self.current.push_str(" | ");
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if text.starts_with(char::is_whitespace) && !self.current.ends_with(' ') && !self.current.is_empty() {
+            self.current.push(' ');
+        }
+        self.current.push_str(&collapse_whitespace(text));
+        if text.ends_with(char::is_whitespace) && !self.current.ends_with(' ') {
+            self.current.push(' ');
+        }
+    }
+
+    fn flush(&mut self) {
+        let paragraph = collapse_whitespace(&self.current);
+        // Table rows end with a cell separator
+        let paragraph = paragraph.trim_end_matches(" |").to_string();
+        self.current.clear();
+        // Drop bullets and headings left without any text
+        if !paragraph.is_empty() && paragraph != "-" && paragraph != "##" {
+            self.paragraphs.push(paragraph);
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.paragraphs.join("\n\n")
+    }
+}
+
+/// Hidden elements and those whose tag, class or id marks navigation or ads
+fn is_boilerplate(element: ElementRef) -> bool {
+    let value = element.value();
+    if SKIPPED_TAGS.contains(&value.name()) {
+        return true;
+    }
+    if value.attr("hidden").is_some()
+        || value.attr("aria-hidden") == Some("true")
+        || matches!(value.attr("role"), Some("navigation" | "banner" | "contentinfo" | "complementary"))
+    {
+        return true;
+    }
+    let names = value.attr("class").into_iter().chain(value.id());
+    names
+        .flat_map(|n| n.split(|c: char| c.is_whitespace() || c == '-' || c == '_'))
+        .any(|word| BOILERPLATE_WORDS.contains(&word.to_lowercase().as_str()))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Plain text with runs of blank lines reduced to paragraph breaks
+fn normalize_paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(collapse_whitespace)
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Cut `text` to roughly `max_tokens`, preferring a paragraph, then a
+/// sentence, then a word boundary. Returns whether anything was cut.
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> (String, bool) {
+    let max_chars = max_tokens * CHARS_PER_TOKEN;
+    if text.len() <= max_chars {
+        return (text.to_string(), false);
+    }
+    let mut end = max_chars;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &text[..end];
+    // Only back off to a boundary that keeps most of the budget
+    let min = end * 3 / 4;
+    let cut = [head.rfind("\n\n"), head.rfind(". ").map(|i| i + 1), head.rfind(' ')]
+        .into_iter()
+        .flatten()
+        .find(|&i| i >= min)
+        .unwrap_or(end);
+    (format!("{} […]", text[..cut].trim_end()), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(name: &str, url: &str) -> Page {
+        let path = format!("{}/tests/fixtures/pages/{}", env!("CARGO_MANIFEST_DIR"), name);
+        extract_page(&std::fs::read_to_string(path).unwrap(), &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn article_keeps_the_story_and_drops_chrome() {
+        let page = page("article.html", "https://energydesk.example/news/12345?ref=home");
+        assert_eq!(page.title, "Solar panel prices fell 80% in a decade");
+        assert_eq!(
+            page.canonical_url,
+            "https://energydesk.example/2024/05/solar-prices?utm_source=feed"
+        );
+        assert!(page.text.starts_with("## Solar panel prices fell 80% in a decade"));
+        assert!(page.text.contains(
+            "The cost of photovoltaic modules has dropped by roughly 80 percent since 2014, driven by larger factories"
+        ));
+        assert!(page.text.contains("## Storage is the next bottleneck"));
+        assert!(page.text.contains("- Module prices: down 80% since 2014"));
+        assert!(page.text.contains("paying households to shift demand to sunny hours."));
+        for chrome in [
+            "Subscribe", "Advertisement", "Sponsored", "cheap inverters", "Share on", "cookies", "Most read",
+            "First! Great article", "daily briefing", "All rights reserved", "dataLayer", "Price chart",
+        ] {
+            assert!(!page.text.contains(chrome), "kept '{}'", chrome);
+        }
+    }
+
+    #[test]
+    fn docs_page_falls_back_to_content_div_and_title() {
+        let page = page("docs.html", "https://docs.example/guides/retries");
+        assert_eq!(page.title, "Configuring retries — HTTP client guide");
+        assert_eq!(page.canonical_url, "https://docs.example/guides/retries");
+        let paragraphs: Vec<&str> = page.text.split("\n\n").collect();
+        assert_eq!(paragraphs[0], "## Configuring retries");
+        assert!(paragraphs.contains(&"Option | Default"));
+        assert!(paragraphs.contains(&"max_retries | 3"));
+        assert!(paragraphs.contains(&"let client = Client::builder()\n    .max_retries(5)\n    .build();"));
+        assert_eq!(
+            paragraphs.last(),
+            Some(&"Retries are never attempted for requests with a body that cannot be replayed.")
+        );
+        for chrome in ["Docs home", "Getting started", "Docs › Guides", "Internal note", "hosted plan", "DocGen", "schema.org"] {
+            assert!(!page.text.contains(chrome), "kept '{}'", chrome);
+        }
+    }
+
+    #[test]
+    fn short_text_is_not_truncated() {
+        assert_eq!(truncate_to_tokens("Short text.", 100), ("Short text.".to_string(), false));
+    }
+
+    #[test]
+    fn truncation_prefers_paragraph_boundaries() {
+        let page = page("article.html", "https://energydesk.example/");
+        // The budget ends inside the second heading, so the cut comes just before it
+        let (text, truncated) = truncate_to_tokens(&page.text, 68);
+        assert!(truncated);
+        assert!(text.len() <= 68 * CHARS_PER_TOKEN + " […]".len());
+        assert!(text.ends_with(" […]"));
+        let kept = text.trim_end_matches(" […]");
+        assert!(page.text.starts_with(kept));
+        assert!(kept.ends_with("competition between manufacturers."), "cut inside a paragraph: {:?}", kept);
+        assert!(page.text[kept.len()..].starts_with("\n\n## Storage"));
+    }
+
+    #[test]
+    fn truncation_falls_back_to_words_and_keeps_utf8_intact() {
+        let text = "ñandú ".repeat(200);
+        let (cut, truncated) = truncate_to_tokens(&text, 10);
+        assert!(truncated);
+        assert!(cut.trim_end_matches(" […]").ends_with("ñandú"));
+    }
+}
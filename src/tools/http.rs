@@ -0,0 +1,332 @@
+//! Shared HTTP client for the research tools, with per-host rate limiting
+//! and backoff when a site signals that we are sending too many requests.
+
+use hyper::client::connect::dns::Name;
+use once_cell::sync::Lazy;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::header::HeaderMap;
+use reqwest::redirect::Policy;
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Minimum spacing between requests to the same host
+const HOST_INTERVAL: Duration = Duration::from_millis(1000);
+/// Retries after a rate-limited response before giving up
+const MAX_RETRIES: u32 = 3;
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 10;
+
+static CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(USER_AGENT)
+        // Names are checked by the resolver on every hop; address literals
+        // never reach it, so redirects to them are checked here
+        .dns_resolver(Arc::new(PublicResolver))
+        .redirect(Policy::custom(|attempt| {
+            let private = is_private_host(attempt.url())
+                && !attempt.url().host_str().is_some_and(is_trusted_host);
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if private {
+                attempt.error("redirect to a private address")
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+        .unwrap_or_default()
+});
+
+/// Hosts the user configured, such as a self-hosted SearxNG instance, which
+/// may live on a private address
+static TRUSTED_HOSTS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Earliest time the next request to each host may start
+static NEXT_SLOT: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The client every tool shares, so connections and TLS sessions are reused
+pub fn client() -> &'static Client {
+    &CLIENT
+}
+
+/// Allow requests to the host of a URL the user configured even when it
+/// resolves to a private address
+pub fn trust_host(url: &str) {
+    let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(host_key)) else { return };
+    if let Ok(mut hosts) = TRUSTED_HOSTS.lock() {
+        hosts.insert(host);
+    }
+}
+
+fn is_trusted_host(host: &str) -> bool {
+    TRUSTED_HOSTS.lock().is_ok_and(|hosts| hosts.contains(&host_key(host)))
+}
+
+fn host_key(host: &str) -> String {
+    host.trim_start_matches('[').trim_end_matches(']').trim_end_matches('.').to_lowercase()
+}
+
+/// Resolves names like the system resolver but drops private addresses,
+/// so neither a redirect nor a DNS answer that changed since
+/// `check_public_url` can reach the server's own network
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let trusted = is_trusted_host(&host);
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| trusted || is_public_ip(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("'{}' resolves only to private addresses", host).into());
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Send a request built on `client()`, waiting for the host's rate limit and
+/// retrying with backoff on 429 responses.
+pub async fn send(request: RequestBuilder) -> Result<Response, String> {
+    send_with(request, false).await
+}
+
+/// Like `send`, but also backs off on 202, which DuckDuckGo answers instead
+/// of 429 when it wants us to slow down
+pub async fn send_to_duckduckgo(request: RequestBuilder) -> Result<Response, String> {
+    send_with(request, true).await
+}
+
+async fn send_with(request: RequestBuilder, accepted_is_rate_limit: bool) -> Result<Response, String> {
+    let request = request.build().map_err(|e| format!("Invalid request: {}", e))?;
+    let host = request.url().host_str().unwrap_or_default().to_string();
+
+    let mut attempt = 0;
+    loop {
+        wait_for_host(&host).await;
+        let Some(this_try) = request.try_clone() else {
+            return CLIENT.execute(request).await.map_err(|e| format!("Network error: {}", e));
+        };
+        let resp = CLIENT
+            .execute(this_try)
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+        if !is_rate_limited(resp.status(), accepted_is_rate_limit) {
+            return Ok(resp);
+        }
+        if attempt >= MAX_RETRIES {
+            return Err(format!(
+                "{} is rate limiting requests (status {}); try again later",
+                host,
+                resp.status()
+            ));
+        }
+        let delay = retry_after(resp.headers()).unwrap_or(Duration::from_secs(2u64.pow(attempt + 1))).min(MAX_BACKOFF);
+        println!("HTTP: {} answered {}, retrying in {:?}", host, resp.status(), delay);
+        push_back(&host, delay);
+        attempt += 1;
+    }
+}
+
+/// Refuse a URL whose host is, or resolves to, a loopback, private or
+/// link-local address, so model-chosen URLs can't probe the local network
+pub async fn check_public_url(url: &Url) -> Result<(), String> {
+    let refused = || Err(format!("Refusing to fetch '{}': it points at a private address", url));
+    if is_private_host(url) {
+        return refused();
+    }
+    let Some(host) = url.host_str().filter(|_| url.domain().is_some()) else {
+        return Ok(());
+    };
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addresses = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Cannot resolve '{}': {}", host, e))?;
+    for address in addresses {
+        if !is_public_ip(address.ip()) {
+            return refused();
+        }
+    }
+    Ok(())
+}
+
+/// Private by its address or name alone, without a DNS lookup
+fn is_private_host(url: &Url) -> bool {
+    let Some(host) = url.host_str() else { return true };
+    // IPv6 literals keep their brackets in the host string
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return !is_public_ip(ip);
+    }
+    let name = host.trim_end_matches('.').to_lowercase();
+    name == "localhost" || name.ends_with(".localhost")
+}
+
+/// Whether `ip` is reachable on the public internet
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_ip(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local fc00::/7 and link-local fe80::/10
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+fn is_rate_limited(status: StatusCode, accepted_is_rate_limit: bool) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (accepted_is_rate_limit && status == StatusCode::ACCEPTED)
+}
+
+/// `Retry-After` given in seconds
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// Reserve the host's next slot and sleep until it arrives
+async fn wait_for_host(host: &str) {
+    let wait = {
+        let Ok(mut slots) = NEXT_SLOT.lock() else { return };
+        let now = Instant::now();
+        let slot = slots.get(host).copied().filter(|s| *s > now).unwrap_or(now);
+        slots.insert(host.to_string(), slot + HOST_INTERVAL);
+        slot - now
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Keep every request to `host` waiting for at least `delay`
+fn push_back(host: &str, delay: Duration) {
+    if let Ok(mut slots) = NEXT_SLOT.lock() {
+        let until = Instant::now() + delay;
+        let slot = slots.entry(host.to_string()).or_insert(until);
+        *slot = (*slot).max(until);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(text: &str) -> Url {
+        Url::parse(text).unwrap()
+    }
+
+    #[test]
+    fn private_and_special_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.10", "169.254.169.254", "0.0.0.0",
+            "100.64.0.1", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} counted as public", ip);
+        }
+        for ip in ["93.184.215.14", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{} counted as private", ip);
+        }
+    }
+
+    #[test]
+    fn localhost_names_and_literals_are_private_hosts() {
+        assert!(is_private_host(&url("http://localhost:8080/admin")));
+        assert!(is_private_host(&url("http://api.localhost/")));
+        assert!(is_private_host(&url("http://[::1]/")));
+        assert!(is_private_host(&url("http://192.168.0.1/router")));
+        assert!(!is_private_host(&url("https://example.com/")));
+    }
+
+    #[tokio::test]
+    async fn names_resolving_to_private_addresses_are_refused() {
+        let name: Name = "localhost".parse().unwrap();
+        let err = PublicResolver.resolve(name).await.err().expect("localhost resolved");
+        assert!(err.to_string().contains("private addresses"), "{}", err);
+
+        // The shared client refuses it even without `check_public_url`
+        let err = client().get("http://localhost:9/").send().await.unwrap_err();
+        assert!(err.is_connect(), "{}", err);
+    }
+
+    #[test]
+    fn trusted_hosts_are_matched_by_name() {
+        trust_host("http://Searx.Internal.:8888/search");
+        assert!(is_trusted_host("searx.internal"));
+        assert!(!is_trusted_host("other.internal"));
+    }
+
+    #[test]
+    fn only_duckduckgo_treats_accepted_as_rate_limiting() {
+        assert!(is_rate_limited(StatusCode::TOO_MANY_REQUESTS, false));
+        assert!(!is_rate_limited(StatusCode::ACCEPTED, false));
+        assert!(is_rate_limited(StatusCode::ACCEPTED, true));
+        assert!(!is_rate_limited(StatusCode::OK, true));
+    }
+
+    #[test]
+    fn retry_after_reads_whole_seconds() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(reqwest::header::RETRY_AFTER, " 7 ".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        // HTTP dates are not supported and fall back to the default backoff
+        headers.insert(reqwest::header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    fn slot(host: &str) -> Duration {
+        let slots = NEXT_SLOT.lock().unwrap();
+        slots[host].saturating_duration_since(Instant::now())
+    }
+
+    #[tokio::test]
+    async fn requests_to_a_host_are_spaced_and_pushed_back() {
+        let host = "spacing.test";
+        let started = Instant::now();
+        wait_for_host(host).await;
+        assert!(started.elapsed() < HOST_INTERVAL / 2, "the first request waited");
+        // The next request is booked one interval later
+        let next = slot(host);
+        assert!(next > HOST_INTERVAL / 2 && next <= HOST_INTERVAL, "{:?}", next);
+
+        push_back(host, Duration::from_secs(5));
+        assert!(slot(host) > Duration::from_secs(4));
+        // A shorter backoff never brings the slot forward
+        push_back(host, Duration::from_millis(10));
+        assert!(slot(host) > Duration::from_secs(4));
+        assert!(!NEXT_SLOT.lock().unwrap().contains_key("other.test"));
+    }
+
+    #[tokio::test]
+    async fn check_public_url_refuses_private_literals() {
+        assert!(check_public_url(&url("http://127.0.0.1:8080/")).await.is_err());
+        assert!(check_public_url(&url("http://localhost/")).await.is_err());
+        assert!(check_public_url(&url("https://1.1.1.1/")).await.is_ok());
+    }
+}
@@ -1,4 +1,16 @@
 #[cfg(feature = "server")]
 pub mod board;
 #[cfg(feature = "server")]
-pub mod ddg;
+pub mod cache;
+#[cfg(feature = "server")]
+pub mod documents;
+#[cfg(feature = "server")]
+pub mod fetch;
+#[cfg(feature = "server")]
+pub mod http;
+#[cfg(feature = "server")]
+pub mod notes;
+#[cfg(feature = "server")]
+pub mod search;
+#[cfg(feature = "server")]
+pub mod trace;
@@ -0,0 +1,50 @@
+use crate::server_state::RESEARCH_RUN;
+use crate::tools::trace::traced_tool;
+use radkit::tools::{FunctionTool, ToolResult};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Longest finding kept in the notes
+const MAX_FINDING_CHARS: usize = 600;
+
+#[derive(Deserialize)]
+pub struct AddNoteArgs {
+    /// One or two sentences on what was found or decided
+    summary: String,
+}
+
+pub fn make_note_tool() -> FunctionTool {
+    traced_tool(
+        "add_note",
+        "Add a short summary of what you just found or decided to the research notes shown beside the board. Call it after reading a source or settling on a structure, with one or two plain sentences; the notes also list the pages you fetched and end with your final answer.",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: AddNoteArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+
+            let summary = args.summary.split_whitespace().collect::<Vec<_>>().join(" ");
+            if summary.is_empty() {
+                return ToolResult::error("summary must not be empty".to_string());
+            }
+            let summary: String = summary.chars().take(MAX_FINDING_CHARS).collect();
+
+            println!("Tool: Noting '{}'", summary);
+
+            let count = match RESEARCH_RUN.lock() {
+                Ok(mut run) => {
+                    run.findings.push(summary);
+                    run.findings.len()
+                }
+                Err(e) => return ToolResult::error(format!("Notes unavailable: {}", e)),
+            };
+
+            ToolResult::success(json!({
+                "status": "noted",
+                "findings": count
+            }))
+        })
+    )
+}
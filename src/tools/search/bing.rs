@@ -0,0 +1,84 @@
+use super::{strip_tags, SearchProvider, SearchResult};
+use crate::tools::http;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+const BING_ENDPOINT: &str = "https://api.bing.microsoft.com/v7.0/search";
+/// Most results the API returns per request
+const BING_MAX_COUNT: usize = 50;
+
+/// Bing Web Search API; the endpoint can point at a regional or proxy URL
+pub struct BingProvider {
+    endpoint: String,
+    api_key: String,
+}
+
+impl BingProvider {
+    pub fn new(endpoint: &str, api_key: &str) -> Self {
+        let endpoint = if endpoint.is_empty() { BING_ENDPOINT } else { endpoint };
+        Self {
+            endpoint: endpoint.to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BingResponse {
+    web_pages: Option<BingWebPages>,
+}
+
+#[derive(Deserialize)]
+struct BingWebPages {
+    #[serde(default)]
+    value: Vec<BingResult>,
+}
+
+#[derive(Deserialize)]
+struct BingResult {
+    #[serde(default)]
+    name: String,
+    url: String,
+    #[serde(default)]
+    snippet: String,
+}
+
+#[async_trait]
+impl SearchProvider for BingProvider {
+    fn name(&self) -> &'static str {
+        "Bing"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>, String> {
+        let url = format!(
+            "{}?q={}&count={}",
+            self.endpoint,
+            urlencoding::encode(query),
+            max_results.min(BING_MAX_COUNT)
+        );
+        let request = http::client()
+            .get(&url)
+            .header("Ocp-Apim-Subscription-Key", &self.api_key);
+        let resp = http::send(request).await?;
+        if !resp.status().is_success() {
+            return Err(format!("Bing request failed with status: {}", resp.status()));
+        }
+        let body: BingResponse = resp
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected Bing response: {}", e))?;
+        Ok(body
+            .web_pages
+            .map(|pages| pages.value)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| SearchResult {
+                title: strip_tags(&r.name),
+                url: r.url,
+                snippet: strip_tags(&r.snippet),
+                rank: 0,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,81 @@
+use super::{strip_tags, SearchProvider, SearchResult};
+use crate::tools::http;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+/// Most results the API returns per request
+const BRAVE_MAX_COUNT: usize = 20;
+
+/// Brave Search web API
+pub struct BraveProvider {
+    api_key: String,
+}
+
+impl BraveProvider {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BraveResponse {
+    web: Option<BraveWeb>,
+}
+
+#[derive(Deserialize)]
+struct BraveWeb {
+    #[serde(default)]
+    results: Vec<BraveResult>,
+}
+
+#[derive(Deserialize)]
+struct BraveResult {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[async_trait]
+impl SearchProvider for BraveProvider {
+    fn name(&self) -> &'static str {
+        "Brave"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>, String> {
+        let url = format!(
+            "{}?q={}&count={}",
+            BRAVE_ENDPOINT,
+            urlencoding::encode(query),
+            max_results.min(BRAVE_MAX_COUNT)
+        );
+        let request = http::client()
+            .get(&url)
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key);
+        let resp = http::send(request).await?;
+        if !resp.status().is_success() {
+            return Err(format!("Brave request failed with status: {}", resp.status()));
+        }
+        let body: BraveResponse = resp
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected Brave response: {}", e))?;
+        Ok(body
+            .web
+            .map(|web| web.results)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| SearchResult {
+                title: strip_tags(&r.title),
+                url: r.url,
+                snippet: strip_tags(&r.description),
+                rank: 0,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,161 @@
+use super::{SearchProvider, SearchResult};
+use crate::tools::http;
+use async_trait::async_trait;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+
+/// Scrapes the HTML-only DuckDuckGo results page
+pub struct DuckDuckGoProvider;
+
+#[async_trait]
+impl SearchProvider for DuckDuckGoProvider {
+    fn name(&self) -> &'static str {
+        "DuckDuckGo"
+    }
+
+    async fn search(&self, query: &str, _max_results: usize) -> Result<Vec<SearchResult>, String> {
+        let encoded_query = urlencoding::encode(query);
+        let url = format!("https://html.duckduckgo.com/html/?q={}", encoded_query);
+
+        let resp = http::send_to_duckduckgo(http::client().get(&url)).await?;
+        if !resp.status().is_success() {
+            return Err(format!("Request failed with status: {}", resp.status()));
+        }
+        let body = resp.text().await.map_err(|e| format!("Failed to read body: {}", e))?;
+        Ok(parse_results(&body))
+    }
+}
+
+/// Parse a DuckDuckGo HTML results page. All known result layouts are tried
+/// (so the same hit may appear more than once) and ads are skipped.
+pub fn parse_results(html: &str) -> Vec<SearchResult> {
+    let document = Html::parse_document(html);
+
+    // Try multiple selectors for different DDG layouts
+    let result_selectors = [
+        ".result__body",
+        ".result",
+        ".web-result",
+    ];
+    let (Ok(title_sel), Ok(snippet_sel)) = (
+        Selector::parse(".result__title a, .result__a, a"),
+        Selector::parse(".result__snippet, .result-snippet"),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut results = Vec::new();
+    for selector_str in result_selectors {
+        let Ok(result_selector) = Selector::parse(selector_str) else { continue };
+        for element in document.select(&result_selector) {
+            if is_ad(element) {
+                continue;
+            }
+            let Some(link) = element.select(&title_sel).next() else { continue };
+            let Some(url) = link.value().attr("href").and_then(resolve_link) else { continue };
+            results.push(SearchResult {
+                title: clean_text(link),
+                url,
+                snippet: element.select(&snippet_sel).next().map(clean_text).unwrap_or_default(),
+                rank: 0,
+            });
+        }
+    }
+    results
+}
+
+/// Sponsored results are marked on the result or one of its ancestors
+fn is_ad(element: ElementRef) -> bool {
+    std::iter::once(element)
+        .chain(element.ancestors().filter_map(ElementRef::wrap))
+        .filter_map(|e| e.value().attr("class"))
+        .any(|class| class.split_whitespace().any(|c| c == "result--ad" || c == "result--ad--small"))
+}
+
+fn clean_text(element: ElementRef) -> String {
+    element.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Turn a result link into the real target. DuckDuckGo wraps targets in
+/// `//duckduckgo.com/l/?uddg=<encoded url>` redirects; its ad links
+/// (`/y.js`) and other internal links are dropped.
+pub fn resolve_link(href: &str) -> Option<String> {
+    let absolute = if href.starts_with("//") {
+        format!("https:{}", href)
+    } else if href.starts_with('/') {
+        format!("https://duckduckgo.com{}", href)
+    } else {
+        href.to_string()
+    };
+    let url = Url::parse(&absolute).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?;
+    if host == "duckduckgo.com" || host.ends_with(".duckduckgo.com") {
+        if url.path() != "/l/" {
+            return None;
+        }
+        let target = url.query_pairs().find(|(k, _)| k == "uddg")?.1.into_owned();
+        return resolve_link(&target);
+    }
+    Some(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::search::rank_results;
+
+    const PAGE: &str = include_str!("../../../tests/fixtures/search/duckduckgo.html");
+
+    #[test]
+    fn result_links_are_unwrapped() {
+        assert_eq!(
+            resolve_link("//duckduckgo.com/l/?uddg=https%3A%2F%2Fexample.com%2Fa%3Fb%3D1&rut=x").as_deref(),
+            Some("https://example.com/a?b=1")
+        );
+        assert_eq!(resolve_link("https://example.com/page").as_deref(), Some("https://example.com/page"));
+        assert_eq!(resolve_link("https://duckduckgo.com/y.js?ad_domain=shop.example&u3=x"), None);
+        assert_eq!(resolve_link("/?q=more+results"), None);
+        assert_eq!(resolve_link("https://html.duckduckgo.com/html/?q=x"), None);
+        // A wrapped link must still lead somewhere real
+        assert_eq!(resolve_link("//duckduckgo.com/l/?uddg=javascript%3Aalert(1)"), None);
+        assert_eq!(resolve_link("//duckduckgo.com/l/?rut=x"), None);
+    }
+
+    #[test]
+    fn ads_are_found_through_their_ancestors() {
+        let page = Html::parse_document(PAGE);
+        let body = Selector::parse(".result__body").unwrap();
+        let ads: Vec<bool> = page.select(&body).map(is_ad).collect();
+        assert_eq!(ads, vec![true, false, false, false, false]);
+    }
+
+    #[test]
+    fn saved_results_page_is_parsed() {
+        let results = parse_results(PAGE);
+        assert!(results.iter().all(|r| !r.url.contains("duckduckgo.com") && !r.url.contains("cheap-panels")));
+        // `.result__body`, `.result` and `.web-result` all match each organic hit
+        assert_eq!(results.len(), 9);
+
+        let ranked = rank_results(results, 10);
+        let found: Vec<(usize, &str, &str)> = ranked
+            .iter()
+            .map(|r| (r.rank, r.url.as_str(), r.title.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "https://www.iea.org/reports/solar-pv", "Solar PV - Analysis - IEA"),
+                (
+                    2,
+                    "https://ourworldindata.org/cheap-renewables-growth?ref=ddg#prices",
+                    "Why did renewables become so cheap so fast? - Our World in Data"
+                ),
+                (3, "https://www.energy.gov/eere/solar/solar-energy-cost", "Solar Energy Cost | Department of Energy"),
+            ]
+        );
+        assert_eq!(ranked[0].snippet, "Solar PV generation increased by a record 270 TWh in 2022, up 26% on 2021.");
+    }
+}
@@ -0,0 +1,102 @@
+use super::{SearchProvider, SearchResult};
+use crate::tools::cache::normalize_key;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// Key in a fixture file whose results answer any query without its own entry
+const FALLBACK_KEY: &str = "*";
+
+/// Canned results read from a JSON file mapping queries to result lists:
+///
+/// ```json
+/// { "solar power": [{"title": "...", "url": "...", "snippet": "..."}], "*": [] }
+/// ```
+///
+/// Queries match case-insensitively with whitespace collapsed. The file is
+/// read on every search so it can be edited while the app runs.
+pub struct FixtureProvider {
+    path: String,
+}
+
+impl FixtureProvider {
+    pub fn new(path: &str) -> Self {
+        Self { path: path.to_string() }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for FixtureProvider {
+    fn name(&self) -> &'static str {
+        "Fixture"
+    }
+
+    fn cacheable(&self) -> bool {
+        false
+    }
+
+    async fn search(&self, query: &str, _max_results: usize) -> Result<Vec<SearchResult>, String> {
+        let text = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| format!("Cannot read search fixture '{}': {}", self.path, e))?;
+        let fixture: HashMap<String, Vec<SearchResult>> = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid search fixture '{}': {}", self.path, e))?;
+        let wanted = normalize_key(query);
+        let results = fixture
+            .iter()
+            .find(|(key, _)| normalize_key(key) == wanted)
+            .or_else(|| fixture.get_key_value(FALLBACK_KEY))
+            .map(|(_, results)| results.clone())
+            .unwrap_or_default();
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/search/results.json");
+
+    async fn titles(provider: &FixtureProvider, query: &str) -> Vec<String> {
+        let results = provider.search(query, 5).await.unwrap();
+        results.into_iter().map(|r| r.title).collect()
+    }
+
+    #[tokio::test]
+    async fn queries_match_ignoring_case_and_spacing() {
+        let provider = FixtureProvider::new(FIXTURE);
+        assert_eq!(
+            titles(&provider, "  battery   STORAGE ").await,
+            vec!["Battery prices hit a record low"]
+        );
+        // Results come back as written; ranking is up to web_search
+        assert_eq!(provider.search("solar power costs", 2).await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn unknown_queries_use_the_fallback_key() {
+        let provider = FixtureProvider::new(FIXTURE);
+        assert_eq!(titles(&provider, "tidal energy").await, vec!["Fallback result"]);
+    }
+
+    #[tokio::test]
+    async fn no_match_and_no_fallback_is_empty() {
+        let path = std::env::temp_dir().join(format!("search-fixture-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"solar": []}"#).unwrap();
+        let provider = FixtureProvider::new(path.to_str().unwrap());
+        assert!(provider.search("wind", 5).await.unwrap().is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn unreadable_fixtures_are_errors() {
+        let missing = FixtureProvider::new("/nonexistent/search-fixture.json");
+        assert!(missing.search("solar", 5).await.unwrap_err().contains("Cannot read"));
+
+        let path = std::env::temp_dir().join(format!("search-fixture-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "[1, 2, 3]").unwrap();
+        let invalid = FixtureProvider::new(path.to_str().unwrap());
+        assert!(invalid.search("solar", 5).await.unwrap_err().contains("Invalid search fixture"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,274 @@
+//! The `web_search` tool and the search backends behind it. The backend is
+//! chosen by `AgentConfig::search`; every backend returns the same ranked
+//! `SearchResult`s so the model sees one tool whatever is configured.
+
+mod bing;
+mod brave;
+mod duckduckgo;
+mod fixture;
+mod searxng;
+
+use crate::model::{SearchBackend, SearchConfig};
+use crate::server_state::AGENT_CONFIG;
+use crate::tools::cache;
+use crate::tools::trace::traced_tool;
+use async_trait::async_trait;
+use radkit::tools::{FunctionTool, ToolResult};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+
+pub use bing::BingProvider;
+pub use brave::BraveProvider;
+pub use duckduckgo::DuckDuckGoProvider;
+pub use fixture::FixtureProvider;
+pub use searxng::SearxngProvider;
+
+const DEFAULT_MAX_RESULTS: usize = 5;
+const MAX_MAX_RESULTS: usize = 20;
+/// Cache namespace of search results
+const CACHE_NAMESPACE: &str = "search";
+
+/// One web search hit; `rank` starts at 1
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+    #[serde(default)]
+    pub rank: usize,
+}
+
+/// A web search backend
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Shown in logs and tool output
+    fn name(&self) -> &'static str;
+
+    /// Whether results may be served from the on-disk cache
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    /// Results for `query` in the backend's order. May return more than
+    /// `max_results`; `web_search` dedupes, cuts and ranks them.
+    async fn search(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>, String>;
+}
+
+/// Build the provider for the configured backend
+pub fn provider_for(config: &SearchConfig) -> Result<Box<dyn SearchProvider>, String> {
+    let endpoint = config.endpoint.trim();
+    let api_key = config.api_key.trim();
+    if config.backend.needs_api_key() && api_key.is_empty() {
+        return Err(format!("{} needs a search API key in the settings", config.backend.display_name()));
+    }
+    Ok(match config.backend {
+        SearchBackend::DuckDuckGo => Box::new(DuckDuckGoProvider),
+        SearchBackend::SearxNG if endpoint.is_empty() => {
+            return Err("SearxNG needs the instance URL as the search endpoint".to_string())
+        }
+        SearchBackend::SearxNG => Box::new(SearxngProvider::new(endpoint)),
+        SearchBackend::Brave => Box::new(BraveProvider::new(api_key)),
+        SearchBackend::Bing => Box::new(BingProvider::new(endpoint, api_key)),
+        SearchBackend::Fixture if endpoint.is_empty() => {
+            return Err("The fixture backend needs a JSON file path as the search endpoint".to_string())
+        }
+        SearchBackend::Fixture => Box::new(FixtureProvider::new(endpoint)),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct WebSearchArgs {
+    /// The search query to look up
+    query: String,
+    /// Number of results to return (default 5, at most 20)
+    max_results: Option<usize>,
+}
+
+pub fn make_search_tool() -> FunctionTool {
+    traced_tool(
+        "web_search",
+        "Search the web to find information about any topic. Returns a ranked list of results, each with title, url and snippet; pass a url to fetch_page to read it and cite it. max_results sets how many results to return (default 5, at most 20).",
+        |args| Box::pin(async move {
+            let args_value = serde_json::Value::Object(args.into_iter().collect());
+
+            let args: WebSearchArgs = match serde_json::from_value(args_value) {
+                Ok(a) => a,
+                Err(e) => return ToolResult::error(format!("Invalid arguments: {}", e)),
+            };
+            let max_results = args.max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, MAX_MAX_RESULTS);
+
+            let config = AGENT_CONFIG.lock().map(|c| c.search.clone()).unwrap_or_default();
+            let provider = match provider_for(&config) {
+                Ok(provider) => provider,
+                Err(e) => return ToolResult::error(e),
+            };
+
+            let cache_key = format!("{}|{}|{}", provider.name(), max_results, cache::normalize_key(&args.query));
+            let cached = if provider.cacheable() {
+                cache::get::<Vec<SearchResult>>(CACHE_NAMESPACE, &cache_key, cache::SEARCH_TTL).await
+            } else {
+                None
+            };
+            if let Some(hit) = cached {
+                println!("Tool: Search cache hit for '{}' ({} results)", args.query, hit.value.len());
+                return ToolResult::success(json!({
+                    "query": args.query,
+                    "provider": provider.name(),
+                    "result_count": hit.value.len(),
+                    "results": hit.value,
+                    "cached": true,
+                    "cache_age_secs": hit.age.as_secs()
+                }));
+            }
+
+            println!("Tool: Searching {} for '{}'", provider.name(), args.query);
+
+            let results = match provider.search(&args.query, max_results).await {
+                Ok(results) => rank_results(results, max_results),
+                Err(e) => return ToolResult::error(e),
+            };
+
+            println!("Tool: Search found {} results", results.len());
+            // Empty answers are often a blocked or changed page; don't keep them
+            if !results.is_empty() && provider.cacheable() {
+                cache::put(CACHE_NAMESPACE, &cache_key, &results).await;
+            }
+
+            ToolResult::success(json!({
+                "query": args.query,
+                "provider": provider.name(),
+                "result_count": results.len(),
+                "results": results,
+                "cached": false
+            }))
+        })
+    )
+}
+
+/// Drop empty and repeated results, keep the first `max_results` and number them
+pub fn rank_results(results: Vec<SearchResult>, max_results: usize) -> Vec<SearchResult> {
+    let mut seen = HashSet::new();
+    results
+        .into_iter()
+        .filter(|r| !r.url.is_empty())
+        .filter(|r| !r.title.is_empty() || !r.snippet.is_empty())
+        .filter(|r| seen.insert(dedupe_key(&r.url)))
+        .take(max_results)
+        .enumerate()
+        .map(|(i, r)| SearchResult { rank: i + 1, ..r })
+        .collect()
+}
+
+/// Results that differ only in scheme, `www.`, fragment or a trailing slash are the same page
+fn dedupe_key(url: &str) -> String {
+    let Ok(mut url) = Url::parse(url) else {
+        return url.to_string();
+    };
+    url.set_fragment(None);
+    let host = url.host_str().unwrap_or_default().trim_start_matches("www.").to_lowercase();
+    let path = url.path().trim_end_matches('/').to_string();
+    match url.query() {
+        Some(query) => format!("{}{}?{}", host, path, query),
+        None => format!("{}{}", host, path),
+    }
+}
+
+/// Plain text from API snippets that carry `<strong>`-style highlighting
+fn strip_tags(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    plain
+        .replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radkit::tools::{BaseTool, DefaultExecutionState, ToolContext};
+    use serde_json::Value;
+
+    #[test]
+    fn rank_results_dedupes_and_numbers() {
+        let result = |url: &str| SearchResult {
+            title: "Title".to_string(),
+            url: url.to_string(),
+            snippet: String::new(),
+            rank: 0,
+        };
+        let ranked = rank_results(
+            vec![
+                result("https://example.com/a"),
+                result("http://www.example.com/a/#top"),
+                result(""),
+                result("https://example.com/b?page=2"),
+                result("https://example.com/c"),
+            ],
+            2,
+        );
+        let urls: Vec<_> = ranked.iter().map(|r| (r.rank, r.url.as_str())).collect();
+        assert_eq!(urls, vec![(1, "https://example.com/a"), (2, "https://example.com/b?page=2")]);
+    }
+
+    #[test]
+    fn strip_tags_removes_highlighting() {
+        assert_eq!(strip_tags("<strong>Solar</strong> &amp; wind\n  power"), "Solar & wind power");
+    }
+
+    #[tokio::test]
+    async fn web_search_runs_through_the_fixture_backend() {
+        if let Ok(mut config) = AGENT_CONFIG.lock() {
+            config.search = SearchConfig {
+                backend: SearchBackend::Fixture,
+                endpoint: concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/search/results.json").to_string(),
+                api_key: String::new(),
+            };
+        }
+        let tool = make_search_tool();
+        let state = DefaultExecutionState::new();
+        let context = ToolContext::new(&state);
+        let args = [
+            ("query".to_string(), json!("Solar power costs")),
+            ("max_results".to_string(), json!(3)),
+        ];
+        let (success, data, error) = tool.run_async(args.into_iter().collect(), &context).await.into_parts();
+        assert!(success, "web_search failed: {:?}", error);
+
+        assert_eq!(data["provider"], "Fixture");
+        assert_eq!(data["cached"], false);
+        assert_eq!(data["result_count"], 3);
+        // The mirror is a duplicate and the result without a url is dropped
+        let results: Vec<(u64, &str)> = data["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| (r["rank"].as_u64().unwrap(), r["url"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                (1, "https://ourworldindata.org/grapher/solar-pv-prices"),
+                (2, "https://www.irena.org/Publications/2024/Sep/Renewable-Power-Generation-Costs-in-2023"),
+                (3, "https://www.lazard.com/research-insights/levelized-cost-of-energyplus/"),
+            ]
+        );
+        assert!(data["results"][0]["snippet"].as_str().is_some_and(|s| s.contains("$0.30")));
+        assert_eq!(data.get("cache_age_secs"), None::<&Value>);
+    }
+}
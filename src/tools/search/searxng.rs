@@ -0,0 +1,67 @@
+use super::{SearchProvider, SearchResult};
+use crate::tools::http;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// Queries a SearxNG instance's JSON API (`format=json` must be enabled in
+/// the instance settings)
+pub struct SearxngProvider {
+    endpoint: String,
+}
+
+impl SearxngProvider {
+    pub fn new(endpoint: &str) -> Self {
+        // A self-hosted instance often runs on the local network
+        http::trust_host(endpoint);
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Deserialize)]
+struct SearxngResult {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+#[async_trait]
+impl SearchProvider for SearxngProvider {
+    fn name(&self) -> &'static str {
+        "SearxNG"
+    }
+
+    async fn search(&self, query: &str, _max_results: usize) -> Result<Vec<SearchResult>, String> {
+        let url = format!("{}/search?q={}&format=json", self.endpoint, urlencoding::encode(query));
+        let resp = http::send(http::client().get(&url)).await?;
+        if !resp.status().is_success() {
+            return Err(format!(
+                "SearxNG request failed with status: {} (is format=json enabled?)",
+                resp.status()
+            ));
+        }
+        let body: SearxngResponse = resp
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected SearxNG response: {}", e))?;
+        Ok(body
+            .results
+            .into_iter()
+            .map(|r| SearchResult {
+                title: r.title.trim().to_string(),
+                url: r.url,
+                snippet: r.content.trim().to_string(),
+                rank: 0,
+            })
+            .collect())
+    }
+}
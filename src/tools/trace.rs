@@ -0,0 +1,47 @@
+//! Tool construction with transcript recording: every call's arguments,
+//! result and timing go into the current run's transcript. Once the run is
+//! at its limits, further calls are refused; they are recorded as refused
+//! and do not count towards the run's usage.
+
+use crate::transcript::store;
+use radkit::tools::{FunctionTool, ToolResult};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Instant;
+
+pub type ToolFuture = Pin<Box<dyn Future<Output = ToolResult> + Send>>;
+
+/// Like `FunctionTool::new`, but the handler's calls are recorded in the
+/// transcript and held to the run's limits
+pub fn traced_tool<F>(name: &'static str, description: &str, handler: F) -> FunctionTool
+where
+    F: Fn(HashMap<String, Value>) -> ToolFuture + Send + Sync + 'static,
+{
+    FunctionTool::new(name, description, move |args, _ctx| {
+        let recorded = Value::Object(args.iter().map(|(k, v)| (k.clone(), v.clone())).collect::<Map<_, _>>());
+        let started_at = store::now_ms();
+        let started = Instant::now();
+        let refusal = store::refuse_call();
+        let refused = refusal.is_some();
+        let call: ToolFuture = match refusal {
+            Some(reason) => {
+                println!("Tool: Refusing {} call, run limit reached ({})", name, reason);
+                Box::pin(async move {
+                    ToolResult::error(format!(
+                        "Run limit reached: {}. Stop calling tools and give your final answer.",
+                        reason
+                    ))
+                })
+            }
+            None => handler(args.into_iter().collect()),
+        };
+        Box::pin(async move {
+            let result = call.await;
+            let outcome = serde_json::to_value(&result).unwrap_or(Value::Null);
+            store::record_tool_call(name, recorded, outcome, started_at, started.elapsed().as_millis() as u64, refused);
+            result
+        })
+    })
+}
@@ -0,0 +1,846 @@
+//! Agent run transcripts: the prompt, every model message with the usage its
+//! provider reported, every tool call with its arguments, result and timing,
+//! and the final answer, kept per research run so odd drawings can be traced
+//! back to what the agent saw and did.
+//!
+//! The server records the running transcript in memory and writes it to a
+//! JSON file when the run ends; the UI lists and opens them through the
+//! server functions below.
+//!
+//! Usage comes from the token counts the provider reports for each model
+//! message and is checked against the config's [`RunLimits`] before every
+//! model turn; a run that reaches a limit gets one last turn to answer.
+
+use crate::model::{AgentProvider, RunLimits};
+use dioxus::fullstack::response::Response;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Rough characters per LLM token, used to estimate usage from text when a
+/// provider does not report it and to turn token budgets into text lengths
+pub const CHARS_PER_TOKEN: usize = 4;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum RunStatus {
+    #[default]
+    Running,
+    Finished,
+    Failed(String),
+    /// Stopped at one of the run limits, with or without an answer
+    LimitReached(String),
+}
+
+/// One step of a run. Offsets are milliseconds since the run started.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TranscriptEntry {
+    Prompt { text: String },
+    ToolCall {
+        name: String,
+        args: Value,
+        result: Value,
+        at_ms: u64,
+        duration_ms: u64,
+        /// Refused because the run was at its limits; not counted in usage
+        #[serde(default)]
+        refused: bool,
+    },
+    /// One reply from the model: its text, the tools it asked to call and the
+    /// token counts its provider reported for the turn, if any
+    ModelMessage {
+        text: String,
+        tool_calls: Vec<String>,
+        input_tokens: Option<u32>,
+        output_tokens: Option<u32>,
+        at_ms: u64,
+        duration_ms: u64,
+    },
+    Answer { text: String, at_ms: u64 },
+    Error { message: String, at_ms: u64 },
+}
+
+/// What a run used: its model turns, the tool calls that ran and the token
+/// counts the provider reported for each turn. Turns without reported usage
+/// are estimated from text length, re-reading the conversation so far.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Usage {
+    /// Requests made to the model
+    #[serde(default)]
+    pub iterations: usize,
+    /// Tool calls that ran; refused calls are left out
+    pub tool_calls: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Cost in US dollars; zero when no prices are configured
+    #[serde(default)]
+    pub cost: f64,
+    /// Some token counts are estimates because the provider left them out
+    #[serde(default)]
+    pub estimated: bool,
+}
+
+impl Usage {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// One-line account like `3 turns · 12 tool calls · 45k tokens · $0.12`,
+    /// with `~` on estimated figures; the cost is left out when no prices are
+    /// configured
+    pub fn describe(&self) -> String {
+        let tokens = match self.total_tokens() {
+            t if t >= 10_000 => format!("{}k", t / 1000),
+            t => t.to_string(),
+        };
+        let approx = if self.estimated { "~" } else { "" };
+        let mut text = format!(
+            "{} turns · {} tool calls · {}{} tokens",
+            self.iterations, self.tool_calls, approx, tokens
+        );
+        if self.cost > 0.0 {
+            text.push_str(&format!(" · {}${:.2}", approx, self.cost));
+        }
+        text
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Transcript {
+    pub id: Uuid,
+    pub topic: String,
+    pub provider: AgentProvider,
+    pub model: String,
+    /// Unix time in milliseconds
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub status: RunStatus,
+    /// Budget the run was held to
+    #[serde(default)]
+    pub limits: RunLimits,
+    /// The limit the run reached, after which it was given its last turn
+    #[serde(default)]
+    pub stopped_for: Option<String>,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+/// Listing entry for the transcript viewer
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptSummary {
+    pub id: Uuid,
+    pub topic: String,
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub status: RunStatus,
+    pub usage: Usage,
+}
+
+/// What the run's limits leave for the next model turn
+#[cfg(feature = "server")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum TurnBudget {
+    /// Within the limits
+    Open,
+    /// A limit has been reached; this is the last turn, to give the answer
+    Last(String),
+    /// The last turn has been taken
+    Spent(String),
+}
+
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Format a Unix time in milliseconds as `YYYY-MM-DD HH:MM UTC`
+pub fn format_time(unix_ms: u64) -> String {
+    let secs = unix_ms / 1000;
+    let (days, rest) = ((secs / 86_400) as i64, secs % 86_400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60
+    )
+}
+
+impl Transcript {
+    #[cfg(feature = "server")]
+    pub fn new(topic: &str, provider: AgentProvider, model: &str, started_at: u64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            topic: topic.to_string(),
+            provider,
+            model: model.to_string(),
+            started_at,
+            duration_ms: 0,
+            status: RunStatus::Running,
+            limits: RunLimits::default(),
+            stopped_for: None,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn usage(&self) -> Usage {
+        let mut usage = Usage::default();
+        // Estimated tokens the model has seen so far, for turns whose
+        // provider reported no usage; every turn reads all of them again
+        let mut context = 0;
+        for entry in &self.entries {
+            match entry {
+                TranscriptEntry::Prompt { text } => context += estimate_tokens(text),
+                TranscriptEntry::ModelMessage { text, input_tokens, output_tokens, .. } => {
+                    usage.iterations += 1;
+                    let written = estimate_tokens(text);
+                    usage.input_tokens += input_tokens.map_or(context, u64::from);
+                    usage.output_tokens += output_tokens.map_or(written, u64::from);
+                    usage.estimated |= input_tokens.is_none() || output_tokens.is_none();
+                    context += written;
+                }
+                TranscriptEntry::ToolCall { args, result, refused, .. } => {
+                    if !refused {
+                        usage.tool_calls += 1;
+                    }
+                    context += estimate_tokens(&args.to_string()) + estimate_tokens(&result.to_string());
+                }
+                TranscriptEntry::Answer { .. } | TranscriptEntry::Error { .. } => {}
+            }
+        }
+        usage.cost = self.limits.cost(usage.input_tokens, usage.output_tokens);
+        usage
+    }
+
+    /// The limit the run has reached, if any
+    #[cfg(feature = "server")]
+    pub fn reached_limit(&self) -> Option<String> {
+        let usage = self.usage();
+        let limits = &self.limits;
+        if limits.max_iterations > 0 && usage.iterations >= limits.max_iterations {
+            Some(format!("the limit of {} model turns", limits.max_iterations))
+        } else if limits.max_tool_calls > 0 && usage.tool_calls >= limits.max_tool_calls {
+            Some(format!("the limit of {} tool calls", limits.max_tool_calls))
+        } else if limits.max_tokens > 0 && usage.total_tokens() >= limits.max_tokens {
+            Some(format!("the limit of {} tokens", limits.max_tokens))
+        } else if limits.max_cost > 0.0 && usage.cost >= limits.max_cost {
+            Some(format!("the limit of ${:.2}", limits.max_cost))
+        } else {
+            None
+        }
+    }
+
+    /// Check the limits before a model turn. The first turn that finds a
+    /// limit reached becomes the run's last; any turn after it is spent.
+    #[cfg(feature = "server")]
+    pub fn begin_turn(&mut self) -> TurnBudget {
+        if let Some(reason) = &self.stopped_for {
+            return TurnBudget::Spent(reason.clone());
+        }
+        match self.reached_limit() {
+            Some(reason) => {
+                self.stopped_for = Some(reason.clone());
+                TurnBudget::Last(reason)
+            }
+            None => TurnBudget::Open,
+        }
+    }
+
+    /// Why a tool call must be refused: the run is on its last turn, or has
+    /// used up its tool calls
+    #[cfg(feature = "server")]
+    pub fn refuse_call(&self) -> Option<String> {
+        if let Some(reason) = &self.stopped_for {
+            return Some(reason.clone());
+        }
+        let limits = &self.limits;
+        if limits.max_tool_calls > 0 && self.usage().tool_calls >= limits.max_tool_calls {
+            Some(format!("the limit of {} tool calls", limits.max_tool_calls))
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "server")]
+    pub fn summary(&self) -> TranscriptSummary {
+        TranscriptSummary {
+            id: self.id,
+            topic: self.topic.clone(),
+            started_at: self.started_at,
+            duration_ms: self.duration_ms,
+            status: self.status.clone(),
+            usage: self.usage(),
+        }
+    }
+}
+
+// ============================================================================
+// SERVER-SIDE RECORDING
+// ============================================================================
+
+#[cfg(feature = "server")]
+pub mod store {
+    use super::*;
+    use crate::server_state::CURRENT_TRANSCRIPT;
+    use async_trait::async_trait;
+    use radkit::errors::AgentResult;
+    use radkit::models::{BaseLlm, Content, Event, LlmResponse, Thread, TokenUsage};
+    use radkit::tools::BaseToolset;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Overrides where finished transcripts are saved
+    const TRANSCRIPT_DIR_VAR: &str = "TRANSCRIPT_DIR";
+    const DEFAULT_TRANSCRIPT_DIR: &str = ".transcripts";
+    /// How many past runs the viewer lists
+    const MAX_LISTED: usize = 50;
+
+    fn transcript_dir() -> PathBuf {
+        std::env::var_os(TRANSCRIPT_DIR_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_TRANSCRIPT_DIR))
+    }
+
+    fn transcript_path(id: Uuid) -> PathBuf {
+        transcript_dir().join(format!("{}.json", id))
+    }
+
+    pub fn now_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default()
+    }
+
+    /// Start recording a run, beginning with its prompt
+    pub fn begin(topic: &str, provider: AgentProvider, model: &str, limits: RunLimits, prompt: &str) {
+        let mut transcript = Transcript::new(topic, provider, model, now_ms());
+        transcript.limits = limits;
+        transcript.entries.push(TranscriptEntry::Prompt {
+            text: prompt.to_string(),
+        });
+        if let Ok(mut current) = CURRENT_TRANSCRIPT.lock() {
+            *current = Some(transcript);
+        }
+    }
+
+    /// Milliseconds since the current run started
+    fn offset(transcript: &Transcript, at: u64) -> u64 {
+        at.saturating_sub(transcript.started_at)
+    }
+
+    pub fn record_tool_call(name: &str, args: Value, result: Value, started: u64, duration_ms: u64, refused: bool) {
+        if let Ok(mut current) = CURRENT_TRANSCRIPT.lock() {
+            if let Some(transcript) = current.as_mut() {
+                let at_ms = offset(transcript, started);
+                transcript.entries.push(TranscriptEntry::ToolCall {
+                    name: name.to_string(),
+                    args,
+                    result,
+                    at_ms,
+                    duration_ms,
+                    refused,
+                });
+            }
+        }
+    }
+
+    /// The transcript entry for one model reply, requested `at_ms` into the run
+    fn model_message(response: &LlmResponse, at_ms: u64, duration_ms: u64) -> TranscriptEntry {
+        let content = response.content();
+        let usage = response.usage();
+        TranscriptEntry::ModelMessage {
+            text: content.joined_texts().unwrap_or_default(),
+            tool_calls: content.tool_calls().iter().map(|call| call.name().to_string()).collect(),
+            input_tokens: usage.input_tokens_opt(),
+            output_tokens: usage.output_tokens_opt(),
+            at_ms,
+            duration_ms,
+        }
+    }
+
+    /// The run's model, wrapped so every reply it gives is recorded in the
+    /// transcript (the worker itself drops the usage the provider reports)
+    /// and each turn is held to the run's limits. The turn that finds a limit
+    /// reached is told to answer now; if the model still asks for tools
+    /// there, their calls are refused and the run ends without asking it again.
+    pub struct RecordedLlm<L> {
+        inner: L,
+        transcript: &'static Mutex<Option<Transcript>>,
+    }
+
+    impl<L: BaseLlm> RecordedLlm<L> {
+        /// Record into the current run's transcript
+        pub fn new(inner: L) -> Self {
+            Self::recording_into(inner, &CURRENT_TRANSCRIPT)
+        }
+
+        fn recording_into(inner: L, transcript: &'static Mutex<Option<Transcript>>) -> Self {
+            Self { inner, transcript }
+        }
+    }
+
+    #[async_trait]
+    impl<L: BaseLlm> BaseLlm for RecordedLlm<L> {
+        fn model_name(&self) -> &str {
+            self.inner.model_name()
+        }
+
+        async fn generate_content(
+            &self,
+            thread: Thread,
+            toolset: Option<Arc<dyn BaseToolset>>,
+        ) -> AgentResult<LlmResponse> {
+            let budget = self
+                .transcript
+                .lock()
+                .ok()
+                .and_then(|mut current| current.as_mut().map(Transcript::begin_turn))
+                .unwrap_or(TurnBudget::Open);
+            let thread = match budget {
+                TurnBudget::Open => thread,
+                TurnBudget::Last(reason) => {
+                    println!("Agent: Run limit reached ({}), asking for the final answer", reason);
+                    // The tools stay declared since the thread already holds calls to them
+                    thread.add_event(Event::user(format!(
+                        "Run limit reached: {}. Do not call any more tools; give your final answer now.",
+                        reason
+                    )))
+                }
+                TurnBudget::Spent(reason) => {
+                    println!("Agent: Model kept calling tools after its last turn ({}), ending the run", reason);
+                    return Ok(LlmResponse::new(
+                        Content::from_text("No final answer was given before the run stopped."),
+                        TokenUsage::empty(),
+                    ));
+                }
+            };
+            let started = now_ms();
+            let response = self.inner.generate_content(thread, toolset).await?;
+            let duration_ms = now_ms().saturating_sub(started);
+            if let Ok(mut current) = self.transcript.lock() {
+                if let Some(transcript) = current.as_mut() {
+                    let at_ms = offset(transcript, started);
+                    transcript.entries.push(model_message(&response, at_ms, duration_ms));
+                }
+            }
+            Ok(response)
+        }
+    }
+
+    /// Why a tool call must be refused, if the current run is at its limits
+    pub fn refuse_call() -> Option<String> {
+        let current = CURRENT_TRANSCRIPT.lock().ok()?;
+        current.as_ref()?.refuse_call()
+    }
+
+    /// Close the current run with its answer, or the failed status, save it to
+    /// disk and return its summary. A run that reached a limit keeps its
+    /// answer and is marked as stopped at that limit.
+    pub async fn finish(outcome: Result<&str, RunStatus>) -> Option<TranscriptSummary> {
+        let mut transcript = CURRENT_TRANSCRIPT.lock().ok().and_then(|mut c| c.take())?;
+        let at_ms = offset(&transcript, now_ms());
+        transcript.duration_ms = at_ms;
+        match outcome {
+            Ok(answer) => {
+                transcript.entries.push(TranscriptEntry::Answer {
+                    text: answer.to_string(),
+                    at_ms,
+                });
+                transcript.status = match &transcript.stopped_for {
+                    Some(reason) => RunStatus::LimitReached(reason.clone()),
+                    None => RunStatus::Finished,
+                };
+            }
+            Err(status) => {
+                let message = match &status {
+                    RunStatus::Failed(message) | RunStatus::LimitReached(message) => message.clone(),
+                    RunStatus::Running | RunStatus::Finished => String::new(),
+                };
+                transcript.entries.push(TranscriptEntry::Error { message, at_ms });
+                transcript.status = status;
+            }
+        }
+        let result = async {
+            tokio::fs::create_dir_all(transcript_dir()).await?;
+            let json = serde_json::to_string_pretty(&transcript)?;
+            tokio::fs::write(transcript_path(transcript.id), json).await
+        }
+        .await;
+        match result {
+            Ok(()) => println!("Agent: Saved transcript {}", transcript.id),
+            Err(e) => println!("Agent: Could not save transcript {}: {}", transcript.id, e),
+        }
+        Some(transcript.summary())
+    }
+
+    /// A saved transcript, or the one being recorded
+    pub async fn load(id: Uuid) -> Option<Transcript> {
+        let running = CURRENT_TRANSCRIPT
+            .lock()
+            .ok()
+            .and_then(|c| c.clone())
+            .filter(|t| t.id == id);
+        if running.is_some() {
+            return running;
+        }
+        let text = tokio::fs::read_to_string(transcript_path(id)).await.ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// The running transcript and the most recent saved ones, newest first
+    pub async fn list() -> Vec<TranscriptSummary> {
+        let mut summaries: Vec<TranscriptSummary> = CURRENT_TRANSCRIPT
+            .lock()
+            .ok()
+            .and_then(|c| c.as_ref().map(Transcript::summary))
+            .into_iter()
+            .collect();
+        if let Ok(mut dir) = tokio::fs::read_dir(transcript_dir()).await {
+            while let Ok(Some(file)) = dir.next_entry().await {
+                let Ok(text) = tokio::fs::read_to_string(file.path()).await else { continue };
+                if let Ok(transcript) = serde_json::from_str::<Transcript>(&text) {
+                    summaries.push(transcript.summary());
+                }
+            }
+        }
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+        summaries.truncate(MAX_LISTED);
+        summaries
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use once_cell::sync::Lazy;
+        use radkit::agent::LlmWorker;
+        use radkit::models::{Content, TokenUsage};
+        use radkit::tools::{FunctionTool, ToolCall, ToolResult};
+        use serde_json::json;
+        use std::collections::VecDeque;
+
+        /// A model that gives canned replies in order and remembers the last
+        /// message of each thread it was sent
+        struct ScriptedLlm {
+            replies: Mutex<VecDeque<LlmResponse>>,
+            last_messages: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl ScriptedLlm {
+            fn new(replies: Vec<LlmResponse>) -> Self {
+                Self { replies: Mutex::new(replies.into()), last_messages: Arc::default() }
+            }
+        }
+
+        #[async_trait]
+        impl BaseLlm for ScriptedLlm {
+            fn model_name(&self) -> &str {
+                "scripted"
+            }
+
+            async fn generate_content(
+                &self,
+                thread: Thread,
+                _toolset: Option<Arc<dyn BaseToolset>>,
+            ) -> AgentResult<LlmResponse> {
+                let last = thread.events().last().and_then(|event| event.content().joined_texts());
+                self.last_messages.lock().unwrap().push(last.unwrap_or_default());
+                let reply = self.replies.lock().unwrap().pop_front();
+                Ok(reply.expect("the model was asked for more replies than scripted"))
+            }
+        }
+
+        fn call(name: &str) -> LlmResponse {
+            let content = Content::from(vec![ToolCall::new("call-1", name, json!({ "topic": "solar" }))]);
+            LlmResponse::new(content, TokenUsage::new(1200, 40, 1240))
+        }
+
+        fn reply(text: &str, usage: TokenUsage) -> LlmResponse {
+            LlmResponse::new(Content::from_text(text), usage)
+        }
+
+        #[tokio::test]
+        async fn records_each_model_message_with_its_reported_usage() {
+            static TRANSCRIPT: Lazy<Mutex<Option<Transcript>>> = Lazy::new(|| Mutex::new(None));
+            *TRANSCRIPT.lock().unwrap() = Some(Transcript::new("Solar", AgentProvider::OpenAI, "scripted", now_ms()));
+
+            let model = ScriptedLlm::new(vec![
+                call("lookup"),
+                reply("Solar is cheap now.", TokenUsage::partial(Some(1500), None, None)),
+            ]);
+            let answer = LlmWorker::<String>::builder(RecordedLlm::recording_into(model, &TRANSCRIPT))
+                .with_tool(lookup_tool())
+                .build()
+                .run(Thread::from_user("Research solar"))
+                .await
+                .unwrap();
+            assert_eq!(answer, "Solar is cheap now.");
+
+            let transcript = TRANSCRIPT.lock().unwrap().take().unwrap();
+            let messages: Vec<_> = transcript
+                .entries
+                .iter()
+                .filter_map(|entry| match entry {
+                    TranscriptEntry::ModelMessage { text, tool_calls, input_tokens, output_tokens, .. } => {
+                        Some((text.as_str(), tool_calls.clone(), *input_tokens, *output_tokens))
+                    }
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                messages,
+                vec![
+                    ("", vec!["lookup".to_string()], Some(1200), Some(40)),
+                    ("Solar is cheap now.", Vec::new(), Some(1500), None),
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn nothing_is_recorded_outside_a_run() {
+            static TRANSCRIPT: Lazy<Mutex<Option<Transcript>>> = Lazy::new(|| Mutex::new(None));
+            let model = RecordedLlm::recording_into(
+                ScriptedLlm::new(vec![reply("Hello", TokenUsage::empty())]),
+                &TRANSCRIPT,
+            );
+            let response = model.generate_content(Thread::from_user("Hi"), None).await.unwrap();
+            assert_eq!(response.content().joined_texts().as_deref(), Some("Hello"));
+            assert!(TRANSCRIPT.lock().unwrap().is_none());
+        }
+
+        fn lookup_tool() -> FunctionTool {
+            FunctionTool::new("lookup", "Look a topic up", |_args, _ctx| {
+                Box::pin(async { ToolResult::success(json!("found")) })
+            })
+        }
+
+        fn limited_run(max_iterations: usize) -> Transcript {
+            let mut transcript = Transcript::new("Solar", AgentProvider::OpenAI, "scripted", now_ms());
+            transcript.limits = RunLimits { max_iterations, max_tool_calls: 0, max_tokens: 0, ..RunLimits::default() };
+            transcript
+        }
+
+        #[tokio::test]
+        async fn a_run_at_its_limit_gets_a_last_turn_to_answer() {
+            static TRANSCRIPT: Lazy<Mutex<Option<Transcript>>> = Lazy::new(|| Mutex::new(None));
+            *TRANSCRIPT.lock().unwrap() = Some(limited_run(1));
+
+            let model = ScriptedLlm::new(vec![
+                call("lookup"),
+                reply("Solar is cheap now.", TokenUsage::new(1500, 20, 1520)),
+            ]);
+            let prompts = model.last_messages.clone();
+            let answer = LlmWorker::<String>::builder(RecordedLlm::recording_into(model, &TRANSCRIPT))
+                .with_tool(lookup_tool())
+                .with_max_iterations(3)
+                .build()
+                .run(Thread::from_user("Research solar"))
+                .await
+                .unwrap();
+            assert_eq!(answer, "Solar is cheap now.");
+            let prompts = prompts.lock().unwrap();
+            assert!(prompts[1].starts_with("Run limit reached: the limit of 1 model turns."));
+
+            let transcript = TRANSCRIPT.lock().unwrap().take().unwrap();
+            assert_eq!(transcript.stopped_for.as_deref(), Some("the limit of 1 model turns"));
+            let usage = transcript.usage();
+            assert_eq!(usage.iterations, 2);
+            assert_eq!((usage.input_tokens, usage.output_tokens), (2700, 60));
+            assert!(!usage.estimated);
+        }
+
+        #[tokio::test]
+        async fn a_run_still_calling_tools_after_its_last_turn_ends_without_asking_again() {
+            static TRANSCRIPT: Lazy<Mutex<Option<Transcript>>> = Lazy::new(|| Mutex::new(None));
+            *TRANSCRIPT.lock().unwrap() = Some(limited_run(1));
+
+            let model = ScriptedLlm::new(vec![call("lookup"), call("lookup")]);
+            let answer = LlmWorker::<String>::builder(RecordedLlm::recording_into(model, &TRANSCRIPT))
+                .with_tool(lookup_tool())
+                .with_max_iterations(3)
+                .build()
+                .run(Thread::from_user("Research solar"))
+                .await
+                .unwrap();
+            assert_eq!(answer, "No final answer was given before the run stopped.");
+            let transcript = TRANSCRIPT.lock().unwrap().take().unwrap();
+            assert_eq!(transcript.usage().iterations, 2);
+        }
+    }
+}
+
+// ============================================================================
+// SERVER FUNCTIONS (Dioxus RPC)
+// ============================================================================
+
+#[server]
+pub async fn list_transcripts() -> Result<Vec<TranscriptSummary>, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        Ok(store::list().await)
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Summary of the run in progress, for live usage while the agent works
+#[server]
+pub async fn current_run() -> Result<Option<TranscriptSummary>, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        use crate::server_state::CURRENT_TRANSCRIPT;
+        Ok(CURRENT_TRANSCRIPT
+            .lock()
+            .ok()
+            .and_then(|c| c.as_ref().map(Transcript::summary)))
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+#[server]
+pub async fn get_transcript(id: Uuid) -> Result<Transcript, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        store::load(id)
+            .await
+            .ok_or_else(|| ServerFnError::new(format!("No transcript {}", id)))
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+/// Download a run's transcript as `transcript-<id>.json`
+#[get("/api/transcript?id")]
+pub async fn download_transcript(id: Uuid) -> Result<Response, ServerFnError> {
+    #[cfg(feature = "server")]
+    {
+        use crate::export::attachment;
+        let transcript = store::load(id)
+            .await
+            .ok_or_else(|| ServerFnError::new(format!("No transcript {}", id)))?;
+        let json = serde_json::to_string_pretty(&transcript)
+            .map_err(|e| ServerFnError::new(format!("Serialization failed: {}", e)))?;
+        attachment(json.into_bytes(), "application/json", &format!("transcript-{}.json", id))
+    }
+    #[cfg(not(feature = "server"))]
+    Err(ServerFnError::new("Not on server"))
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(input_tokens: Option<u32>, output_tokens: Option<u32>) -> TranscriptEntry {
+        TranscriptEntry::ModelMessage {
+            text: "Looking this up".to_string(),
+            tool_calls: vec!["web_search".to_string()],
+            input_tokens,
+            output_tokens,
+            at_ms: 0,
+            duration_ms: 0,
+        }
+    }
+
+    fn tool_call(refused: bool) -> TranscriptEntry {
+        TranscriptEntry::ToolCall {
+            name: "web_search".to_string(),
+            args: json!({ "query": "solar" }),
+            result: json!("results"),
+            at_ms: 0,
+            duration_ms: 0,
+            refused,
+        }
+    }
+
+    fn run(limits: RunLimits, entries: Vec<TranscriptEntry>) -> Transcript {
+        let mut transcript = Transcript::new("Solar", AgentProvider::OpenAI, "gpt", 0);
+        transcript.limits = limits;
+        transcript.entries = entries;
+        transcript
+    }
+
+    fn no_limits() -> RunLimits {
+        RunLimits { max_iterations: 0, max_tool_calls: 0, max_tokens: 0, ..RunLimits::default() }
+    }
+
+    #[test]
+    fn usage_comes_from_reported_tokens_and_skips_refused_calls() {
+        let limits = RunLimits { input_price: 2.0, output_price: 10.0, ..no_limits() };
+        let transcript = run(
+            limits,
+            vec![
+                TranscriptEntry::Prompt { text: "Research solar".to_string() },
+                message(Some(1_000), Some(50)),
+                tool_call(false),
+                tool_call(true),
+                message(Some(3_000), Some(150)),
+            ],
+        );
+        let usage = transcript.usage();
+        assert_eq!((usage.iterations, usage.tool_calls), (2, 1));
+        assert_eq!((usage.input_tokens, usage.output_tokens), (4_000, 200));
+        assert!((usage.cost - 0.01).abs() < 1e-9);
+        assert!(!usage.estimated);
+        assert_eq!(usage.describe(), "2 turns · 1 tool calls · 4200 tokens · $0.01");
+    }
+
+    #[test]
+    fn turns_without_reported_usage_are_estimated() {
+        let transcript = run(
+            no_limits(),
+            vec![TranscriptEntry::Prompt { text: "a".repeat(400) }, message(None, Some(7))],
+        );
+        let usage = transcript.usage();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (100, 7));
+        assert!(usage.estimated);
+        assert_eq!(usage.describe(), "1 turns · 0 tool calls · ~107 tokens");
+    }
+
+    #[test]
+    fn reaching_a_limit_leaves_one_last_turn() {
+        let limits = RunLimits { max_iterations: 2, ..no_limits() };
+        let mut transcript = run(limits, vec![message(Some(10), Some(1))]);
+        assert_eq!(transcript.begin_turn(), TurnBudget::Open);
+        assert_eq!(transcript.refuse_call(), None);
+
+        transcript.entries.push(message(Some(10), Some(1)));
+        let reason = "the limit of 2 model turns".to_string();
+        assert_eq!(transcript.begin_turn(), TurnBudget::Last(reason.clone()));
+        assert_eq!(transcript.refuse_call(), Some(reason.clone()));
+        assert_eq!(transcript.begin_turn(), TurnBudget::Spent(reason));
+    }
+
+    #[test]
+    fn token_and_cost_limits_use_reported_usage() {
+        let limits = RunLimits { max_tokens: 1_000, ..no_limits() };
+        let mut transcript = run(limits, vec![message(Some(990), Some(10))]);
+        assert_eq!(transcript.begin_turn(), TurnBudget::Last("the limit of 1000 tokens".to_string()));
+
+        let limits = RunLimits { max_cost: 0.5, input_price: 1.0, ..no_limits() };
+        let mut transcript = run(limits, vec![message(Some(400_000), Some(0))]);
+        assert_eq!(transcript.begin_turn(), TurnBudget::Open);
+        transcript.entries.push(message(Some(100_000), Some(0)));
+        assert_eq!(transcript.begin_turn(), TurnBudget::Last("the limit of $0.50".to_string()));
+    }
+
+    #[test]
+    fn refused_calls_do_not_use_up_the_tool_call_limit() {
+        let limits = RunLimits { max_tool_calls: 2, ..no_limits() };
+        let mut transcript = run(limits, vec![tool_call(false), tool_call(true)]);
+        assert_eq!(transcript.refuse_call(), None);
+        transcript.entries.push(tool_call(false));
+        assert_eq!(transcript.refuse_call(), Some("the limit of 2 tool calls".to_string()));
+    }
+}